@@ -0,0 +1,318 @@
+// Actor classes a mod declares, for an overview like "adds 14 new
+// monsters". Only the declaration headers are read, never the bodies'
+// code:
+//
+//     actor FireImp : DoomImp replaces DoomImp 30001    // DECORATE
+//     class PlasmaZombie : ZombieMan { Default { Monster; } }   // ZSCRIPT
+//
+// along with `#include "path"` lines, which are followed to other lumps
+// (WAD) or entries (PK3) of the same file. A class counts as a monster
+// when it sets the Monster combo or +ISMONSTER, or inherits from a class
+// that does or from a stock Doom monster.
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::game_archives;
+use crate::wad_parser::{self, WadType};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Decorate,
+    Zscript,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActorClass {
+    pub name: String,
+    pub parent: Option<String>,
+    /// The stock class this one replaces in game.
+    pub replaces: Option<String>,
+    /// The editor number maps place it by (DECORATE only).
+    pub doomednum: Option<u32>,
+    pub language: Language,
+    /// Lump name, or entry path in a PK3.
+    pub lump: String,
+    pub is_monster: bool,
+}
+
+/// Doom's monsters, which a mod's monster usually inherits from.
+const STOCK_MONSTERS: &[&str] = &[
+    "zombieman", "shotgunguy", "chaingunguy", "doomimp", "demon", "spectre", "lostsoul", "cacodemon",
+    "hellknight", "baronofhell", "arachnotron", "painelemental", "revenant", "fatso", "archvile",
+    "spidermastermind", "cyberdemon", "wolfensteinss", "commanderkeen", "bossbrain",
+];
+
+/// Includes followed per file, so a cycle or a runaway chain ends.
+const MAX_SOURCES: usize = 256;
+
+#[derive(Debug, PartialEq)]
+struct Tok {
+    text: String,
+    quoted: bool,
+}
+
+impl Tok {
+    fn is(&self, word: &str) -> bool {
+        !self.quoted && self.text.eq_ignore_ascii_case(word)
+    }
+}
+
+/// Words, "strings" and the punctuation { } : ; ( ) , = , without `//` and
+/// `/* */` comments. Unlike mapinfo::tokenize, `;` ends a statement here.
+fn tokenize(src: &str) -> Vec<Tok> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && chars[i] != '"' {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            out.push(Tok { text: chars[start..i.min(chars.len())].iter().collect(), quoted: true });
+            i += 1;
+        } else if "{}:;(),=".contains(c) {
+            out.push(Tok { text: c.to_string(), quoted: false });
+            i += 1;
+        } else {
+            let ends_word = |j: usize| {
+                chars[j].is_whitespace()
+                    || "{}:;(),=\"".contains(chars[j])
+                    || (chars[j] == '/' && matches!(chars.get(j + 1), Some('/' | '*')))
+            };
+            let start = i;
+            while i < chars.len() && !ends_word(i) {
+                i += 1;
+            }
+            out.push(Tok { text: chars[start..i].iter().collect(), quoted: false });
+        }
+    }
+    out
+}
+
+/// The index just past the `}` matching the `{` at `open`.
+fn block_end(tokens: &[Tok], open: usize) -> usize {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        if token.is("{") {
+            depth += 1;
+        } else if token.is("}") {
+            depth -= 1;
+            if depth == 0 {
+                return i + 1;
+            }
+        }
+    }
+    tokens.len()
+}
+
+fn sets_monster(flags: &[Tok]) -> bool {
+    flags.iter().any(|t| t.is("monster") || t.is("+ismonster"))
+}
+
+/// The classes one lump declares and the paths it #includes.
+fn parse_source(src: &str, language: Language, lump: &str) -> (Vec<ActorClass>, Vec<String>) {
+    let tokens = tokenize(src);
+    let keyword = match language {
+        Language::Decorate => "actor",
+        Language::Zscript => "class",
+    };
+    let mut classes = Vec::new();
+    let mut includes = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        if token.is("#include") && let Some(path) = tokens.get(i + 1).filter(|t| t.quoted) {
+            includes.push(path.text.clone());
+            i += 2;
+        } else if token.is("{") {
+            // Structs, enums, `extend class` and the like.
+            i = block_end(&tokens, i);
+        } else if token.is(keyword) && !(i > 0 && (tokens[i - 1].is("extend") || tokens[i - 1].is("mixin"))) {
+            let Some(name) = tokens.get(i + 1) else { break };
+            let header_end = tokens[i..].iter().position(|t| t.is("{") || t.is(";")).map_or(tokens.len(), |n| i + n);
+            let header = &tokens[i + 2..header_end];
+            let after = |word: &str| header.iter().position(|t| t.is(word)).and_then(|at| header.get(at + 1)).map(|t| t.text.clone());
+            // The tokens between the braces, and where the declaration ends.
+            let (body, next) = if tokens.get(header_end).is_some_and(|t| t.is("{")) {
+                let end = block_end(&tokens, header_end);
+                (&tokens[header_end + 1..end.max(header_end + 2) - 1], end)
+            } else {
+                (&tokens[header_end..header_end], header_end + 1)
+            };
+            let flags = match language {
+                Language::Decorate => body,
+                Language::Zscript => match body.windows(2).position(|w| w[0].is("default") && w[1].is("{")) {
+                    Some(at) => &body[at + 2..block_end(body, at + 1).max(at + 3) - 1],
+                    None => &[],
+                },
+            };
+            classes.push(ActorClass {
+                name: name.text.clone(),
+                parent: after(":"),
+                replaces: after("replaces"),
+                doomednum: match language {
+                    Language::Decorate => header.iter().find_map(|t| t.text.parse().ok().filter(|_| !t.quoted)),
+                    Language::Zscript => None,
+                },
+                language,
+                lump: lump.to_string(),
+                is_monster: sets_monster(flags),
+            });
+            i = next;
+        } else {
+            i += 1;
+        }
+    }
+    (classes, includes)
+}
+
+/// A lump's text from `path` by the name an #include gives: an entry path
+/// in a PK3, a lump name (the path's basename without extension) in a WAD.
+/// None when it isn't there.
+fn read_include(path: &str, kind: WadType, include: &str, entries: &[String]) -> Result<Option<(String, String)>, String> {
+    match kind {
+        WadType::Iwad | WadType::Pwad => {
+            let name = wad_parser::zip_entry_lump_name(include);
+            let lump = wad_parser::collect_lumps(path, &|n| n == name)?.pop();
+            Ok(lump.map(|l| (l.name, String::from_utf8_lossy(&l.data).into_owned())))
+        }
+        _ => {
+            let wanted = include.replace('\\', "/").trim_start_matches('/').to_lowercase();
+            match entries.iter().find(|e| e.to_lowercase() == wanted) {
+                Some(entry) => Ok(Some((entry.clone(), String::from_utf8_lossy(&game_archives::read_zip_entry(path, entry)?).into_owned()))),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+/// The actor classes a WAD/PK3 declares in DECORATE and ZSCRIPT, in the
+/// order they are read: each root lump, then what it includes. Includes
+/// that can't be found are logged and skipped.
+pub fn list_actor_classes(path: &str) -> Result<Vec<ActorClass>, String> {
+    let kind = wad_parser::detect_wad_type(path)?;
+    let mut pending: Vec<(Language, String, String)> = Vec::new();
+    let mut entries = Vec::new();
+    match kind {
+        WadType::Iwad | WadType::Pwad => {
+            for lump in wad_parser::collect_lumps(path, &|n| n == "DECORATE" || n == "ZSCRIPT")? {
+                let language = if lump.name == "DECORATE" { Language::Decorate } else { Language::Zscript };
+                pending.push((language, lump.name, String::from_utf8_lossy(&lump.data).into_owned()));
+            }
+        }
+        WadType::Pk3 | WadType::Ipk3 | WadType::Zip => {
+            entries = game_archives::list_zip_entries(path)?.into_iter().map(|e| e.path).collect();
+            for entry in entries.iter().filter(|e| !e.contains(['/', '\\'])) {
+                let language = match wad_parser::zip_entry_lump_name(entry).as_str() {
+                    "DECORATE" => Language::Decorate,
+                    "ZSCRIPT" => Language::Zscript,
+                    _ => continue,
+                };
+                let text = String::from_utf8_lossy(&game_archives::read_zip_entry(path, entry)?).into_owned();
+                pending.push((language, entry.clone(), text));
+            }
+        }
+        WadType::Unknown => return Err(format!("Not a WAD or ZIP/PK3 file: {}", path)),
+    }
+
+    let mut seen: HashSet<String> = pending.iter().map(|(_, lump, _)| lump.to_lowercase()).collect();
+    let mut classes = Vec::new();
+    pending.reverse();
+    while let Some((language, lump, text)) = pending.pop() {
+        let (found, includes) = parse_source(&text, language, &lump);
+        classes.extend(found);
+        for include in includes.into_iter().rev() {
+            if seen.len() >= MAX_SOURCES {
+                eprintln!("{}: more than {} DECORATE/ZSCRIPT sources, stopping at {}", path, MAX_SOURCES, include);
+                break;
+            }
+            match read_include(path, kind, &include, &entries)? {
+                Some((name, text)) if seen.insert(name.to_lowercase()) => pending.push((language, name, text)),
+                Some(_) => {}
+                None => eprintln!("{}: #include \"{}\" in {} not found", path, include, lump),
+            }
+        }
+    }
+
+    // Inherited monster status, through the mod's own classes as far as
+    // they go. Each pass settles one more level of the hierarchy.
+    loop {
+        let monsters: HashSet<String> = classes.iter().filter(|c| c.is_monster).map(|c| c.name.to_lowercase()).collect();
+        let mut changed = false;
+        for class in classes.iter_mut().filter(|c| !c.is_monster) {
+            if let Some(parent) = class.parent.as_ref().map(|p| p.to_lowercase())
+                && (monsters.contains(&parent) || STOCK_MONSTERS.contains(&parent.as_str()))
+            {
+                class.is_monster = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            return Ok(classes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, build_zip, write_temp};
+
+    fn summary(classes: &[ActorClass]) -> Vec<(&str, Option<&str>, bool)> {
+        classes.iter().map(|c| (c.name.as_str(), c.parent.as_deref(), c.is_monster)).collect()
+    }
+
+    #[test]
+    fn reads_decorate_headers_and_includes() {
+        let decorate = b"#include \"DECOMON\"\n// actor Commented : DoomImp\nactor FireImp : DoomImp replaces DoomImp 30001\n{\n  Health 80\n}\n";
+        let monsters = b"ACTOR Brute 30002 {\n  Monster\n  States { Spawn: TROO A 10 Loop }\n}\nactor BigBrute:Brute {}\nactor Ammo1 : Clip {}\n#include \"DECORATE\"\n";
+        let wad = write_temp("actors.wad", &build_wad(b"PWAD", &[("DECORATE", decorate), ("DECOMON", monsters)]));
+        let classes = list_actor_classes(&wad).unwrap();
+        assert_eq!(
+            summary(&classes),
+            vec![("FireImp", Some("DoomImp"), true), ("Brute", None, true), ("BigBrute", Some("Brute"), true), ("Ammo1", Some("Clip"), false)]
+        );
+        assert_eq!((classes[0].replaces.as_deref(), classes[0].doomednum), (Some("DoomImp"), Some(30001)));
+        assert_eq!((classes[1].doomednum, classes[1].lump.as_str()), (Some(30002), "DECOMON"));
+    }
+
+    #[test]
+    fn reads_zscript_classes_in_a_pk3() {
+        let root = b"version \"4.10\"\n#include \"zscript/monsters.zs\"\nextend class DoomImp { int x; }\nstruct Data { int y; }\n";
+        let monsters = b"class PlasmaZombie : Actor replaces ZombieMan\n{\n  Default { Monster; Health 60; }\n  override void Tick() { Super.Tick(); }\n}\nclass Handler : EventHandler {}\nclass MegaZombie : PlasmaZombie {}\n";
+        let pk3 = write_temp("actors.pk3", &build_zip(&[("zscript.txt", root), ("zscript/monsters.zs", monsters)]));
+        let classes = list_actor_classes(&pk3).unwrap();
+        assert_eq!(
+            summary(&classes),
+            vec![("PlasmaZombie", Some("Actor"), true), ("Handler", Some("EventHandler"), false), ("MegaZombie", Some("PlasmaZombie"), true)]
+        );
+        assert_eq!((classes[0].language, classes[0].lump.as_str()), (Language::Zscript, "zscript/monsters.zs"));
+        assert_eq!(classes[0].replaces.as_deref(), Some("ZombieMan"));
+    }
+
+    #[test]
+    fn survives_unclosed_zscript_blocks() {
+        for src in ["class Foo : Actor { default { }", "class Foo : Actor { default { Monster;", "class Foo : Actor {"] {
+            let (classes, _) = parse_source(src, Language::Zscript, "zscript");
+            assert_eq!(summary(&classes), vec![("Foo", Some("Actor"), false)], "{}", src);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+// Backups of GZDoom's config and saves, taken before trying a mod that
+// might wreck them. A backup is one zip, gzdoom-backup-<UTC time>.zip,
+// holding each backed-up folder or file under a numbered root next to a
+// backup.json manifest that records where each root came from:
+//
+//     backup.json         {"version":1,"roots":[{"name":"0-gzdoom","path":"/home/me/.config/gzdoom"}]}
+//     0-gzdoom/gzdoom.ini
+//     0-gzdoom/savegames/save00.zds
+//
+// Restoring puts every file back where the manifest says, but only into
+// GZDoom's own folders or ones the caller names, since the manifest comes
+// from whoever made the zip. It refuses to replace existing files unless
+// told to, and plan_restore lists the targets first so the UI can ask.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::timestamp;
+use zip::ZipArchive;
+use zip::write::SimpleFileOptions;
+
+const MANIFEST: &str = "backup.json";
+
+/// Start of the error restore_gzdoom_data returns when files would be
+/// replaced and overwriting wasn't allowed.
+pub const WOULD_OVERWRITE: &str = "Restoring would overwrite";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackupRoot {
+    /// Top-level name in the zip.
+    pub name: String,
+    /// The folder or file it was taken from.
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackupManifest {
+    pub version: u8,
+    pub roots: Vec<BackupRoot>,
+}
+
+/// GZDoom's default config and save locations on this platform. Not all
+/// exist; portable installs keep everything next to the executable instead
+/// and are backed up by passing that folder.
+#[cfg(target_os = "linux")]
+pub fn data_paths() -> Vec<PathBuf> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else { return Vec::new() };
+    let config = std::env::var_os("XDG_CONFIG_HOME").map_or_else(|| home.join(".config"), PathBuf::from);
+    vec![config.join("gzdoom"), home.join(".var/app/org.zdoom.GZDoom/.config/gzdoom")]
+}
+
+#[cfg(target_os = "macos")]
+pub fn data_paths() -> Vec<PathBuf> {
+    let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else { return Vec::new() };
+    vec![home.join("Library/Preferences/gzdoom.ini"), home.join("Documents/GZDoom")]
+}
+
+#[cfg(target_os = "windows")]
+pub fn data_paths() -> Vec<PathBuf> {
+    let Some(profile) = std::env::var_os("USERPROFILE").map(PathBuf::from) else { return Vec::new() };
+    vec![profile.join("Documents").join("My Games").join("GZDoom"), profile.join("Saved Games").join("GZDoom")]
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn data_paths() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// "20261014-153000" for a backup's file name, in UTC.
+fn utc_stamp(time: SystemTime) -> String {
+    let t = timestamp::utc(time);
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}", t.year, t.month, t.day, t.hour, t.minute, t.second)
+}
+
+/// Every regular file under `dir`, relative to it with `/` separators.
+/// Symlinks are skipped rather than followed out of the folder.
+fn files_under(dir: &Path, prefix: &str, out: &mut Vec<(String, PathBuf)>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    for entry in entries.flatten() {
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => files_under(&entry.path(), &format!("{}/", name), out)?,
+            Ok(kind) if kind.is_file() => out.push((name, entry.path())),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Zip `sources` (folders or files; data_paths() that exist when empty)
+/// into a new timestamped backup in `dest_dir`. Returns the zip's path.
+pub fn backup_gzdoom_data(dest_dir: &str, sources: &[String]) -> Result<String, String> {
+    let dest_dir = Path::new(dest_dir);
+    if !dest_dir.is_dir() {
+        return Err(format!("Backup folder not found: {}", dest_dir.display()));
+    }
+    let sources: Vec<PathBuf> = if sources.is_empty() {
+        data_paths().into_iter().filter(|p| p.exists()).collect()
+    } else {
+        sources.iter().map(PathBuf::from).collect()
+    };
+    if sources.is_empty() {
+        return Err("No GZDoom config or save folder found to back up".to_string());
+    }
+    let dest_real = dest_dir.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", dest_dir.display(), e))?;
+    for source in &sources {
+        let real = source.canonicalize().map_err(|_| format!("Nothing to back up at {}", source.display()))?;
+        if real.is_dir() && dest_real.starts_with(&real) {
+            return Err(format!("The backup can't be stored inside {}, which it backs up", source.display()));
+        }
+    }
+
+    let path = dest_dir.join(format!("gzdoom-backup-{}.zip", utc_stamp(SystemTime::now())));
+    if path.exists() {
+        return Err(format!("{} already exists", path.display()));
+    }
+    let mut roots = Vec::new();
+    let mut files = Vec::new();
+    for (i, source) in sources.iter().enumerate() {
+        let base = source.file_name().map_or_else(|| "root".to_string(), |n| n.to_string_lossy().to_string());
+        let name = format!("{}-{}", i, base);
+        if source.is_dir() {
+            files_under(source, &format!("{}/", name), &mut files)?;
+        } else {
+            files.push((name.clone(), source.clone()));
+        }
+        roots.push(BackupRoot { name, path: source.to_string_lossy().to_string() });
+    }
+
+    let write = || -> Result<(), String> {
+        let file = File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let manifest = serde_json::to_vec_pretty(&BackupManifest { version: 1, roots })
+            .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+        zip.start_file(MANIFEST, options).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        io::Write::write_all(&mut zip, &manifest).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        for (name, source) in &files {
+            let mut input = File::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+            zip.start_file(name.as_str(), options).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            io::copy(&mut input, &mut zip).map_err(|e| format!("Failed to back up {}: {}", source.display(), e))?;
+        }
+        zip.finish().map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(())
+    };
+    if let Err(e) = write() {
+        // A half-written backup would look like a good one later.
+        if let Err(remove) = fs::remove_file(&path) {
+            eprintln!("Failed to remove incomplete backup {}: {}", path.display(), remove);
+        }
+        return Err(e);
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// A file a restore would write.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreTarget {
+    pub path: String,
+    /// The restore would replace a file already there.
+    pub exists: bool,
+}
+
+/// Whether a manifest root may be restored to: an absolute path without
+/// ".." that is one of `allowed` or inside one.
+fn root_allowed(root: &Path, allowed: &[PathBuf]) -> bool {
+    root.is_absolute()
+        && !root.components().any(|c| c == std::path::Component::ParentDir)
+        && allowed.iter().any(|dir| root.starts_with(dir))
+}
+
+/// (index of the zip entry, the file it is restored to).
+type Target = (usize, PathBuf);
+
+/// The backup's entries with where each goes. Every root must be in
+/// data_paths() or `allowed_dirs`, or the whole backup is refused.
+fn restore_targets(zip_path: &str, allowed_dirs: &[String]) -> Result<(ZipArchive<File>, Vec<Target>), String> {
+    let file = File::open(zip_path).map_err(|e| format!("Failed to open {}: {}", zip_path, e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Failed to read ZIP {}: {}", zip_path, e))?;
+    let manifest: BackupManifest = {
+        let entry = archive.by_name(MANIFEST).map_err(|_| format!("{} is not a GZDoom backup (no {})", zip_path, MANIFEST))?;
+        serde_json::from_reader(entry).map_err(|e| format!("Failed to parse the manifest of {}: {}", zip_path, e))?
+    };
+    let allowed: Vec<PathBuf> = data_paths().into_iter().chain(allowed_dirs.iter().map(PathBuf::from)).collect();
+    if let Some(root) = manifest.roots.iter().find(|r| !root_allowed(Path::new(&r.path), &allowed)) {
+        return Err(format!("Refusing to restore {}: {} is not a GZDoom config or save folder", zip_path, root.path));
+    }
+
+    let mut targets: Vec<Target> = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| format!("Failed to read ZIP entry #{}: {}", i, e))?;
+        if entry.is_dir() || entry.name() == MANIFEST {
+            continue;
+        }
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Refusing to restore {}: path escapes the backup", entry.name()))?;
+        let mut parts = relative.components();
+        let root_name = parts.next().map(|c| c.as_os_str().to_string_lossy().to_string()).unwrap_or_default();
+        let root = manifest
+            .roots
+            .iter()
+            .find(|r| r.name == root_name)
+            .ok_or_else(|| format!("{} in {} belongs to no backed-up folder", entry.name(), zip_path))?;
+        let rest = parts.as_path();
+        let target = if rest.as_os_str().is_empty() { PathBuf::from(&root.path) } else { Path::new(&root.path).join(rest) };
+        targets.push((i, target));
+    }
+    Ok((archive, targets))
+}
+
+/// The files restoring the backup at `zip_path` would write, for the UI
+/// to show before restore_gzdoom_data; fails as that does on roots outside
+/// data_paths() and `allowed_dirs`.
+pub fn plan_restore(zip_path: &str, allowed_dirs: &[String]) -> Result<Vec<RestoreTarget>, String> {
+    let (_, targets) = restore_targets(zip_path, allowed_dirs)?;
+    Ok(targets
+        .into_iter()
+        .map(|(_, target)| RestoreTarget { exists: target.exists(), path: target.to_string_lossy().to_string() })
+        .collect())
+}
+
+/// Put the files of the backup at `zip_path` back where they were taken
+/// from, as plan_restore lists them. A backup with a folder outside GZDoom's
+/// own (data_paths()) and `allowed_dirs` is refused. Unless `overwrite`,
+/// fails with WOULD_OVERWRITE and the files in the way before writing
+/// anything. Returns the restored paths.
+pub fn restore_gzdoom_data(zip_path: &str, allowed_dirs: &[String], overwrite: bool) -> Result<Vec<String>, String> {
+    let (mut archive, targets) = restore_targets(zip_path, allowed_dirs)?;
+    if !overwrite {
+        let existing: Vec<String> = targets.iter().filter(|(_, t)| t.exists()).map(|(_, t)| t.display().to_string()).collect();
+        if !existing.is_empty() {
+            let shown: Vec<&str> = existing.iter().take(5).map(String::as_str).collect();
+            let more = if existing.len() > shown.len() { format!(" and {} more", existing.len() - shown.len()) } else { String::new() };
+            return Err(format!("{} {} file(s): {}{}", WOULD_OVERWRITE, existing.len(), shown.join(", "), more));
+        }
+    }
+
+    let mut restored = Vec::new();
+    for (i, target) in targets {
+        let mut entry = archive.by_index(i).map_err(|e| format!("Failed to read ZIP entry #{}: {}", i, e))?;
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut out = File::create(&target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        io::copy(&mut entry, &mut out).map_err(|e| format!("Failed to restore {}: {}", target.display(), e))?;
+        restored.push(target.to_string_lossy().to_string());
+    }
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn formats_utc_stamps() {
+        assert_eq!(utc_stamp(UNIX_EPOCH), "19700101-000000");
+        assert_eq!(utc_stamp(UNIX_EPOCH + std::time::Duration::from_secs(1_792_000_000)), "20261014-174640");
+    }
+
+    #[test]
+    fn backs_up_and_restores_without_clobbering() {
+        let root = std::env::temp_dir().join(format!("backup_test_{}", std::process::id()));
+        let data = root.join("gzdoom");
+        fs::create_dir_all(data.join("savegames")).unwrap();
+        fs::write(data.join("gzdoom.ini"), b"[GlobalSettings]").unwrap();
+        fs::write(data.join("savegames/save00.zds"), b"save").unwrap();
+        let extra = root.join("extra.ini");
+        fs::write(&extra, b"extra").unwrap();
+        let dest = root.join("backups");
+        fs::create_dir_all(&dest).unwrap();
+        let sources = [data.to_string_lossy().to_string(), extra.to_string_lossy().to_string()];
+
+        let inside = backup_gzdoom_data(&data.join("savegames").to_string_lossy(), &sources).unwrap_err();
+        assert!(inside.contains("can't be stored inside"), "{}", inside);
+        let zip = backup_gzdoom_data(&dest.to_string_lossy(), &sources).unwrap();
+        assert!(Path::new(&zip).file_name().unwrap().to_string_lossy().starts_with("gzdoom-backup-"));
+
+        fs::write(data.join("gzdoom.ini"), b"corrupted by a mod").unwrap();
+        fs::remove_file(data.join("savegames/save00.zds")).unwrap();
+        let elsewhere = restore_gzdoom_data(&zip, &[dest.to_string_lossy().to_string()], true).unwrap_err();
+        assert!(elsewhere.contains("is not a GZDoom config or save folder"), "{}", elsewhere);
+        assert!(!data.join("savegames/save00.zds").exists());
+        let planned = plan_restore(&zip, &sources).unwrap();
+        let exists: Vec<bool> = planned.iter().map(|t| t.exists).collect();
+        assert_eq!((planned.len(), exists.iter().filter(|e| **e).count()), (3, 2));
+
+        let refused = restore_gzdoom_data(&zip, &sources, false).unwrap_err();
+        assert!(refused.starts_with(WOULD_OVERWRITE) && refused.contains("2 file(s)"), "{}", refused);
+        assert!(!data.join("savegames/save00.zds").exists());
+
+        let mut restored = restore_gzdoom_data(&zip, &sources, true).unwrap();
+        restored.sort();
+        assert_eq!(restored.len(), 3);
+        assert_eq!(fs::read(data.join("gzdoom.ini")).unwrap(), b"[GlobalSettings]");
+        assert_eq!(fs::read(data.join("savegames/save00.zds")).unwrap(), b"save");
+        assert_eq!(fs::read(&extra).unwrap(), b"extra");
+    }
+}
@@ -0,0 +1,155 @@
+// Mod bundles: a WAD or PK3 together with the files released alongside it
+// under the same name, the way idgames uploads unpack:
+//
+//     scythe2.wad   the maps
+//     scythe2.deh   DEHACKED patch the maps rely on
+//     scythe2.txt   description
+//     scythe2.gwa   GL nodes, built by glBSP for old GL ports
+//
+// Loading only the WAD silently drops the patch, so a bundle keeps them
+// together and a launch config can take the whole bundle. A .gwa is not
+// loaded with -file: the GL ports that use one find it next to the WAD by
+// name, so it is only reported.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::wad_parser::{self, WadType};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModBundle {
+    /// The WAD/PK3 that is loaded with -file.
+    pub primary: String,
+    /// Companion .deh and .bex patches, .deh first.
+    #[serde(default)]
+    pub dehacked: Vec<String>,
+    /// Companion text file, for the description; never loaded.
+    pub text: Option<String>,
+    /// Companion .gwa holding GL nodes.
+    #[serde(default)]
+    pub gwa: Option<String>,
+    /// Where the WAD's GL nodes are, if it has any.
+    #[serde(default)]
+    pub gl_nodes: Option<GlNodes>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GlNodes {
+    /// GL_ lumps in the WAD itself.
+    Internal,
+    /// Only in the companion .gwa.
+    External,
+}
+
+/// Bundles for the files directly in `dir`, grouped by file stem
+/// (case-insensitively) and sorted by it. Every PWAD, PK3 or ZIP starts a
+/// bundle; IWADs don't, and patches, text or a .gwa without a WAD of the
+/// same name belong to no bundle. Two loadable files with one stem (a .wad and a
+/// .pk3) are two bundles sharing the companions.
+pub fn scan_bundles(dir: &str) -> Result<Vec<ModBundle>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir, e))?;
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(stem) = path.file_stem() else { continue };
+        groups.entry(stem.to_string_lossy().to_lowercase()).or_default().push(path.to_string_lossy().to_string());
+    }
+
+    let mut bundles = Vec::new();
+    for mut files in groups.into_values() {
+        files.sort_by_key(|path| path.to_lowercase());
+        let with_extension = |ext: &str| -> Vec<String> {
+            files.iter().filter(|path| extension(path) == ext).cloned().collect()
+        };
+        let mut dehacked = with_extension("deh");
+        dehacked.extend(with_extension("bex"));
+        let text = with_extension("txt").into_iter().next();
+        let gwa = with_extension("gwa").into_iter().next();
+        for path in &files {
+            if matches!(extension(path).as_str(), "deh" | "bex" | "txt" | "gwa") {
+                continue;
+            }
+            match wad_parser::detect_wad_type(path) {
+                Ok(kind @ (WadType::Pwad | WadType::Pk3 | WadType::Zip)) => bundles.push(ModBundle {
+                    primary: path.clone(),
+                    dehacked: dehacked.clone(),
+                    text: text.clone(),
+                    gwa: gwa.clone(),
+                    gl_nodes: gl_nodes(path, kind, gwa.is_some()),
+                }),
+                Ok(_) => {}
+                Err(e) => eprintln!("Skipping {} while scanning for bundles: {}", path, e),
+            }
+        }
+    }
+    Ok(bundles)
+}
+
+/// GL nodes are GL_ lumps (GL_MAP01, GL_VERT, …) built by glBSP or a node
+/// builder into the WAD, or the same lumps in a .gwa beside it. Only WADs
+/// have them; a lump directory that can't be read is logged and counts as
+/// having none inside.
+fn gl_nodes(path: &str, kind: WadType, has_gwa: bool) -> Option<GlNodes> {
+    let internal = kind == WadType::Pwad
+        && match wad_parser::read_lumps(path) {
+            Ok(lumps) => lumps.iter().any(|lump| lump.name.starts_with("GL_")),
+            Err(e) => {
+                eprintln!("Failed to look for GL nodes in {}: {}", path, e);
+                false
+            }
+        };
+    match (internal, has_gwa && kind == WadType::Pwad) {
+        (true, _) => Some(GlNodes::Internal),
+        (false, true) => Some(GlNodes::External),
+        (false, false) => None,
+    }
+}
+
+fn extension(path: &str) -> String {
+    Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::build_wad;
+
+    #[test]
+    fn groups_companions_by_stem() {
+        let dir = std::env::temp_dir().join(format!("bundles_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pwad = build_wad(b"PWAD", &[("MAP01", b"")]);
+        std::fs::write(dir.join("scythe2.wad"), &pwad).unwrap();
+        std::fs::write(dir.join("SCYTHE2.BEX"), b"[STRINGS]\n").unwrap();
+        std::fs::write(dir.join("scythe2.deh"), b"Patch File for DeHackEd v3.0\n").unwrap();
+        std::fs::write(dir.join("scythe2.txt"), b"Scythe 2").unwrap();
+        std::fs::write(dir.join("doom2.wad"), build_wad(b"IWAD", &[("MAP01", b"")])).unwrap();
+        std::fs::write(dir.join("orphan.deh"), b"Patch File for DeHackEd v3.0\n").unwrap();
+        std::fs::write(dir.join("plain.wad"), &pwad).unwrap();
+        std::fs::write(dir.join("scythe2.gwa"), build_wad(b"PWAD", &[("GL_MAP01", b""), ("GL_VERT", b"gNd2")])).unwrap();
+        std::fs::write(dir.join("glbsp.wad"), build_wad(b"PWAD", &[("MAP01", b""), ("GL_MAP01", b"")])).unwrap();
+
+        let bundles = scan_bundles(&dir.to_string_lossy()).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        assert_eq!(
+            bundles,
+            vec![
+                ModBundle { primary: path("glbsp.wad"), gl_nodes: Some(GlNodes::Internal), ..Default::default() },
+                ModBundle { primary: path("plain.wad"), ..Default::default() },
+                ModBundle {
+                    primary: path("scythe2.wad"),
+                    dehacked: vec![path("scythe2.deh"), path("SCYTHE2.BEX")],
+                    text: Some(path("scythe2.txt")),
+                    gwa: Some(path("scythe2.gwa")),
+                    gl_nodes: Some(GlNodes::External),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,564 @@
+// Minimum source-port feature set a PWAD needs, so the launcher can warn
+// before starting an MBF21 map set in Chocolate Doom. Nothing declares this
+// reliably — COMPLVL is rare — so it is inferred from what the file uses:
+// definition lumps only ZDoom-family ports read, non-Doom map formats, and
+// line specials and flags beyond vanilla's. Each finding is kept as
+// evidence, to show why a file was rated the way it was.
+
+use serde::Serialize;
+
+use crate::engine::EngineKind;
+use crate::engine_version::{self, VersionInfo};
+use crate::mapinfo;
+use crate::wad_parser::{self, MapFormat, WadType};
+
+/// Feature levels, each a superset of the one before.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum PortFeatures {
+    Vanilla,
+    /// Boom/MBF: generalized specials, BEX patches.
+    Boom,
+    Mbf21,
+    /// ZDoom family: DECORATE, MAPINFO, Hexen-format and UDMF maps, PK3s.
+    Zdoom,
+    /// GZDoom only: ZSCRIPT, GLDEFS, models.
+    Gzdoom,
+}
+
+impl PortFeatures {
+    /// Whether `kind` runs files that need this feature level.
+    pub fn supported_by(self, kind: EngineKind) -> bool {
+        match self {
+            PortFeatures::Vanilla => true,
+            PortFeatures::Boom => kind != EngineKind::Chocolate && kind != EngineKind::Crispy,
+            PortFeatures::Mbf21 => matches!(kind, EngineKind::Dsda | EngineKind::Woof | EngineKind::GZDoom),
+            PortFeatures::Zdoom => matches!(kind, EngineKind::GZDoom | EngineKind::ZDoom | EngineKind::Zandronum),
+            PortFeatures::Gzdoom => kind == EngineKind::GZDoom,
+        }
+    }
+
+    /// Human-readable name for messages.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            PortFeatures::Vanilla => "vanilla",
+            PortFeatures::Boom => "Boom",
+            PortFeatures::Mbf21 => "MBF21",
+            PortFeatures::Zdoom => "ZDoom",
+            PortFeatures::Gzdoom => "GZDoom",
+        }
+    }
+}
+
+/// What a piece of evidence was found in.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EvidenceKind {
+    /// The file is a PK3.
+    Archive,
+    /// A COMPLVL lump, the author's own statement.
+    Complvl,
+    Dehacked,
+    /// A definition lump only some ports read.
+    PortLump,
+    /// A Hexen-format or UDMF map.
+    MapFormat,
+    /// Line specials and flags of a Doom-format map.
+    LineSpecials,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Evidence {
+    pub kind: EvidenceKind,
+    pub needs: PortFeatures,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Compatibility {
+    /// The highest level any evidence needs; Vanilla when nothing stood out.
+    pub minimum: PortFeatures,
+    pub evidence: Vec<Evidence>,
+    /// Engines that run files at `minimum`.
+    pub supported_engines: Vec<EngineKind>,
+}
+
+/// Lumps only a ZDoom-family port reads, and the level each implies.
+const PORT_LUMPS: &[(&str, PortFeatures)] = &[
+    ("ZSCRIPT", PortFeatures::Gzdoom),
+    ("GLDEFS", PortFeatures::Gzdoom),
+    ("MODELDEF", PortFeatures::Gzdoom),
+    ("DECORATE", PortFeatures::Zdoom),
+    ("ZMAPINFO", PortFeatures::Zdoom),
+    ("MAPINFO", PortFeatures::Zdoom),
+    ("KEYCONF", PortFeatures::Zdoom),
+    ("SBARINFO", PortFeatures::Zdoom),
+    ("LOCKDEFS", PortFeatures::Zdoom),
+    ("TEXTURES", PortFeatures::Zdoom),
+    ("LOADACS", PortFeatures::Zdoom),
+    ("UMAPINFO", PortFeatures::Boom),
+];
+
+/// The level a COMPLVL lump declares ("vanilla", "boom", "mbf", "mbf21").
+fn complvl_features(text: &str) -> Option<PortFeatures> {
+    match text.trim().to_lowercase().as_str() {
+        "vanilla" => Some(PortFeatures::Vanilla),
+        "boom" | "mbf" => Some(PortFeatures::Boom),
+        "mbf21" => Some(PortFeatures::Mbf21),
+        _ => None,
+    }
+}
+
+/// The level a DEHACKED patch needs: MBF21 declares itself with
+/// "Doom version = 2021"; BEX sections are Boom's extension.
+fn dehacked_features(text: &str) -> Option<(PortFeatures, &'static str)> {
+    let lower = text.to_lowercase();
+    let version_2021 = lower.lines().any(|line| {
+        line.split_once('=')
+            .is_some_and(|(key, value)| key.trim() == "doom version" && value.trim() == "2021")
+    });
+    if version_2021 {
+        Some((PortFeatures::Mbf21, "DEHACKED declares Doom version 2021 (MBF21)"))
+    } else if ["[codeptr]", "[strings]", "[pars]", "[helper]", "[sprites]", "[sounds]"]
+        .iter()
+        .any(|section| lower.contains(section))
+    {
+        Some((PortFeatures::Boom, "DEHACKED uses BEX sections"))
+    } else {
+        None
+    }
+}
+
+const MBF21_LINE_FLAGS: u16 = 0x1000 | 0x2000;
+const BOOM_LINE_FLAGS: u16 = 0x200;
+
+/// The level a Doom-format line special needs, if it is a known non-vanilla
+/// one: Boom's 142-272 and generalized range, MBF21's 2048-2056.
+fn special_features(special: u16) -> Option<PortFeatures> {
+    match special {
+        142..=272 | 0x2F80..=0x7FFF => Some(PortFeatures::Boom),
+        2048..=2056 => Some(PortFeatures::Mbf21),
+        _ => None,
+    }
+}
+
+/// Evidence from one Doom-format map's LINEDEFS (14 bytes per line: v1, v2,
+/// flags, special, tag, sides), one entry per level found.
+fn linedef_evidence(map: &str, linedefs: &[u8]) -> Vec<Evidence> {
+    let mut boom = 0;
+    let mut mbf21 = 0;
+    for line in linedefs.chunks_exact(14) {
+        let flags = u16::from_le_bytes([line[4], line[5]]);
+        let special = u16::from_le_bytes([line[6], line[7]]);
+        let level = special_features(special).max(if flags & MBF21_LINE_FLAGS != 0 {
+            Some(PortFeatures::Mbf21)
+        } else if flags & BOOM_LINE_FLAGS != 0 {
+            Some(PortFeatures::Boom)
+        } else {
+            None
+        });
+        match level {
+            Some(PortFeatures::Mbf21) => mbf21 += 1,
+            Some(_) => boom += 1,
+            None => {}
+        }
+    }
+    let mut out = Vec::new();
+    if mbf21 > 0 {
+        out.push(Evidence {
+            kind: EvidenceKind::LineSpecials,
+            needs: PortFeatures::Mbf21,
+            reason: format!("{}: {} MBF21 line(s)", map, mbf21),
+        });
+    }
+    if boom > 0 {
+        out.push(Evidence {
+            kind: EvidenceKind::LineSpecials,
+            needs: PortFeatures::Boom,
+            reason: format!("{}: {} Boom line(s)", map, boom),
+        });
+    }
+    out
+}
+
+/// Estimate the minimum port features a WAD/PK3 needs.
+pub fn detect_compatibility(path: &str) -> Result<Compatibility, String> {
+    let mut evidence = Vec::new();
+    if matches!(wad_parser::detect_wad_type(path)?, WadType::Pk3 | WadType::Ipk3) {
+        evidence.push(Evidence {
+            kind: EvidenceKind::Archive,
+            needs: PortFeatures::Zdoom,
+            reason: "PK3 archive".to_string(),
+        });
+    }
+
+    let lumps = wad_parser::collect_lumps(path, &|name| {
+        name == "COMPLVL" || name == "DEHACKED" || PORT_LUMPS.iter().any(|(lump, _)| *lump == name)
+    })?;
+    for lump in &lumps {
+        let text = String::from_utf8_lossy(&lump.data);
+        let found = match lump.name.as_str() {
+            "COMPLVL" => complvl_features(&text)
+                .map(|level| (EvidenceKind::Complvl, level, format!("COMPLVL lump: {}", text.trim()))),
+            "DEHACKED" => {
+                dehacked_features(&text).map(|(level, reason)| (EvidenceKind::Dehacked, level, reason.to_string()))
+            }
+            name => PORT_LUMPS
+                .iter()
+                .find(|(lump, _)| *lump == name)
+                .map(|(_, level)| (EvidenceKind::PortLump, *level, format!("{} lump", name))),
+        };
+        // A lump repeated in the load order is one piece of evidence.
+        if let Some((kind, needs, reason)) = found
+            && !evidence.iter().any(|e: &Evidence| e.kind == kind && e.reason == reason)
+        {
+            evidence.push(Evidence { kind, needs, reason });
+        }
+    }
+
+    for map in wad_parser::read_maps(path, &["LINEDEFS"])? {
+        match map.format {
+            Some(MapFormat::Udmf) => evidence.push(Evidence {
+                kind: EvidenceKind::MapFormat,
+                needs: PortFeatures::Zdoom,
+                reason: format!("{}: UDMF map", map.id),
+            }),
+            Some(MapFormat::Hexen) => evidence.push(Evidence {
+                kind: EvidenceKind::MapFormat,
+                needs: PortFeatures::Zdoom,
+                reason: format!("{}: Hexen-format map", map.id),
+            }),
+            Some(MapFormat::Doom) => evidence.extend(linedef_evidence(&map.id, map.data("LINEDEFS")?)),
+            None => {}
+        }
+    }
+
+    // A COMPLVL lump is the author's statement of intent and caps the
+    // inference from specials, which Boom-era editors sometimes set stray.
+    let declared = evidence.iter().find(|e| e.kind == EvidenceKind::Complvl).map(|e| e.needs);
+    let minimum = evidence
+        .iter()
+        .filter(|e| declared.is_none() || e.kind != EvidenceKind::LineSpecials)
+        .map(|e| e.needs)
+        .max()
+        .unwrap_or(PortFeatures::Vanilla);
+    let supported_engines = EngineKind::ALL.into_iter().filter(|kind| minimum.supported_by(*kind)).collect();
+    Ok(Compatibility { minimum, evidence, supported_engines })
+}
+
+/// The complevel PrBoom+/DSDA-Doom/Woof! should run a file at.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Complevel {
+    /// The `-complevel` number: 2 (Doom II), 3 (Ultimate Doom) or 4 (Final
+    /// Doom) for vanilla, 9 Boom, 11 MBF, 21 MBF21. None for a file that
+    /// needs a ZDoom-family port, which has no complevels.
+    pub complevel: Option<u32>,
+    /// The COMPLVL lump's text; None when the level was inferred by
+    /// detect_compatibility instead.
+    pub declared: Option<String>,
+}
+
+/// Vanilla's complevel depends on the executable the game shipped with.
+fn vanilla_complevel(game: Option<&str>) -> u32 {
+    match game {
+        Some("doom") => 3,
+        Some("tnt" | "plutonia") => 4,
+        _ => 2,
+    }
+}
+
+/// The vanilla complevel for the game `iwad` is.
+pub(crate) fn game_complevel(iwad: &str) -> Result<u32, String> {
+    Ok(vanilla_complevel(wad_parser::detect_game(iwad)?.as_deref()))
+}
+
+/// The complevel a DEHACKED patch file needs, when it uses more than
+/// vanilla's.
+pub(crate) fn patch_complevel(path: &str) -> Result<Option<u32>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(dehacked_features(&String::from_utf8_lossy(&data)).and_then(|(level, _)| match level {
+        PortFeatures::Vanilla => None,
+        PortFeatures::Boom => Some(9),
+        _ => Some(21),
+    }))
+}
+
+/// The complevel for a WAD/PK3: what its COMPLVL lump declares ("vanilla",
+/// "boom", "mbf", "mbf21"), else the level detect_compatibility infers,
+/// where Boom can't be told from MBF and counts as Boom. Which vanilla
+/// level depends on the game: `iwad`'s when given, else the one the file
+/// is for.
+pub fn detect_complevel(path: &str, iwad: Option<&str>) -> Result<Complevel, String> {
+    complevel_with(path, &|| game_complevel(iwad.unwrap_or(path)))
+}
+
+/// detect_complevel for a game already identified: `vanilla` is its
+/// vanilla complevel.
+pub(crate) fn detect_complevel_at(path: &str, vanilla: u32) -> Result<Complevel, String> {
+    complevel_with(path, &|| Ok(vanilla))
+}
+
+fn complevel_with(path: &str, vanilla: &dyn Fn() -> Result<u32, String>) -> Result<Complevel, String> {
+    // The engine reads the last COMPLVL in the load order.
+    if let Some(lump) = wad_parser::collect_lumps(path, &|name| name == "COMPLVL")?.pop() {
+        let text = String::from_utf8_lossy(&lump.data).trim().to_string();
+        let complevel = match text.to_lowercase().as_str() {
+            "vanilla" => Some(vanilla()?),
+            "boom" => Some(9),
+            "mbf" => Some(11),
+            "mbf21" => Some(21),
+            _ => None,
+        };
+        if let Some(complevel) = complevel {
+            return Ok(Complevel { complevel: Some(complevel), declared: Some(text) });
+        }
+        eprintln!("Unknown COMPLVL \"{}\" in {}, inferring the complevel instead", text, path);
+    }
+    let complevel = match detect_compatibility(path)?.minimum {
+        PortFeatures::Vanilla => Some(vanilla()?),
+        PortFeatures::Boom => Some(9),
+        PortFeatures::Mbf21 => Some(21),
+        PortFeatures::Zdoom | PortFeatures::Gzdoom => None,
+    };
+    Ok(Complevel { complevel, declared: None })
+}
+
+/// A Doom-format map lump bigger than the original executables cope with.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitWarning {
+    pub map: String,
+    /// "SEGS", "SIDEDEFS", "LINEDEFS", "VERTEXES" or "BLOCKMAP".
+    pub lump: String,
+    /// Entries (bytes for BLOCKMAP).
+    pub count: usize,
+    pub limit: usize,
+    /// Past a hard limit: the map can't load at all in a vanilla port,
+    /// rather than likely crashing somewhere in it.
+    pub hard: bool,
+    pub message: String,
+}
+
+/// Vanilla stores indices into these lumps as signed 16-bit numbers, so
+/// entries past this one can't be referred to and the map won't load.
+const VANILLA_INDEX_LIMIT: usize = 32767;
+
+/// BLOCKMAP offsets are 16-bit word offsets: a lump over 64K words
+/// overflows them.
+const VANILLA_BLOCKMAP_BYTES: usize = 0x10000 * 2;
+
+/// Sizes past which a map rarely stays under vanilla's static 128
+/// visplanes and 256 drawsegs in every view, the overflows that crash
+/// Chocolate Doom mid-game. Those depend on what is in sight, so nothing
+/// in the lumps counts them; these are rules of thumb from how large maps
+/// built for vanilla get, not limits the executable checks.
+const LIKELY_OVERFLOW_SEGS: usize = 10000;
+const LIKELY_OVERFLOW_SIDEDEFS: usize = 8000;
+const LIKELY_OVERFLOW_LINEDEFS: usize = 5000;
+
+/// (lump, record size, hard limit, size that likely overflows).
+type LumpLimit = (&'static str, usize, usize, Option<usize>);
+
+const LUMP_LIMITS: &[LumpLimit] = &[
+    ("SEGS", 12, VANILLA_INDEX_LIMIT, Some(LIKELY_OVERFLOW_SEGS)),
+    ("SIDEDEFS", 30, VANILLA_INDEX_LIMIT, Some(LIKELY_OVERFLOW_SIDEDEFS)),
+    ("LINEDEFS", 14, VANILLA_INDEX_LIMIT, Some(LIKELY_OVERFLOW_LINEDEFS)),
+    ("VERTEXES", 4, VANILLA_INDEX_LIMIT, None),
+];
+
+/// Doom-format maps in a WAD/PK3 that exceed, or likely exceed, the
+/// original executables' static limits, for warning before a launch in
+/// Chocolate Doom or another vanilla port. At most one warning per lump,
+/// the hard limit taking precedence. Hexen-format and UDMF maps are
+/// already beyond vanilla and are left to detect_compatibility.
+pub fn check_vanilla_limits(path: &str) -> Result<Vec<LimitWarning>, String> {
+    let mut out = Vec::new();
+    for map in wad_parser::read_maps(path, &["SEGS", "SIDEDEFS", "LINEDEFS", "VERTEXES", "BLOCKMAP"])? {
+        if map.format != Some(MapFormat::Doom) {
+            continue;
+        }
+        for &(lump, record, hard, likely) in LUMP_LIMITS {
+            let count = map.data(lump)?.len() / record;
+            let (limit, is_hard) = if count > hard {
+                (hard, true)
+            } else if let Some(likely) = likely.filter(|&likely| count > likely) {
+                (likely, false)
+            } else {
+                continue;
+            };
+            let message = match is_hard {
+                true => format!("{}: {} {} exceed vanilla's limit of {}; the map won't load", map.id, count, lump, limit),
+                false => format!("{}: {} {} (over {}) will likely overflow vanilla's visplane or drawseg limits", map.id, count, lump, limit),
+            };
+            out.push(LimitWarning { map: map.id.clone(), lump: lump.to_string(), count, limit, hard: is_hard, message });
+        }
+        let blockmap = map.data("BLOCKMAP")?.len();
+        if blockmap > VANILLA_BLOCKMAP_BYTES {
+            out.push(LimitWarning {
+                map: map.id.clone(),
+                lump: "BLOCKMAP".to_string(),
+                count: blockmap,
+                limit: VANILLA_BLOCKMAP_BYTES,
+                hard: true,
+                message: format!("{}: the {}-byte BLOCKMAP is too large for vanilla; collision will break", map.id, blockmap),
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// The ZScript version a mod asks for, checked against the engine.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ZscriptCheck {
+    /// Highest `version` any file's ZSCRIPT declares, as written ("4.10").
+    pub required: Option<String>,
+    /// The file that declares it.
+    pub required_by: Option<String>,
+    pub engine: VersionInfo,
+    /// Set when the engine is known to be older than `required`.
+    pub warning: Option<String>,
+}
+
+/// The `version "4.10"` directive, which must open a ZSCRIPT lump.
+fn zscript_version(src: &str) -> Option<String> {
+    let tokens = mapinfo::tokenize(src);
+    match tokens.as_slice() {
+        [directive, version, ..] if directive.text.eq_ignore_ascii_case("version") && version.quoted => {
+            Some(version.text.trim().to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Compare the ZScript versions `files` declare with `engine`. A lump
+/// without a directive is ZScript 2.3-compatible and never warns; an engine
+/// whose version is unknown isn't assumed too old.
+pub fn check_zscript_version(files: &[String], engine: &VersionInfo) -> Result<ZscriptCheck, String> {
+    let mut required: Option<(VersionInfo, String, String)> = None;
+    for file in files {
+        for lump in wad_parser::collect_lumps(file, &|name| name == "ZSCRIPT")? {
+            let Some(written) = zscript_version(&String::from_utf8_lossy(&lump.data)) else {
+                continue;
+            };
+            let version = engine_version::parse_version(&written);
+            if version.key().is_some()
+                && required.as_ref().is_none_or(|(max, _, _)| version.key() > max.key())
+            {
+                required = Some((version, written, file.clone()));
+            }
+        }
+    }
+    let warning = match (&required, engine.key()) {
+        (Some((version, written, file)), Some(have)) if version.key().is_some_and(|need| need > have) => {
+            Some(format!("{} needs ZScript {}, but the engine is {}", file, written, engine.raw))
+        }
+        _ => None,
+    };
+    let (required, required_by) = required.map_or((None, None), |(_, written, file)| (Some(written), Some(file)));
+    Ok(ZscriptCheck { required, required_by, engine: engine.clone(), warning })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, build_zip, write_temp};
+
+    fn linedef(flags: u16, special: u16) -> Vec<u8> {
+        [0u16, 1, flags, special, 0, 0, 0xFFFF].iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn rates_maps_by_line_specials() {
+        let lines = [linedef(0, 1), linedef(0, 242), linedef(0x1000, 0)].concat();
+        let wad = write_temp("compat_mbf21.wad", &build_wad(b"PWAD", &[("MAP01", b""), ("LINEDEFS", &lines)]));
+        let c = detect_compatibility(&wad).unwrap();
+        assert_eq!(c.minimum, PortFeatures::Mbf21);
+        let reasons: Vec<&str> = c.evidence.iter().map(|e| e.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["MAP01: 1 MBF21 line(s)", "MAP01: 1 Boom line(s)"]);
+
+        let vanilla = write_temp("compat_vanilla.wad", &build_wad(b"PWAD", &[("E1M1", b""), ("LINEDEFS", &linedef(0, 1))]));
+        let c = detect_compatibility(&vanilla).unwrap();
+        assert_eq!((c.minimum, c.evidence), (PortFeatures::Vanilla, vec![]));
+        assert_eq!(c.supported_engines.len(), EngineKind::ALL.len());
+    }
+
+    #[test]
+    fn rates_by_lumps_and_complvl() {
+        let pk3 = write_temp("compat.pk3", &build_zip(&[("zscript.zs", b"version \"4.10\""), ("decorate.txt", b"")]));
+        let c = detect_compatibility(&pk3).unwrap();
+        assert_eq!(c.minimum, PortFeatures::Gzdoom);
+        assert_eq!(c.supported_engines, vec![EngineKind::GZDoom]);
+
+        let lines = linedef(0, 242);
+        let wad = build_wad(b"PWAD", &[("COMPLVL", b"vanilla\n"), ("MAP01", b""), ("LINEDEFS", &lines)]);
+        let c = detect_compatibility(&write_temp("compat_complvl.wad", &wad)).unwrap();
+        assert_eq!(c.minimum, PortFeatures::Vanilla);
+        let kinds: Vec<EvidenceKind> = c.evidence.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![EvidenceKind::Complvl, EvidenceKind::LineSpecials]);
+
+        let deh = build_wad(b"PWAD", &[("DEHACKED", b"Patch File for DeHackEd v3.0\nDoom version = 2021\n")]);
+        let c = detect_compatibility(&write_temp("compat_deh.wad", &deh)).unwrap();
+        assert_eq!(c.minimum, PortFeatures::Mbf21);
+        assert!(PortFeatures::Mbf21.supported_by(EngineKind::Dsda));
+        assert!(!PortFeatures::Boom.supported_by(EngineKind::Chocolate));
+    }
+
+    #[test]
+    fn reads_complevel_from_complvl_or_infers_it() {
+        let level = |name: &str, lumps: &[(&str, &[u8])]| detect_complevel(&write_temp(name, &build_wad(b"PWAD", lumps)), None).unwrap();
+        assert_eq!(
+            level("cl_mbf21.wad", &[("COMPLVL", b"MBF21\n"), ("MAP01", b"")]),
+            Complevel { complevel: Some(21), declared: Some("MBF21".into()) }
+        );
+        assert_eq!(level("cl_vanilla.wad", &[("COMPLVL", b"vanilla"), ("MAP01", b"")]).complevel, Some(2));
+        assert_eq!(level("cl_ultimate.wad", &[("COMPLVL", b"vanilla"), ("E1M1", b"")]).complevel, Some(3));
+        let boom = level("cl_boom.wad", &[("MAP01", b""), ("LINEDEFS", &linedef(0, 242))]);
+        assert_eq!(boom, Complevel { complevel: Some(9), declared: None });
+        assert_eq!(level("cl_unknown.wad", &[("COMPLVL", b"zdoom"), ("MAP01", b"")]).complevel, Some(2));
+        assert_eq!(level("cl_zdoom.wad", &[("DECORATE", b""), ("MAP01", b"")]).complevel, None);
+    }
+
+    #[test]
+    fn flags_maps_over_vanilla_limits() {
+        let lines = vec![0u8; 14 * (LIKELY_OVERFLOW_LINEDEFS + 1)];
+        let vertexes = vec![0u8; 4 * (VANILLA_INDEX_LIMIT + 1)];
+        let wad = build_wad(
+            b"PWAD",
+            &[
+                ("MAP01", b""),
+                ("LINEDEFS", &linedef(0, 1)),
+                ("MAP02", b""),
+                ("LINEDEFS", &lines),
+                ("VERTEXES", &vertexes),
+            ],
+        );
+        let warnings = check_vanilla_limits(&write_temp("limits.wad", &wad)).unwrap();
+        let summary: Vec<(&str, &str, usize, bool)> =
+            warnings.iter().map(|w| (w.map.as_str(), w.lump.as_str(), w.count, w.hard)).collect();
+        assert_eq!(summary, vec![("MAP02", "LINEDEFS", 5001, false), ("MAP02", "VERTEXES", 32768, true)]);
+        assert!(warnings[1].message.contains("won't load"));
+    }
+
+    #[test]
+    fn warns_when_the_engine_is_older_than_zscript() {
+        let old_mod = write_temp("zs_old.pk3", &build_zip(&[("zscript.zs", b"// comment\nversion \"4.2\"\nclass A {}")]));
+        let new_mod = write_temp("zs_new.pk3", &build_zip(&[("zscript.txt", b"version \"4.12.1\"\n#include \"zscript/b.zs\"")]));
+        let plain = write_temp("zs_none.pk3", &build_zip(&[("zscript.zs", b"class B {}")]));
+        let files = vec![old_mod, new_mod.clone(), plain.clone()];
+
+        let check = check_zscript_version(&files, &engine_version::parse_version("GZDoom g4.11.3")).unwrap();
+        assert_eq!((check.required.as_deref(), check.required_by.as_ref()), (Some("4.12.1"), Some(&new_mod)));
+        assert!(check.warning.unwrap().contains("needs ZScript 4.12.1"));
+
+        let check = check_zscript_version(&files, &engine_version::parse_version("g4.14.2")).unwrap();
+        assert_eq!(check.warning, None);
+        let check = check_zscript_version(&files, &VersionInfo::unknown()).unwrap();
+        assert_eq!(check.warning, None);
+        let check = check_zscript_version(&[plain], &engine_version::parse_version("g4.11.3")).unwrap();
+        assert_eq!((check.required, check.warning), (None, None));
+    }
+}
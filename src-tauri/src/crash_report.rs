@@ -0,0 +1,219 @@
+// Crash reports for engines that exit abnormally: a signal, or a nonzero
+// exit code. The report gathers what a bug report for the port needs — how
+// it died, the tail of the log, the stack trace its crash handler printed
+// and the errors log_analysis finds — plus the crash dump file the engine
+// wrote, if any. GZDoom on Linux prints a backtrace to stderr,
+//
+//     Caught signal 11 (Segmentation fault)
+//     Backtrace:
+//     #0  0x5611c2 in P_CheckSight ...
+//
+// while dumps (crashreport.zip, *.dmp, core files) land next to the engine
+// or in the working directory, depending on the port and platform, so
+// those are searched for files named like one and written since the
+// session started. The temp directory isn't: other programs' dumps end up
+// there too. A session stopped with kill_gzdoom or by a quick test isn't a
+// crash, whatever signal it died of.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::log_analysis::{self, Diagnostic};
+use crate::session::{GZDoomSession, LogLine, SessionId, Stream};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub session_id: SessionId,
+    pub engine_path: String,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    /// The signal or Windows exception by name, e.g. "SIGSEGV (segmentation
+    /// fault)", when it is a known one.
+    pub cause: Option<String>,
+    /// The last lines of the log, [time_ms, text, stream] as in
+    /// get_gzdoom_log.
+    pub last_lines: Vec<LogLine>,
+    /// stderr from the crash handler's first line on; empty without one.
+    pub backtrace: Vec<String>,
+    pub errors: Vec<Diagnostic>,
+    /// The engine's own crash dump or report file.
+    pub crash_dump: Option<String>,
+}
+
+pub const DEFAULT_TAIL_LINES: usize = 100;
+
+/// Lines crash handlers start their output with.
+const CRASH_HANDLER_MARKERS: &[&str] = &["caught signal", "signal caught", "backtrace", "stack trace", "crash report"];
+
+/// Files written within this of the session start still count: file
+/// times and the start time come from different clocks' rounding.
+const DUMP_CLOCK_SLACK: Duration = Duration::from_secs(2);
+
+/// Whether an exit is a crash rather than the player quitting.
+pub fn is_abnormal(exit_code: Option<i32>, signal: Option<i32>) -> bool {
+    !matches!((exit_code, signal), (Some(0), None))
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> Option<&'static str> {
+    Some(match signal {
+        libc::SIGSEGV => "SIGSEGV (segmentation fault)",
+        libc::SIGABRT => "SIGABRT (aborted)",
+        libc::SIGBUS => "SIGBUS (bus error)",
+        libc::SIGFPE => "SIGFPE (arithmetic error)",
+        libc::SIGILL => "SIGILL (illegal instruction)",
+        libc::SIGKILL => "SIGKILL (killed)",
+        libc::SIGTERM => "SIGTERM (terminated)",
+        _ => return None,
+    })
+}
+
+#[cfg(not(unix))]
+fn signal_name(_signal: i32) -> Option<&'static str> {
+    None
+}
+
+/// NTSTATUS codes a crashed Windows process exits with.
+fn windows_exception(code: i32) -> Option<&'static str> {
+    Some(match code as u32 {
+        0xC000_0005 => "access violation (0xC0000005)",
+        0xC000_00FD => "stack overflow (0xC00000FD)",
+        0xC000_0409 => "stack buffer overrun (0xC0000409)",
+        0xC000_001D => "illegal instruction (0xC000001D)",
+        0xC000_0094 => "integer division by zero (0xC0000094)",
+        _ => return None,
+    })
+}
+
+fn cause(exit_code: Option<i32>, signal: Option<i32>) -> Option<String> {
+    match (exit_code, signal) {
+        (_, Some(signal)) => signal_name(signal).map(str::to_string),
+        (Some(code), None) => windows_exception(code).map(str::to_string),
+        (None, None) => None,
+    }
+}
+
+fn backtrace(lines: &[LogLine]) -> Vec<String> {
+    let stderr: Vec<&str> = lines.iter().filter(|(_, _, s)| *s == Stream::Stderr).map(|(_, l, _)| l.as_str()).collect();
+    match stderr.iter().position(|l| {
+        let lower = l.to_lowercase();
+        CRASH_HANDLER_MARKERS.iter().any(|m| lower.contains(m))
+    }) {
+        Some(start) => stderr[start..].iter().map(|l| l.to_string()).collect(),
+        None => Vec::new(),
+    }
+}
+
+fn looks_like_dump(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.contains("crash")
+        || lower.ends_with(".dmp")
+        || lower == "core"
+        || lower.strip_prefix("core.").is_some_and(|pid| pid.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// The newest file in `dirs` (not recursing) named like a crash dump and
+/// modified since `since`.
+fn find_crash_dump(dirs: &[PathBuf], since: SystemTime) -> Option<PathBuf> {
+    let since = since.checked_sub(DUMP_CLOCK_SLACK).unwrap_or(since);
+    let mut newest: Option<(SystemTime, PathBuf)> = None;
+    for dir in dirs {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Failed to look for crash dumps in {}: {}", dir.display(), e);
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !looks_like_dump(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let Ok(modified) = entry.metadata().and_then(|m| m.modified()) else { continue };
+            if path.is_file() && modified >= since && newest.as_ref().is_none_or(|(t, _)| modified > *t) {
+                newest = Some((modified, path));
+            }
+        }
+    }
+    newest.map(|(_, path)| path)
+}
+
+/// The report for a finished session that exited abnormally; None while
+/// it runs, after a clean exit or after it was killed on purpose.
+pub fn crash_report(session: &GZDoomSession, tail_lines: usize) -> Option<CrashReport> {
+    if !session.finished || session.killed || !is_abnormal(session.exit_code, session.signal) {
+        return None;
+    }
+    let lines: Vec<LogLine> = session.lines.iter().cloned().collect();
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Some(parent) = Path::new(&session.engine_path).parent().filter(|p| !p.as_os_str().is_empty()) {
+        dirs.push(parent.to_path_buf());
+    }
+    dirs.extend(session.cwd.iter().map(PathBuf::from));
+    dirs.dedup();
+    Some(CrashReport {
+        session_id: session.id,
+        engine_path: session.engine_path.clone(),
+        exit_code: session.exit_code,
+        signal: session.signal,
+        cause: cause(session.exit_code, session.signal),
+        last_lines: lines[lines.len().saturating_sub(tail_lines)..].to_vec(),
+        backtrace: backtrace(&lines),
+        errors: log_analysis::analyze(&lines).errors,
+        crash_dump: find_crash_dump(&dirs, session.started_at()).map(|p| p.to_string_lossy().to_string()),
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::session::{Sessions, SpawnOptions};
+    use std::sync::Arc;
+
+    #[test]
+    fn reports_signal_backtrace_and_dump() {
+        let dir = std::env::temp_dir().join(format!("crash_report_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("crashreport-old.txt"), b"stale").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::options().write(true).open(dir.join("crashreport-old.txt")).unwrap().set_modified(old).unwrap();
+
+        let sessions = Sessions::new();
+        let options = SpawnOptions { cwd: Some(dir.to_string_lossy().to_string()), ..Default::default() };
+        let script = "echo W_Init; echo 'Script error, \"m.pk3:zscript.zs\" line 2:'; echo 'Caught signal 11' >&2; \
+                      echo '#0 P_CheckSight' >&2; echo dump > crashreport.txt; kill -SEGV $$";
+        let id = sessions.start_with("/bin/sh", &["-c".to_string(), script.to_string()], &options, Arc::new(|_| {})).unwrap();
+        let session = sessions.get(id).unwrap();
+        while !session.lock().unwrap().finished {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        let report = crash_report(&session.lock().unwrap(), 2).unwrap();
+        assert_eq!((report.exit_code, report.signal), (None, Some(libc::SIGSEGV)));
+        assert_eq!(report.cause.as_deref(), Some("SIGSEGV (segmentation fault)"));
+        assert_eq!(report.last_lines.len(), 2);
+        assert_eq!(report.backtrace, vec!["Caught signal 11", "#0 P_CheckSight"]);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.crash_dump, Some(dir.join("crashreport.txt").to_string_lossy().to_string()));
+
+        let clean = sessions.start("/bin/sh", &["-c".to_string(), "exit 0".to_string()], Arc::new(|_| {})).unwrap();
+        let session = sessions.get(clean).unwrap();
+        while !session.lock().unwrap().finished {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(crash_report(&session.lock().unwrap(), 2), None);
+
+        let stopped = sessions.start("/bin/sh", &["-c".to_string(), "sleep 5".to_string()], Arc::new(|_| {})).unwrap();
+        sessions.kill(stopped).unwrap();
+        let session = sessions.get(stopped).unwrap();
+        while !session.lock().unwrap().finished {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(session.lock().unwrap().signal, Some(libc::SIGTERM));
+        assert_eq!(crash_report(&session.lock().unwrap(), 2), None);
+        assert_eq!(windows_exception(0xC000_0005u32 as i32), Some("access violation (0xC0000005)"));
+    }
+}
@@ -0,0 +1,318 @@
+// DEHACKED string replacements, for the map names a patch sets. Two
+// syntaxes carry them:
+//
+//     [STRINGS]                      BEX: by mnemonic
+//     HUSTR_1 = MAP01: The Start
+//
+//     Text 17 16                     DEH: old string immediately followed by
+//     level 1: entrywayMAP01: The Start   the new one, lengths in characters
+//
+// Classic Text blocks name the string by its original text, so Doom and
+// Doom II's automap titles are tabled here to find the mnemonic.
+//
+// Thing blocks ("Thing 12 (Imp)", then "Hit points = 100") are read only
+// for the monsters' health, which the difficulty estimate weighs.
+
+use std::collections::HashMap;
+
+/// Doom II's HUSTR_1..HUSTR_32, in order.
+const DOOM2_TITLES: [&str; 32] = [
+    "level 1: entryway",
+    "level 2: underhalls",
+    "level 3: the gantlet",
+    "level 4: the focus",
+    "level 5: the waste tunnels",
+    "level 6: the crusher",
+    "level 7: dead simple",
+    "level 8: tricks and traps",
+    "level 9: the pit",
+    "level 10: refueling base",
+    "level 11: 'o' of destruction!",
+    "level 12: the factory",
+    "level 13: downtown",
+    "level 14: the inmost dens",
+    "level 15: industrial zone",
+    "level 16: suburbs",
+    "level 17: tenements",
+    "level 18: the courtyard",
+    "level 19: the citadel",
+    "level 20: gotcha!",
+    "level 21: nirvana",
+    "level 22: the catacombs",
+    "level 23: barrels o' fun",
+    "level 24: the chasm",
+    "level 25: bloodfalls",
+    "level 26: the abandoned mines",
+    "level 27: monster condo",
+    "level 28: the spirit world",
+    "level 29: the living end",
+    "level 30: icon of sin",
+    "level 31: wolfenstein",
+    "level 32: grosse",
+];
+
+/// Doom's HUSTR_E1M1..HUSTR_E4M9, by episode.
+const DOOM_TITLES: [[&str; 9]; 4] = [
+    [
+        "Hangar",
+        "Nuclear Plant",
+        "Toxin Refinery",
+        "Command Control",
+        "Phobos Lab",
+        "Central Processing",
+        "Computer Station",
+        "Phobos Anomaly",
+        "Military Base",
+    ],
+    [
+        "Deimos Anomaly",
+        "Containment Area",
+        "Refinery",
+        "Deimos Lab",
+        "Command Center",
+        "Halls of the Damned",
+        "Spawning Vats",
+        "Tower of Babel",
+        "Fortress of Mystery",
+    ],
+    [
+        "Hell Keep",
+        "Slough of Despair",
+        "Pandemonium",
+        "House of Pain",
+        "Unholy Cathedral",
+        "Mt. Erebus",
+        "Limbo",
+        "Dis",
+        "Warrens",
+    ],
+    [
+        "Hell Beneath",
+        "Perfect Hatred",
+        "Sever The Wicked",
+        "Unruly Evil",
+        "They Will Repent",
+        "Against Thee Wickedly",
+        "And Hell Followed",
+        "Unto The Cruel",
+        "Fear",
+    ],
+];
+
+/// The mnemonic of a vanilla automap title, matched case-insensitively.
+fn mnemonic_for_original(text: &str) -> Option<String> {
+    let text = text.trim();
+    if let Some(i) = DOOM2_TITLES.iter().position(|t| t.eq_ignore_ascii_case(text)) {
+        return Some(format!("HUSTR_{}", i + 1));
+    }
+    for (e, episode) in DOOM_TITLES.iter().enumerate() {
+        for (m, title) in episode.iter().enumerate() {
+            let id = format!("E{}M{}", e + 1, m + 1);
+            if text.eq_ignore_ascii_case(&format!("{}: {}", id, title)) {
+                return Some(format!("HUSTR_{}", id));
+            }
+        }
+    }
+    None
+}
+
+/// Byte length of the first line of `text`, newline included.
+fn line_end(text: &str) -> usize {
+    text.find('\n').map_or(text.len(), |i| i + 1)
+}
+
+/// The lengths in a "Text <old> <new>" header.
+fn text_header(line: &str) -> Option<(usize, usize)> {
+    let mut words = line.split_whitespace();
+    if !words.next()?.eq_ignore_ascii_case("text") {
+        return None;
+    }
+    let old = words.next()?.parse().ok()?;
+    let new = words.next()?.parse().ok()?;
+    words.next().is_none().then_some((old, new))
+}
+
+/// String replacements in a DEH/BEX patch, mnemonic (uppercase) -> new text.
+/// Later replacements of the same string win, as when the engine applies
+/// the patch.
+pub fn parse_strings(patch: &str) -> HashMap<String, String> {
+    let patch = patch.replace('\r', "");
+    let mut out = HashMap::new();
+    let mut in_strings = false;
+    let mut rest = patch.as_str();
+    while !rest.is_empty() {
+        let end = line_end(rest);
+        let line = rest[..end].trim();
+        rest = &rest[end..];
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_strings = line.eq_ignore_ascii_case("[strings]");
+            continue;
+        }
+        if let Some((old_len, new_len)) = text_header(line) {
+            in_strings = false;
+            let text: String = rest.chars().take(old_len + new_len).collect();
+            rest = &rest[text.len()..];
+            if text.chars().count() == old_len + new_len {
+                let old: String = text.chars().take(old_len).collect();
+                if let Some(mnemonic) = mnemonic_for_original(&old) {
+                    out.insert(mnemonic, text[old.len()..].trim().to_string());
+                }
+            }
+            continue;
+        }
+        if !in_strings {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            // A classic block header ends the BEX section.
+            in_strings = false;
+            continue;
+        };
+        let mut value = value.trim().to_string();
+        // A trailing backslash continues the value on the next line.
+        while value.ends_with('\\') && !rest.is_empty() {
+            value.pop();
+            let end = line_end(rest);
+            value.push_str(rest[..end].trim());
+            rest = &rest[end..];
+        }
+        out.insert(key.trim().to_uppercase(), value.replace("\\n", "\n"));
+    }
+    out
+}
+
+/// Hit points a DEH/BEX patch gives things, by DEHACKED thing number (the
+/// 1-based mobjinfo index: 1 is the player, 12 the imp). Later blocks for
+/// the same thing win.
+pub fn thing_hit_points(patch: &str) -> HashMap<u32, u32> {
+    let patch = patch.replace('\r', "");
+    let mut out = HashMap::new();
+    let mut thing: Option<u32> = None;
+    let mut rest = patch.as_str();
+    while !rest.is_empty() {
+        let end = line_end(rest);
+        let line = rest[..end].trim();
+        rest = &rest[end..];
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((old_len, new_len)) = text_header(line) {
+            // Skip the text itself; it may contain anything.
+            thing = None;
+            let skip: usize = rest.chars().take(old_len + new_len).map(char::len_utf8).sum();
+            rest = &rest[skip..];
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) if key.trim().eq_ignore_ascii_case("hit points") => {
+                if let (Some(number), Ok(hp)) = (thing, value.trim().parse()) {
+                    out.insert(number, hp);
+                }
+            }
+            Some(_) => {}
+            None => {
+                let mut words = line.split_whitespace();
+                thing = match words.next() {
+                    Some(word) if word.eq_ignore_ascii_case("thing") => words.next().and_then(|n| n.parse().ok()),
+                    _ => None,
+                };
+            }
+        }
+    }
+    out
+}
+
+/// The map a title mnemonic belongs to: HUSTR_7 / PHUSTR_7 / THUSTR_7 ->
+/// MAP07, HUSTR_E2M4 -> E2M4.
+fn mnemonic_map(mnemonic: &str) -> Option<(String, u8)> {
+    let (family, rank) = [("HUSTR_", 0), ("PHUSTR_", 1), ("THUSTR_", 2)]
+        .into_iter()
+        .find(|(prefix, _)| mnemonic.starts_with(prefix))?;
+    let suffix = &mnemonic[family.len()..];
+    if let Ok(n) = suffix.parse::<u32>() {
+        return (1..=32).contains(&n).then(|| (format!("MAP{:02}", n), rank));
+    }
+    (rank == 0 && crate::wad_parser::is_exmy_marker(suffix)).then(|| (suffix.to_string(), rank))
+}
+
+/// Drop the automap prefix ("level 7: ", "MAP07: ", "E1M1: ") GZDoom also
+/// strips when it shows a level name.
+fn strip_title_prefix(title: &str) -> &str {
+    let Some((prefix, name)) = title.split_once(':') else {
+        return title;
+    };
+    let prefix = prefix.trim().to_uppercase();
+    let is_level = prefix
+        .strip_prefix("LEVEL")
+        .is_some_and(|n| !n.trim().is_empty() && n.trim().chars().all(|c| c.is_ascii_digit()));
+    let is_map = crate::wad_parser::is_mapxx_marker(&prefix) || crate::wad_parser::is_exmy_marker(&prefix);
+    if (is_level || is_map) && !name.trim().is_empty() { name.trim() } else { title }
+}
+
+/// Map names a patch sets, map id -> name. Doom II (HUSTR_) titles win over
+/// Plutonia's (PHUSTR_), which win over TNT's (THUSTR_), when a patch sets
+/// several for the same map.
+pub fn level_names(strings: &HashMap<String, String>) -> HashMap<String, String> {
+    let mut ranked: HashMap<String, (u8, String)> = HashMap::new();
+    for (mnemonic, text) in strings {
+        let Some((id, rank)) = mnemonic_map(mnemonic) else {
+            continue;
+        };
+        let name = strip_title_prefix(text).to_string();
+        if name.is_empty() {
+            continue;
+        }
+        match ranked.get(&id) {
+            Some((existing, _)) if *existing <= rank => {}
+            _ => {
+                ranked.insert(id, (rank, name));
+            }
+        }
+    }
+    ranked.into_iter().map(|(id, (_, name))| (id, name)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_bex_strings_and_text_blocks() {
+        let patch = "Patch File for DeHackEd v3.0\r\n\
+                     # comment\r\n\
+                     Text 17 20\r\n\
+                     level 1: entrywayMAP01: The Beginning\r\n\
+                     Thing 1 (Player)\r\n\
+                     Hit points = 200\r\n\
+                     [STRINGS]\r\n\
+                     HUSTR_2 = level 2: The \\\r\n\
+                     Depths\r\n\
+                     PHUSTR_2 = Ignored for Doom II\r\n\
+                     THUSTR_3 = TNT Three\r\n\
+                     HUSTR_E1M1 = E1M1: Landing Zone\r\n\
+                     GOTARMOR = Armor!\r\n";
+        let strings = parse_strings(patch);
+        assert_eq!(strings["HUSTR_1"], "MAP01: The Beginning");
+        assert_eq!(strings["HUSTR_2"], "level 2: The Depths");
+        assert!(!strings.contains_key("HIT POINTS"));
+
+        let names = level_names(&strings);
+        assert_eq!(names["MAP01"], "The Beginning");
+        assert_eq!(names["MAP02"], "The Depths");
+        assert_eq!(names["MAP03"], "TNT Three");
+        assert_eq!(names["E1M1"], "Landing Zone");
+        assert_eq!(names.len(), 4);
+        assert_eq!(thing_hit_points(patch), HashMap::from([(1, 200)]));
+    }
+
+    #[test]
+    fn keeps_titles_without_a_map_prefix() {
+        assert_eq!(strip_title_prefix("Hell: The Return"), "Hell: The Return");
+        assert_eq!(strip_title_prefix("level 31: wolfenstein"), "wolfenstein");
+        assert_eq!(mnemonic_for_original("E3M6: Mt. Erebus").as_deref(), Some("HUSTR_E3M6"));
+        assert_eq!(mnemonic_map("HUSTR_33"), None);
+    }
+}
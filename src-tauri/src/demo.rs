@@ -0,0 +1,133 @@
+// Demo recording and playback. The flags are the same across source ports
+// (-record, -playdemo, -timedemo); what differs is how a finished timedemo
+// reports its speed, so the result is recovered from the captured log.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// What to do with a demo lump/file. Serialized as
+/// `{ "mode": "record", "path": "…" }`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "mode", content = "path", rename_all = "lowercase")]
+pub enum DemoAction {
+    Record(String),
+    Play(String),
+    /// Play back as fast as possible and report the frame rate.
+    Timedemo(String),
+}
+
+/// Recording (or saving) fails only after the game is under way if the
+/// directory can't be written, losing the run. Probe it up front with a
+/// scratch file. `what` names the directory in errors ("Demo directory").
+pub(crate) fn check_writable_dir(dir: &Path, what: &str) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("{} does not exist: {}", what, dir.display()));
+    }
+    let probe = dir.join(format!(".doom-launcher-write-test-{}", std::process::id()));
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .map_err(|e| format!("{} is not writable: {}: {}", what, dir.display(), e))?;
+    std::fs::remove_file(&probe).map_err(|e| format!("Failed to remove {}: {}", probe.display(), e))
+}
+
+/// Validate the demo path and return the engine arguments for `action`.
+pub fn demo_args(action: &DemoAction) -> Result<Vec<String>, String> {
+    let (flag, path) = match action {
+        DemoAction::Record(path) => ("-record", path),
+        DemoAction::Play(path) => ("-playdemo", path),
+        DemoAction::Timedemo(path) => ("-timedemo", path),
+    };
+    if path.trim().is_empty() {
+        return Err("Demo path is empty".to_string());
+    }
+    match action {
+        DemoAction::Record(_) => {
+            let parent = Path::new(path).parent().filter(|p| !p.as_os_str().is_empty());
+            check_writable_dir(parent.unwrap_or(Path::new(".")), "Demo directory")?;
+        }
+        _ => {
+            if !Path::new(path).is_file() {
+                return Err(format!("Demo file not found: {}", path));
+            }
+        }
+    }
+    Ok(vec![flag.to_string(), path.clone()])
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimedemoResult {
+    pub gametics: u64,
+    pub realtics: u64,
+    /// gametics / realtics scaled by the 35 Hz tic rate.
+    pub fps: f64,
+}
+
+const TICRATE: f64 = 35.0;
+
+/// The number immediately before `word` in `line`, e.g. 2134 in
+/// "timed 2134 gametics in 1002 realtics".
+fn number_before(line: &str, word: &str) -> Option<u64> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let at = words.iter().position(|w| w.to_lowercase().starts_with(word))?;
+    words.get(at.checked_sub(1)?)?.parse().ok()
+}
+
+/// Find the timedemo summary in engine output. Vanilla and Chocolate print
+/// "timed N gametics in M realtics", PrBoom+ "Timed N gametics in M
+/// realtics = X frames per second"; the FPS is recomputed from the tics so
+/// every port reports it the same way.
+pub fn parse_timedemo<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<TimedemoResult> {
+    lines.into_iter().find_map(|line| {
+        let gametics = number_before(line, "gametics")?;
+        let realtics = number_before(line, "realtics")?;
+        if realtics == 0 {
+            return None;
+        }
+        Some(TimedemoResult {
+            gametics,
+            realtics,
+            fps: gametics as f64 * TICRATE / realtics as f64,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_and_validates_demo_args() {
+        let dir = std::env::temp_dir().join(format!("demo_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let record = dir.join("run.lmp").to_string_lossy().to_string();
+        assert_eq!(demo_args(&DemoAction::Record(record.clone())).unwrap(), vec!["-record", &record]);
+        // Nothing left behind by the writability probe.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let missing_dir = dir.join("nope/run.lmp").to_string_lossy().to_string();
+        assert!(demo_args(&DemoAction::Record(missing_dir)).unwrap_err().contains("does not exist"));
+        assert!(demo_args(&DemoAction::Play(record.clone())).unwrap_err().contains("not found"));
+
+        std::fs::write(&record, b"demo").unwrap();
+        assert_eq!(demo_args(&DemoAction::Timedemo(record.clone())).unwrap(), vec!["-timedemo", &record]);
+    }
+
+    #[test]
+    fn parses_timedemo_output() {
+        let r = parse_timedemo(["W_Init: Init WADfiles.", "timed 2134 gametics in 1067 realtics"]).unwrap();
+        assert_eq!((r.gametics, r.realtics), (2134, 1067));
+        assert!((r.fps - 70.0).abs() < 1e-9);
+        let r = parse_timedemo(["Timed 350 gametics in 35 realtics = 350.0 frames per second"]).unwrap();
+        assert!((r.fps - 350.0).abs() < 1e-9);
+        assert_eq!(parse_timedemo(["no timing here", "timed 5 gametics in 0 realtics"]), None);
+    }
+
+    #[test]
+    fn serializes_tagged() {
+        let json = serde_json::to_string(&DemoAction::Play("a.lmp".into())).unwrap();
+        assert_eq!(json, r#"{"mode":"play","path":"a.lmp"}"#);
+    }
+}
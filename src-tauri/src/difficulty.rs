@@ -0,0 +1,195 @@
+// A rough difficulty rating per map, 1 (a stroll) to 5 (slaughter), for
+// browsing a megawad. It only looks at the things, so it can't see traps,
+// ammo starvation or a map's layout; it is meant to be consistent, not
+// right. At the skill asked for:
+//
+//   - the monsters' hit points, added up, give the base rating: a map of
+//     zombiemen and imps is a 1, one with several cyberdemons a 5. HP rather
+//     than a monster count, so ten barons weigh more than ten imps. Changes
+//     the file's DEHACKED (or a patch loaded with it) makes to a monster's
+//     health count.
+//   - density, monsters per 1024x1024 block of the area the map's things
+//     span (a cheap stand-in for the map's size), moves it a step: a crowded
+//     map (SLAUGHTER_DENSITY or more) is harder, a sparse one (under
+//     SPARSE_DENSITY) easier.
+//   - a MAPINFO par time that leaves little time per hit point (at least
+//     RUSHED_HP_PER_SECOND to get through) also makes it harder.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::dehacked;
+use crate::map_stats::{self, PlacedThing};
+use crate::wad_parser::{self, MapFormat, MapLumps};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DifficultyScore {
+    /// 1-5.
+    pub rating: u8,
+    pub monsters: u32,
+    /// Total health of the monsters.
+    pub hit_points: u64,
+    /// Monsters per 1024x1024 map units of the things' bounding box.
+    pub density: f64,
+    pub par_seconds: Option<u32>,
+}
+
+/// (doomednum, DEHACKED thing number, vanilla hit points) of each monster
+/// that counts toward kills, as map_stats counts them: lost souls don't.
+const MONSTER_HEALTH: &[(u16, u32, u32)] = &[
+    (3004, 2, 20),
+    (9, 3, 30),
+    (64, 4, 700),
+    (66, 6, 300),
+    (67, 9, 600),
+    (65, 11, 70),
+    (3001, 12, 60),
+    (3002, 13, 150),
+    (58, 14, 150),
+    (3005, 15, 400),
+    (3003, 16, 1000),
+    (69, 18, 500),
+    (7, 20, 3000),
+    (68, 21, 500),
+    (16, 22, 4000),
+    (71, 23, 400),
+    (84, 24, 50),
+    (72, 25, 100),
+];
+
+/// Upper bounds of the total hit points for ratings 1-4; anything more is 5.
+const HP_BANDS: [u64; 4] = [1500, 5000, 15000, 40000];
+
+const SLAUGHTER_DENSITY: f64 = 16.0;
+const SPARSE_DENSITY: f64 = 1.0;
+const RUSHED_HP_PER_SECOND: f64 = 150.0;
+
+const BLOCK_AREA: f64 = 1024.0 * 1024.0;
+
+/// Monster health by doomednum, with the patches' Hit points applied in
+/// order.
+fn monster_health(patches: &[String]) -> HashMap<u16, u32> {
+    let mut health: HashMap<u16, u32> = MONSTER_HEALTH.iter().map(|&(kind, _, hp)| (kind, hp)).collect();
+    for patch in patches {
+        for (number, hp) in dehacked::thing_hit_points(patch) {
+            if let Some(&(kind, _, _)) = MONSTER_HEALTH.iter().find(|(_, n, _)| *n == number) {
+                health.insert(kind, hp);
+            }
+        }
+    }
+    health
+}
+
+/// Score one map's things.
+fn score(things: &[PlacedThing], health: &HashMap<u16, u32>, par_seconds: Option<u32>) -> DifficultyScore {
+    let hp: Vec<u32> = things.iter().filter_map(|t| health.get(&t.kind).copied()).collect();
+    let hit_points: u64 = hp.iter().map(|&h| h as u64).sum();
+    let monsters = hp.len() as u32;
+
+    let (min_x, max_x, min_y, max_y) = things.iter().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(x0, x1, y0, y1), t| (x0.min(t.x), x1.max(t.x), y0.min(t.y), y1.max(t.y)),
+    );
+    // At least one block, so a handful of things in a closet isn't a
+    // slaughter map.
+    let area = if things.is_empty() { BLOCK_AREA } else { ((max_x - min_x) * (max_y - min_y)).max(BLOCK_AREA) };
+    let density = monsters as f64 / (area / BLOCK_AREA);
+
+    let mut rating = 1 + HP_BANDS.iter().filter(|&&band| hit_points >= band).count() as i32;
+    let rushed = par_seconds.is_some_and(|par| par > 0 && hit_points as f64 / par as f64 >= RUSHED_HP_PER_SECOND);
+    if density >= SLAUGHTER_DENSITY || rushed {
+        rating += 1;
+    } else if density < SPARSE_DENSITY {
+        rating -= 1;
+    }
+    DifficultyScore { rating: rating.clamp(1, 5) as u8, monsters, hit_points, density, par_seconds }
+}
+
+/// The things of one map at `skill`; None for a bare marker.
+fn map_things(map: &MapLumps, skill: u8) -> Result<Option<Vec<PlacedThing>>, String> {
+    Ok(match map.format {
+        Some(MapFormat::Udmf) => Some(map_stats::udmf_things(&String::from_utf8_lossy(map.data("TEXTMAP")?), skill)),
+        Some(format) => Some(map_stats::binary_things(map.data("THINGS")?, format, skill)),
+        None => None,
+    })
+}
+
+/// A difficulty estimate for every map in a WAD/PK3 at `skill` (1-5), in
+/// directory order, taking monster health from the file's DEHACKED lumps
+/// and then `patches` (.deh/.bex files loaded with it), and par times from
+/// its MAPINFO. Maps without things data are left out.
+pub fn estimate_difficulty(path: &str, skill: u8, patches: &[&str]) -> Result<Vec<(String, DifficultyScore)>, String> {
+    let mut texts: Vec<String> = wad_parser::collect_lumps(path, &|name| name == "DEHACKED")?
+        .into_iter()
+        .map(|lump| String::from_utf8_lossy(&lump.data).to_string())
+        .collect();
+    for patch in patches {
+        let data = std::fs::read(patch).map_err(|e| format!("Failed to read {}: {}", patch, e))?;
+        texts.push(String::from_utf8_lossy(&data).to_string());
+    }
+    let health = monster_health(&texts);
+    let pars: HashMap<String, u32> = wad_parser::extract_levels(path, None)?
+        .into_iter()
+        .filter_map(|l| Some((wad_parser::marker_id(&l.id), l.par_seconds?)))
+        .collect();
+
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::new();
+    for map in wad_parser::read_maps(path, &["THINGS", "TEXTMAP"])? {
+        if !seen.insert(map.id.clone()) {
+            continue;
+        }
+        match map_things(&map, skill) {
+            Ok(Some(things)) => {
+                let par = pars.get(&map.id).copied();
+                out.push((map.id, score(&things, &health, par)));
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to read things of {} in {}: {}", map.id, path, e),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, write_temp};
+
+    fn things(list: &[(u16, i16, i16)]) -> Vec<u8> {
+        list.iter()
+            .flat_map(|&(kind, x, y)| [x as u16, y as u16, 0, kind, 0b111].into_iter().flat_map(u16::to_le_bytes))
+            .collect()
+    }
+
+    #[test]
+    fn rates_maps_by_health_density_and_par() {
+        let imps = |n: i16, spread: i16| -> Vec<(u16, i16, i16)> { (0..n).map(|i| (3001, i * spread, i * spread)).collect() };
+        let mut cybers = vec![(1, 0, 0)];
+        cybers.extend((0..12).map(|i| (16, i * 400, 0)));
+        let wad = write_temp(
+            "difficulty.wad",
+            &build_wad(
+                b"PWAD",
+                &[
+                    ("MAPINFO", b"map MAP03 \"Rush\"\n{\npar = 10\n}\n"),
+                    ("DEHACKED", b"Thing 12 (Imp)\nHit points = 600\n"),
+                    ("MAP01", b""),
+                    ("THINGS", &things(&[(1, 0, 0), (3004, 4000, 4000), (3004, 8000, 0)])),
+                    ("MAP02", b""),
+                    ("THINGS", &things(&cybers)),
+                    ("MAP03", b""),
+                    ("THINGS", &things(&imps(4, 2000))),
+                    ("MAP04", b""),
+                ],
+            ),
+        );
+        let scores = estimate_difficulty(&wad, 4, &[]).unwrap();
+        let summary: Vec<(&str, u8, u32, u64)> =
+            scores.iter().map(|(id, s)| (id.as_str(), s.rating, s.monsters, s.hit_points)).collect();
+        // Sparse zombiemen; twelve cyberdemons; DEHACKED imps against a par.
+        assert_eq!(summary, vec![("MAP01", 1, 2, 40), ("MAP02", 5, 12, 48000), ("MAP03", 3, 4, 2400)]);
+        assert_eq!(scores[2].1.par_seconds, Some(10));
+    }
+}
@@ -0,0 +1,198 @@
+// Mod downloads. The body streams to `<dest>.part`, so an interrupted
+// download resumes with a Range request instead of starting over, and the
+// file only appears under its real name once it is complete (and matches
+// the expected SHA-1, when one is known). A resumed response is only
+// appended when the server says it starts where the part file ends; any
+// other answer restarts the download. Redirects are followed by the HTTP
+// client, which idgames mirrors rely on.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::wad_parser::to_hex;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+    pub url: String,
+    pub downloaded: u64,
+    /// None when the server doesn't say how large the file is.
+    pub total: Option<u64>,
+}
+
+/// Minimum time between progress reports; the last one is always sent.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+pub fn part_path(dest: &Path) -> PathBuf {
+    let mut path = dest.as_os_str().to_owned();
+    path.push(".part");
+    PathBuf::from(path)
+}
+
+/// The full size from a Content-Range header ("bytes 100-199/1000").
+fn content_range_total(header: &str) -> Option<u64> {
+    header.rsplit('/').next()?.trim().parse().ok()
+}
+
+/// The first byte from a Content-Range header ("bytes 100-199/1000" ->
+/// 100); None for the "bytes */1000" a 416 carries.
+fn content_range_start(header: &str) -> Option<u64> {
+    header.trim().strip_prefix("bytes ")?.split('-').next()?.trim().parse().ok()
+}
+
+fn content_range(response: &reqwest::Response) -> Option<&str> {
+    response.headers().get(reqwest::header::CONTENT_RANGE).and_then(|v| v.to_str().ok())
+}
+
+fn file_sha1(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha1::new();
+    let mut buf = vec![0u8; 1 << 16];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Check the finished part file against `expected_sha1` and move it into
+/// place. A mismatching file is deleted, so a retry starts clean.
+fn finish(part: &Path, dest: &Path, expected_sha1: Option<&str>) -> Result<(), String> {
+    if let Some(expected) = expected_sha1 {
+        let actual = file_sha1(part)?;
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            fs::remove_file(part).map_err(|e| format!("Failed to remove {}: {}", part.display(), e))?;
+            return Err(format!("SHA-1 mismatch for {}: expected {}, got {}", dest.display(), expected, actual));
+        }
+    }
+    fs::rename(part, dest).map_err(|e| format!("Failed to move download to {}: {}", dest.display(), e))
+}
+
+/// Download `url` to `dest`, resuming a previous partial download, and
+/// report progress through `on_progress`. Returns the final path.
+pub async fn download(
+    url: &str,
+    dest: &str,
+    expected_sha1: Option<&str>,
+    on_progress: impl Fn(DownloadProgress),
+) -> Result<String, String> {
+    let dest = Path::new(dest);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let part = part_path(dest);
+    let client = reqwest::Client::new();
+    let mut offset = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(url);
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let mut response = request.send().await.map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The part file is as long as the resource when the previous
+        // attempt failed after the last byte; otherwise the resource
+        // changed, and guessing would corrupt it: start over.
+        if content_range(&response).and_then(content_range_total) == Some(offset) {
+            on_progress(DownloadProgress { url: url.to_string(), downloaded: offset, total: Some(offset) });
+            finish(&part, dest, expected_sha1)?;
+            return Ok(dest.to_string_lossy().to_string());
+        }
+        offset = 0;
+        response = client.get(url).send().await.map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    } else if offset > 0
+        && response.status() == reqwest::StatusCode::PARTIAL_CONTENT
+        && content_range(&response).and_then(content_range_start) != Some(offset)
+    {
+        eprintln!(
+            "Download of {}: asked to resume at byte {}, got range {:?}; starting over",
+            url,
+            offset,
+            content_range(&response)
+        );
+        offset = 0;
+        response = client.get(url).send().await.map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    }
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("Failed to download {}: HTTP {}", url, status));
+    }
+    // A 200 to a Range request means the server sent the whole file.
+    let resumed = offset > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let total = if resumed {
+        content_range(&response).and_then(content_range_total)
+    } else {
+        offset = 0;
+        response.content_length()
+    };
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part)
+        .map_err(|e| format!("Failed to open {}: {}", part.display(), e))?;
+    let mut downloaded = offset;
+    let mut last_report: Option<Instant> = None;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Download of {} interrupted: {}", url, e))?;
+        file.write_all(&chunk).map_err(|e| format!("Failed to write {}: {}", part.display(), e))?;
+        downloaded += chunk.len() as u64;
+        if last_report.is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL) {
+            on_progress(DownloadProgress { url: url.to_string(), downloaded, total });
+            last_report = Some(Instant::now());
+        }
+    }
+    file.flush().map_err(|e| format!("Failed to write {}: {}", part.display(), e))?;
+    drop(file);
+    on_progress(DownloadProgress { url: url.to_string(), downloaded, total });
+    if let Some(total) = total
+        && downloaded != total
+    {
+        return Err(format!("Download of {} ended at {} of {} bytes", url, downloaded, total));
+    }
+
+    finish(&part, dest, expected_sha1)?;
+    Ok(dest.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_content_range_totals() {
+        assert_eq!(content_range_total("bytes 100-199/1000"), Some(1000));
+        assert_eq!(content_range_total("bytes 100-199/*"), None);
+        assert_eq!(content_range_start("bytes 100-199/1000"), Some(100));
+        assert_eq!(content_range_start("bytes */1000"), None);
+    }
+
+    #[test]
+    fn verifies_checksum_before_moving_into_place() {
+        let dir = std::env::temp_dir().join(format!("download_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let dest = dir.join("mod.zip");
+        let part = part_path(&dest);
+        assert!(part.to_string_lossy().ends_with("mod.zip.part"));
+
+        fs::write(&part, b"abc").unwrap();
+        let err = finish(&part, &dest, Some("0000")).unwrap_err();
+        assert!(err.contains("SHA-1 mismatch"), "{}", err);
+        assert!(!part.exists() && !dest.exists());
+
+        fs::write(&part, b"abc").unwrap();
+        finish(&part, &dest, Some("A9993E364706816ABA3E25717850C26C9CD0D89D")).unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"abc");
+    }
+}
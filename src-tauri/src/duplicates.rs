@@ -0,0 +1,81 @@
+// Duplicate files in the mods library: the same WAD downloaded twice, or
+// saved as both "sunder.wad" and "SUNDER (1).wad". Files are grouped by
+// size first, so only files that could be identical are hashed, then by
+// MD5.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::wad_parser;
+
+/// Extensions of the files worth comparing; readmes and screenshots that
+/// ship in several mods are not duplicates worth cleaning up.
+const MOD_EXTENSIONS: &[&str] = &["wad", "pk3", "pk7", "ipk3", "zip", "deh", "bex"];
+
+fn is_mod_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| MOD_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+/// Groups of identical files under `dir` (recursively), each sorted by
+/// path and the groups by their first path. Files that can't be read are
+/// logged and left out rather than failing the whole scan.
+pub fn find_duplicate_wads(dir: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    crate::gog_import::walk_files(Path::new(dir), &mut files)?;
+    files.retain(|p| is_mod_file(p));
+
+    let mut by_size: HashMap<u64, Vec<String>> = HashMap::new();
+    for path in files {
+        match std::fs::metadata(&path) {
+            // Empty files are all "identical" and not worth reporting.
+            Ok(meta) if meta.len() > 0 => {
+                by_size.entry(meta.len()).or_default().push(path.to_string_lossy().to_string())
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    let mut groups = Vec::new();
+    for paths in by_size.into_values().filter(|paths| paths.len() > 1) {
+        let mut by_md5: HashMap<String, Vec<String>> = HashMap::new();
+        for path in paths {
+            match wad_parser::file_md5(&path) {
+                Ok(md5) => by_md5.entry(md5).or_default().push(path),
+                Err(e) => eprintln!("Skipping {}: {}", path, e),
+            }
+        }
+        groups.extend(by_md5.into_values().filter(|group| group.len() > 1));
+    }
+    for group in &mut groups {
+        group.sort();
+    }
+    groups.sort();
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_archives::create_temp_dir;
+
+    #[test]
+    fn groups_identical_files_by_content() {
+        let dir = create_temp_dir().unwrap();
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        let write = |name: &str, bytes: &[u8]| std::fs::write(dir.join(name), bytes).unwrap();
+        write("sunder.wad", b"PWAD-sunder");
+        write("sub/SUNDER (1).WAD", b"PWAD-sunder");
+        write("other.wad", b"PWAD-other!"); // same size, different content
+        write("readme.txt", b"PWAD-sunder");
+        write("empty1.wad", b"");
+        write("empty2.wad", b"");
+
+        let groups = find_duplicate_wads(dir.to_str().unwrap()).unwrap();
+        let name = |p: &String| Path::new(p).file_name().unwrap().to_string_lossy().to_string();
+        let names: Vec<Vec<String>> = groups.iter().map(|g| g.iter().map(name).collect()).collect();
+        assert_eq!(names, vec![vec!["SUNDER (1).WAD".to_string(), "sunder.wad".to_string()]]);
+    }
+}
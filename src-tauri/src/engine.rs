@@ -0,0 +1,303 @@
+// Source-port identification. The launcher started out GZDoom-only; other
+// ports (PrBoom+, DSDA-Doom, Crispy, …) take different arguments, so the
+// launch path needs to know which engine it is driving. The kind is either
+// passed explicitly by the frontend or inferred from the executable name.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::launch_error::LaunchError;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineKind {
+    GZDoom,
+    ZDoom,
+    PrBoom,
+    Dsda,
+    Eternity,
+    Crispy,
+    Chocolate,
+    Woof,
+    Zandronum,
+}
+
+impl EngineKind {
+    pub const ALL: [EngineKind; 9] = [
+        EngineKind::GZDoom,
+        EngineKind::ZDoom,
+        EngineKind::PrBoom,
+        EngineKind::Dsda,
+        EngineKind::Eternity,
+        EngineKind::Crispy,
+        EngineKind::Chocolate,
+        EngineKind::Woof,
+        EngineKind::Zandronum,
+    ];
+
+    /// Human-readable name for error messages.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            EngineKind::GZDoom => "GZDoom",
+            EngineKind::ZDoom => "ZDoom",
+            EngineKind::PrBoom => "PrBoom+",
+            EngineKind::Dsda => "DSDA-Doom",
+            EngineKind::Eternity => "Eternity",
+            EngineKind::Crispy => "Crispy Doom",
+            EngineKind::Chocolate => "Chocolate Doom",
+            EngineKind::Woof => "Woof!",
+            EngineKind::Zandronum => "Zandronum",
+        }
+    }
+
+    /// Infer the engine from an executable's basename. Order matters:
+    /// "gzdoom" contains "zdoom", so the more specific names go first.
+    pub fn from_executable(engine_path: &str) -> Option<EngineKind> {
+        let stem = executable_stem(engine_path);
+        const PATTERNS: &[(&str, EngineKind)] = &[
+            ("gzdoom", EngineKind::GZDoom),
+            ("uzdoom", EngineKind::GZDoom),
+            ("vkdoom", EngineKind::GZDoom),
+            ("zandronum", EngineKind::Zandronum),
+            ("lzdoom", EngineKind::ZDoom),
+            ("qzdoom", EngineKind::ZDoom),
+            ("zdoom", EngineKind::ZDoom),
+            ("dsda", EngineKind::Dsda),
+            ("prboom", EngineKind::PrBoom),
+            ("glboom", EngineKind::PrBoom),
+            ("eternity", EngineKind::Eternity),
+            ("crispy", EngineKind::Crispy),
+            ("chocolate", EngineKind::Chocolate),
+            ("woof", EngineKind::Woof),
+        ];
+        PATTERNS
+            .iter()
+            .find(|(pattern, _)| stem.contains(pattern))
+            .map(|(_, kind)| *kind)
+    }
+}
+
+fn executable_stem(engine_path: &str) -> String {
+    // Split on both separators so Windows paths parse the same on every host.
+    let basename = engine_path.rsplit(['/', '\\']).next().unwrap_or(engine_path);
+    Path::new(basename)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Sanity check that a configured engine path names a known source port
+/// before we exec it. This guards against misconfiguration (picking the
+/// wrong file in Settings), not a hostile webview — it is not a security
+/// boundary. The executable's basename must name the engine; a mere
+/// substring anywhere in the path (/tmp/gzdoom-stuff/other) doesn't pass.
+pub fn validate_engine_path(engine_path: &str) -> Result<EngineKind, LaunchError> {
+    EngineKind::from_executable(engine_path).ok_or_else(|| LaunchError::UnknownEngine {
+        path: engine_path.to_string(),
+        message: format!(
+            "Invalid engine path: expected a known source port executable (GZDoom, PrBoom+, DSDA-Doom, …), got '{}'. Select the engine type explicitly if the binary was renamed.",
+            engine_path
+        ),
+    })
+}
+
+/// Resolve which engine is being launched. An explicit kind wins — that is
+/// how renamed binaries and forks get through — but then the path must at
+/// least be an existing file, since the name no longer vouches for it.
+pub fn resolve_engine(engine_path: &str, kind: Option<EngineKind>) -> Result<EngineKind, LaunchError> {
+    match kind {
+        Some(kind) => {
+            engine_file(engine_path)?;
+            Ok(kind)
+        }
+        None => validate_engine_path(engine_path),
+    }
+}
+
+/// CFBundleExecutable from an XML Info.plist. Binary plists aren't read;
+/// callers fall back to looking in Contents/MacOS.
+fn plist_executable(plist: &str) -> Option<String> {
+    let after_key = &plist[plist.find("<key>CFBundleExecutable</key>")? + "<key>CFBundleExecutable</key>".len()..];
+    let value = after_key.trim_start().strip_prefix("<string>")?;
+    let name = value[..value.find("</string>")?].trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// The executable inside a macOS app bundle, for a path to the .app
+/// itself; any other path is returned as is. The bundle can only be
+/// started through `open`, which detaches the engine from the launcher's
+/// pipes, so nothing reaches the log. The binary is the one Info.plist's
+/// CFBundleExecutable names; without one, the file in Contents/MacOS named
+/// like the bundle, else the only file there. NotFound when the bundle has
+/// no such binary.
+pub fn bundle_executable(engine_path: &str) -> Result<String, LaunchError> {
+    let bundle = Path::new(engine_path.trim_end_matches(['/', '\\']));
+    let is_bundle = bundle.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("app")) && bundle.is_dir();
+    if !is_bundle {
+        return Ok(engine_path.to_string());
+    }
+    let not_found = |message: String| LaunchError::NotFound { path: engine_path.to_string(), message };
+    let contents = bundle.join("Contents");
+    let macos = contents.join("MacOS");
+    if let Ok(plist) = std::fs::read_to_string(contents.join("Info.plist"))
+        && let Some(name) = plist_executable(&plist)
+    {
+        let binary = macos.join(&name);
+        if !binary.is_file() {
+            return Err(not_found(format!("{} names {} as its executable, but {} is missing", bundle.display(), name, binary.display())));
+        }
+        return Ok(binary.to_string_lossy().to_string());
+    }
+    let binaries: Vec<PathBuf> = std::fs::read_dir(&macos)
+        .map_err(|e| not_found(format!("Failed to read {}: {}", macos.display(), e)))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    let wanted = executable_stem(&bundle.to_string_lossy());
+    let named = binaries.iter().find(|p| executable_stem(&p.to_string_lossy()) == wanted);
+    match (named, binaries.as_slice()) {
+        (Some(binary), _) | (None, [binary]) => Ok(binary.to_string_lossy().to_string()),
+        _ => Err(not_found(format!("No engine executable found in {}", macos.display()))),
+    }
+}
+
+/// The engine's metadata: NotFound when nothing is at `engine_path`,
+/// NotExecutable when it isn't a file. The one existence check of the
+/// launch path, shared by resolve_engine and check_executable.
+fn engine_file(engine_path: &str) -> Result<std::fs::Metadata, LaunchError> {
+    let path = engine_path.to_string();
+    let meta = match std::fs::metadata(engine_path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(LaunchError::NotFound { message: format!("Engine not found at '{}'", engine_path), path });
+        }
+        Err(e) => return Err(format!("Failed to read engine at '{}': {}", engine_path, e).into()),
+    };
+    if !meta.is_file() {
+        return Err(LaunchError::NotExecutable { message: format!("Engine path is not a file: '{}'", engine_path), path });
+    }
+    Ok(meta)
+}
+
+/// Check the engine binary can actually be executed, so a launch fails
+/// with "not found" or "not executable" instead of a bare OS error. A
+/// freshly downloaded or copied binary often lacks the execute bit on
+/// Unix; with `make_executable` (the user agreed to fix it) the bit is set
+/// for everyone who can read the file, as `chmod +x` would.
+pub fn check_executable(engine_path: &str, make_executable: bool) -> Result<(), LaunchError> {
+    let meta = engine_file(engine_path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = meta.permissions().mode();
+        if mode & 0o111 == 0 {
+            if !make_executable {
+                return Err(LaunchError::NotExecutable {
+                    message: format!(
+                        "Engine at '{}' is not executable. Allow the launcher to fix its permissions, or run: chmod +x \"{}\"",
+                        engine_path, engine_path
+                    ),
+                    path: engine_path.to_string(),
+                });
+            }
+            // r bits shifted onto x bits: +x wherever the file is readable.
+            let mode = mode | ((mode & 0o444) >> 2);
+            std::fs::set_permissions(engine_path, std::fs::Permissions::from_mode(mode))
+                .map_err(|e| format!("Failed to make '{}' executable: {}", engine_path, e))?;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = make_executable;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_engine_basenames_rejects_substring_paths() {
+        assert!(validate_engine_path("/Applications/GZDoom.app/Contents/MacOS/gzdoom").is_ok());
+        assert!(validate_engine_path("C:\\Program Files\\UZDoom\\uzdoom.exe").is_ok());
+        assert!(validate_engine_path("/opt/homebrew/bin/gzdoom-4.11").is_ok());
+        // "gzdoom" in a parent directory is not enough.
+        assert!(validate_engine_path("/tmp/gzdoom-evil/malware").is_err());
+        assert!(validate_engine_path("/usr/bin/doom").is_err());
+    }
+
+    #[test]
+    fn infers_kind_from_port_names() {
+        let kind = |p| EngineKind::from_executable(p);
+        assert_eq!(kind("/usr/bin/gzdoom"), Some(EngineKind::GZDoom));
+        assert_eq!(kind("/usr/bin/lzdoom"), Some(EngineKind::ZDoom));
+        assert_eq!(kind("/usr/local/bin/dsda-doom"), Some(EngineKind::Dsda));
+        assert_eq!(kind("/usr/bin/prboom-plus"), Some(EngineKind::PrBoom));
+        assert_eq!(kind("C:\\Games\\woof.exe"), Some(EngineKind::Woof));
+        assert_eq!(kind("/usr/games/chocolate-doom"), Some(EngineKind::Chocolate));
+        assert_eq!(kind("/usr/games/crispy-heretic"), Some(EngineKind::Crispy));
+        assert_eq!(kind("/opt/zandronum/zandronum"), Some(EngineKind::Zandronum));
+        assert_eq!(kind("/opt/eternity/eternity"), Some(EngineKind::Eternity));
+    }
+
+    #[test]
+    fn explicit_kind_requires_existing_file() {
+        let path = std::env::temp_dir().join(format!("engine_test_{}_mybuild", std::process::id()));
+        std::fs::write(&path, b"#!/bin/sh\n").unwrap();
+        let p = path.to_str().unwrap();
+        // Renamed binary: unrecognized by name, accepted with an explicit kind.
+        assert!(resolve_engine(p, None).is_err());
+        assert_eq!(resolve_engine(p, Some(EngineKind::Dsda)), Ok(EngineKind::Dsda));
+        let err = resolve_engine("/nonexistent/mybuild", Some(EngineKind::Woof)).unwrap_err();
+        assert!(matches!(&err, LaunchError::NotFound { path, .. } if path == "/nonexistent/mybuild"), "{:?}", err);
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+
+    #[test]
+    fn finds_the_binary_inside_app_bundles() {
+        let root = std::env::temp_dir().join(format!("engine_test_{}_bundles", std::process::id()));
+        let contents = root.join("GZDoom.app").join("Contents");
+        let macos = contents.join("MacOS");
+        std::fs::create_dir_all(&macos).unwrap();
+        std::fs::write(macos.join("gzdoom"), b"").unwrap();
+        std::fs::write(macos.join("helper"), b"").unwrap();
+        // Left over from an earlier run.
+        let _ = std::fs::remove_file(contents.join("Info.plist"));
+        let bundle = root.join("GZDoom.app").to_string_lossy().to_string();
+        let gzdoom = macos.join("gzdoom").to_string_lossy().to_string();
+        assert_eq!(bundle_executable(&bundle), Ok(gzdoom.clone()));
+        assert_eq!(bundle_executable(&format!("{}/", bundle)), Ok(gzdoom));
+        assert_eq!(bundle_executable("/usr/bin/gzdoom"), Ok("/usr/bin/gzdoom".to_string()));
+
+        let plist = |name: &str| format!("<plist><dict>\n<key>CFBundleExecutable</key>\n\t<string>{}</string>\n</dict></plist>", name);
+        std::fs::write(contents.join("Info.plist"), plist("helper")).unwrap();
+        assert_eq!(bundle_executable(&bundle), Ok(macos.join("helper").to_string_lossy().to_string()));
+        std::fs::write(contents.join("Info.plist"), plist("missing")).unwrap();
+        assert!(matches!(bundle_executable(&bundle), Err(LaunchError::NotFound { .. })));
+    }
+
+    #[test]
+    fn serializes_lowercase() {
+        assert_eq!(serde_json::to_string(&EngineKind::GZDoom).unwrap(), "\"gzdoom\"");
+        assert_eq!(serde_json::to_string(&EngineKind::Dsda).unwrap(), "\"dsda\"");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reports_and_fixes_missing_execute_bit() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!("engine_test_{}_noexec", std::process::id()));
+        std::fs::write(&path, b"#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        let p = path.to_str().unwrap();
+        let err = check_executable(p, false).unwrap_err();
+        assert!(matches!(err, LaunchError::NotExecutable { .. }), "{:?}", err);
+        assert!(err.to_string().contains("not executable"));
+        check_executable(p, true).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o750);
+        check_executable(p, false).unwrap();
+        let err = check_executable("/nonexistent/gzdoom", true).unwrap_err();
+        assert!(matches!(err, LaunchError::NotFound { .. }), "{:?}", err);
+        assert!(err.to_string().contains("not found"));
+    }
+}
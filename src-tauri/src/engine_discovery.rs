@@ -0,0 +1,120 @@
+// Finding installed GZDoom-family engines, so first-run setup can offer
+// them instead of asking the user to browse to a binary they may never have
+// seen. Only well-known install locations are checked, no disk-wide search:
+// app bundles and Homebrew on macOS, Program Files and winget on Windows,
+// the usual bin directories, snap and flatpak exports on Linux.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::engine_version::VersionInfo;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineInstall {
+    pub path: String,
+    pub version: VersionInfo,
+}
+
+/// Executable names of the GZDoom family, without extension.
+const ENGINE_NAMES: &[&str] = &["gzdoom", "uzdoom", "vkdoom"];
+
+#[cfg(target_os = "macos")]
+pub fn candidate_paths() -> Vec<PathBuf> {
+    let mut roots = vec![PathBuf::from("/Applications")];
+    if let Some(home) = std::env::var_os("HOME") {
+        roots.push(Path::new(&home).join("Applications"));
+    }
+    let mut paths = Vec::new();
+    for root in &roots {
+        for (bundle, exe) in [("GZDoom.app", "gzdoom"), ("UZDoom.app", "uzdoom"), ("VKDoom.app", "vkdoom")] {
+            paths.push(root.join(bundle).join("Contents/MacOS").join(exe));
+        }
+    }
+    // Homebrew: Apple Silicon prefix, then Intel.
+    for bin in ["/opt/homebrew/bin", "/usr/local/bin"] {
+        paths.extend(ENGINE_NAMES.iter().map(|name| Path::new(bin).join(name)));
+    }
+    paths
+}
+
+#[cfg(target_os = "windows")]
+pub fn candidate_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+        if let Some(dir) = std::env::var_os(var) {
+            for (folder, exe) in [("GZDoom", "gzdoom.exe"), ("UZDoom", "uzdoom.exe"), ("VKDoom", "vkdoom.exe")] {
+                paths.push(Path::new(&dir).join(folder).join(exe));
+            }
+        }
+    }
+    if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+        let winget = Path::new(&local).join("Microsoft").join("WinGet");
+        paths.extend(ENGINE_NAMES.iter().map(|name| winget.join("Links").join(format!("{}.exe", name))));
+        // Portable winget packages unpack to Packages/<Id>_<source>/.
+        if let Ok(entries) = std::fs::read_dir(winget.join("Packages")) {
+            for entry in entries.flatten() {
+                let folder = entry.file_name().to_string_lossy().to_lowercase();
+                for name in ENGINE_NAMES.iter().filter(|name| folder.contains(*name)) {
+                    paths.push(entry.path().join(format!("{}.exe", name)));
+                }
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(target_os = "linux")]
+pub fn candidate_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for bin in ["/usr/bin", "/usr/local/bin", "/usr/games", "/snap/bin"] {
+        paths.extend(ENGINE_NAMES.iter().map(|name| Path::new(bin).join(name)));
+    }
+    // Flatpak exports a launcher script per app id, system-wide and per user.
+    let mut exports = vec![PathBuf::from("/var/lib/flatpak/exports/bin")];
+    if let Some(home) = std::env::var_os("HOME") {
+        exports.push(Path::new(&home).join(".local/share/flatpak/exports/bin"));
+    }
+    for dir in &exports {
+        paths.push(dir.join("org.zdoom.GZDoom"));
+        paths.push(dir.join("org.zdoom.UZDoom"));
+    }
+    paths
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+pub fn candidate_paths() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// The candidates that exist, in order, skipping ones that resolve to an
+/// executable already listed (/usr/local/bin/gzdoom linking to /usr/bin).
+pub fn existing(candidates: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|path| path.is_file())
+        .filter(|path| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_existing_candidates_once() {
+        let dir = std::env::temp_dir().join(format!("engine_discovery_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let real = dir.join("gzdoom");
+        let link = dir.join("gzdoom-link");
+        std::fs::write(&real, b"").unwrap();
+        if !link.exists() {
+            std::os::unix::fs::symlink(&real, &link).unwrap();
+        }
+        let found = existing([dir.join("uzdoom"), real.clone(), link, dir.clone()]);
+        assert_eq!(found, vec![real]);
+    }
+}
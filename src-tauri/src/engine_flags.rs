@@ -0,0 +1,72 @@
+// Which command-line flags each source port takes. A flag a port doesn't
+// know is ignored at best; at worst the launch fails or the value after it
+// is taken for something else. Probing `-help` isn't an option, since most
+// ports have none and start the game instead, so this is a table: the vanilla
+// flags every port inherited, then per-port flags with the release that
+// added them when that came later than the port itself. Anything not in
+// the table counts as unsupported.
+
+use crate::engine::EngineKind::{self, *};
+use crate::engine_version::VersionInfo;
+
+/// Flags of the original executables, which every supported port keeps.
+const VANILLA_FLAGS: &[&str] = &[
+    "-iwad", "-file", "-deh", "-warp", "-skill", "-episode", "-fast", "-nomonsters", "-respawn",
+    "-deathmatch", "-altdeath", "-record", "-playdemo", "-timedemo", "-config", "-nosound",
+    "-nomusic", "-nosfx",
+];
+
+/// (flag, ports that take it, first release that did when not all of them).
+type PortFlag = (&'static str, &'static [EngineKind], Option<(u32, u32, u32)>);
+
+const PORT_FLAGS: &[PortFlag] = &[
+    ("-savedir", &[GZDoom, ZDoom, Zandronum], None),
+    ("-savedir", &[Chocolate], Some((3, 0, 0))),
+    ("-savedir", &[Crispy], Some((5, 0, 0))),
+    ("-save", &[PrBoom, Dsda, Woof], None),
+    ("-complevel", &[PrBoom, Dsda], None),
+    ("-complevel", &[Woof], Some((6, 0, 0))),
+    ("+map", &[GZDoom, ZDoom, Zandronum], None),
+    ("-host", &[GZDoom, ZDoom], None),
+    ("-join", &[GZDoom, ZDoom], None),
+    ("-server", &[Chocolate, Crispy, Woof], None),
+    ("-connect", &[Chocolate, Crispy, Woof, Zandronum], None),
+    ("-stdout", &[GZDoom, ZDoom], None),
+    ("-levelstat", &[Dsda], None),
+    ("-analysis", &[Dsda], None),
+    ("-skipsec", &[PrBoom, Dsda], None),
+];
+
+/// Whether `kind` takes `flag`; a value after the flag ("-complevel 21")
+/// is ignored. `version` is only asked for when the answer depends on
+/// it, since finding it out may mean running the engine, and an unknown
+/// version doesn't qualify.
+pub fn supports_flag(kind: EngineKind, flag: &str, version: impl FnOnce() -> VersionInfo) -> bool {
+    let flag = flag.split_whitespace().next().unwrap_or("").to_lowercase();
+    if VANILLA_FLAGS.contains(&flag.as_str()) {
+        return true;
+    }
+    match PORT_FLAGS.iter().find(|(f, kinds, _)| *f == flag && kinds.contains(&kind)) {
+        None => false,
+        Some((_, _, None)) => true,
+        Some((_, _, Some(since))) => version().key().is_some_and(|have| have >= *since),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_version::parse_version;
+
+    #[test]
+    fn looks_up_flags_by_port_and_version() {
+        let never = || -> VersionInfo { panic!("version not needed") };
+        assert!(supports_flag(Chocolate, "-WARP", never));
+        assert!(supports_flag(Dsda, "-complevel 21", never));
+        assert!(!supports_flag(Chocolate, "-complevel", never));
+        assert!(!supports_flag(GZDoom, "-made-up", never));
+        assert!(supports_flag(Woof, "-complevel", || parse_version("Woof 14.5.0")));
+        assert!(!supports_flag(Woof, "-complevel", || parse_version("Woof 5.1.0")));
+        assert!(!supports_flag(Chocolate, "-savedir", VersionInfo::unknown));
+    }
+}
@@ -0,0 +1,155 @@
+// Engine version detection. Platform metadata (Info.plist, Windows version
+// resources) is the cheap source. Only ports known to print a version and
+// exit are asked with a flag, under a timeout all the same: the others,
+// the ZDoom family among them, start the game on a flag they don't know.
+// The startup banner of a previous session is the last resort.
+
+use serde::Serialize;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::engine::EngineKind;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    /// The string the version was parsed from, or "unknown".
+    pub raw: String,
+    pub major: Option<u32>,
+    pub minor: Option<u32>,
+    pub patch: Option<u32>,
+    /// Letters before the number, like GZDoom's "g" in "g4.14.2".
+    pub prefix: Option<String>,
+}
+
+impl VersionInfo {
+    pub fn unknown() -> Self {
+        VersionInfo { raw: "unknown".to_string(), major: None, minor: None, patch: None, prefix: None }
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.major.is_some()
+    }
+
+    /// (major, minor, patch) for comparisons, a missing patch counting as
+    /// 0; None without at least major and minor.
+    pub fn key(&self) -> Option<(u32, u32, u32)> {
+        Some((self.major?, self.minor?, self.patch.unwrap_or(0)))
+    }
+}
+
+/// Find the first version-looking word in `raw`: optional letters, then
+/// dot-separated numbers with at least one dot ("g4.14.2", "v0.28.1",
+/// "2.6.66um"). Trailing non-digits on the last part are ignored.
+pub fn parse_version(raw: &str) -> VersionInfo {
+    let raw = raw.trim();
+    for word in raw.split(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == ',') {
+        let prefix: String = word.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+        // "-version" style words and names like "dsda-doom" aren't versions.
+        let rest = &word[prefix.len()..];
+        if prefix.len() > 1 || !rest.contains('.') {
+            continue;
+        }
+        let parts: Vec<Option<u32>> = rest
+            .split('.')
+            .map(|p| {
+                let digits: String = p.chars().take_while(|c| c.is_ascii_digit()).collect();
+                digits.parse().ok()
+            })
+            .collect();
+        if parts.len() < 2 || parts[0].is_none() || parts[1].is_none() {
+            continue;
+        }
+        return VersionInfo {
+            raw: raw.to_string(),
+            major: parts[0],
+            minor: parts[1],
+            patch: parts.get(2).copied().flatten(),
+            prefix: (!prefix.is_empty()).then_some(prefix),
+        };
+    }
+    VersionInfo { raw: raw.to_string(), ..VersionInfo::unknown() }
+}
+
+/// The flag that makes `kind` print its version and exit: Chocolate Doom's
+/// `--version`, which Crispy Doom and Woof! inherited. None for the ports
+/// that would start the game instead.
+pub fn version_flag(kind: EngineKind) -> Option<&'static str> {
+    match kind {
+        EngineKind::Chocolate | EngineKind::Crispy | EngineKind::Woof => Some("--version"),
+        _ => None,
+    }
+}
+
+/// Run `engine_path flag` and return its first non-empty output line, or
+/// None if it didn't exit within `timeout` (it was killed) or said nothing.
+pub fn probe(engine_path: &str, flag: &str, timeout: Duration) -> Result<Option<String>, String> {
+    let mut child = Command::new(engine_path)
+        .arg(flag)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run engine with {}: {}", flag, e))?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(25)),
+            Ok(None) => {
+                // Didn't understand the flag and started the game: stop it.
+                if let Err(e) = child.kill() {
+                    eprintln!("Failed to stop version probe: {}", e);
+                }
+                let _ = child.wait();
+                return Ok(None);
+            }
+            Err(e) => return Err(format!("Failed to wait for engine: {}", e)),
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to read engine output: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Ok(stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_port_version_strings() {
+        let v = parse_version("GZDoom g4.14.2 - 2025-05-04 22:20:32");
+        assert_eq!((v.major, v.minor, v.patch), (Some(4), Some(14), Some(2)));
+        assert_eq!(v.prefix.as_deref(), Some("g"));
+        let v = parse_version("dsda-doom v0.28.1");
+        assert_eq!((v.major, v.minor, v.patch, v.prefix.as_deref()), (Some(0), Some(28), Some(1), Some("v")));
+        let v = parse_version("PrBoom-Plus 2.6.66um");
+        assert_eq!((v.major, v.minor, v.patch), (Some(2), Some(6), Some(66)));
+        assert!(!parse_version("Usage: woof [options]").is_known());
+        assert_eq!(parse_version("  ").raw, "");
+    }
+
+    #[test]
+    fn probes_only_ports_that_print_and_exit() {
+        assert_eq!(version_flag(EngineKind::Chocolate), Some("--version"));
+        assert_eq!(version_flag(EngineKind::GZDoom), None);
+        assert_eq!(version_flag(EngineKind::Dsda), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn probe_times_out_on_engines_that_ignore_the_flag() {
+        assert_eq!(probe("/bin/echo", "v1.2.3", Duration::from_secs(5)).unwrap().as_deref(), Some("v1.2.3"));
+        let started = Instant::now();
+        assert_eq!(probe("/bin/sleep", "30", Duration::from_millis(200)).unwrap(), None);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
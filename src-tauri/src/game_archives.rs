@@ -68,13 +68,11 @@ pub fn validate_game_file(path: &str, filename: &str) -> Result<(), String> {
         .map_err(|e| format!("Failed to read {}: {}", path, e))?;
 
     match ext.as_str() {
-        "zip" | "pk3" => {
-            if read < 2 || magic[0] != 0x50 || magic[1] != 0x4b {
-                return Err(format!(
-                    "Invalid ZIP file: {} - file appears corrupted or is not a ZIP archive (got {} bytes, magic: {:02x} {:02x})",
-                    filename, file_len, magic[0], magic[1]
-                ));
-            }
+        "zip" | "pk3" if read < 2 || magic[0] != 0x50 || magic[1] != 0x4b => {
+            return Err(format!(
+                "Invalid ZIP file: {} - file appears corrupted or is not a ZIP archive (got {} bytes, magic: {:02x} {:02x})",
+                filename, file_len, magic[0], magic[1]
+            ));
         }
         "wad" => {
             if read < 4 {
@@ -129,6 +127,129 @@ pub fn extract_game_files(zip_path: &str, dest_dir: &str) -> Result<Vec<Extracte
     Ok(extracted)
 }
 
+/// Extensions extract_archive unpacks: game data and DEHACKED patches.
+const ARCHIVE_FILES: &[&str] = &[".wad", ".pk3", ".pk7", ".pke", ".ipk3", ".deh", ".bex"];
+
+/// Unpack a downloaded mod archive into `dest_dir`, keeping its folder
+/// layout: WAD/PK3 and DEH/BEX files, plus .txt files when `include_text`
+/// (readmes are junk in a mods folder unless the user wants them). An entry
+/// whose path would land outside `dest_dir` (zip-slip: "../", absolute
+/// paths) fails the whole extraction. Returns the extracted paths.
+pub fn extract_archive(zip_path: &str, dest_dir: &str, include_text: bool) -> Result<Vec<String>, String> {
+    std::fs::create_dir_all(dest_dir)
+        .map_err(|e| format!("Failed to create {}: {}", dest_dir, e))?;
+    let mut archive = open_archive(zip_path)?;
+    let mut extracted = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read ZIP entry #{}: {}", i, e))?;
+        let lower = entry.name().to_lowercase();
+        let wanted = ARCHIVE_FILES.iter().any(|ext| lower.ends_with(ext))
+            || (include_text && lower.ends_with(".txt"));
+        if entry.is_dir() || !wanted {
+            continue;
+        }
+        let relative = entry.enclosed_name().ok_or_else(|| {
+            format!("Refusing to extract {}: path escapes the destination", entry.name())
+        })?;
+        let dest_path = Path::new(dest_dir).join(relative);
+        if let Some(parent) = dest_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut out = File::create(&dest_path)
+            .map_err(|e| format!("Failed to create {}: {}", dest_path.display(), e))?;
+        io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("Failed to extract {}: {}", dest_path.display(), e))?;
+        extracted.push(dest_path.to_string_lossy().to_string());
+    }
+    Ok(extracted)
+}
+
+/// What to do with an archive before launching it.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ArchiveRecommendation {
+    /// A resource archive (PK3 layout at the root): load it with -file.
+    Load,
+    /// A wrapper around WADs/PK3s, like an idgames download: extract
+    /// them and load those.
+    ExtractFiles,
+    /// A PK3 layout one folder down ("mymod/zscript.txt"), which the engine
+    /// doesn't see: extract it and load the folder.
+    ExtractFolder,
+    /// Neither game files nor resources.
+    NotAMod,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveAdvice {
+    pub recommendation: ArchiveRecommendation,
+    /// Entry paths of the WAD/PK3 files to load after extracting.
+    pub files: Vec<String>,
+    /// The folder holding the resources, for ExtractFolder.
+    pub folder: Option<String>,
+    pub message: String,
+}
+
+/// Top-level folders of a PK3, per the GZDoom wiki's namespace list.
+const RESOURCE_DIRS: &[&str] = &[
+    "acs", "actors", "brightmaps", "colormaps", "filter", "flats", "graphics", "hires", "maps", "materials",
+    "models", "music", "patches", "shaders", "sounds", "sprites", "textures", "voices", "voxels", "zscript",
+];
+
+/// Definition lumps a PK3 keeps at its root.
+const RESOURCE_LUMPS: &[&str] = &[
+    "MAPINFO", "ZMAPINFO", "UMAPINFO", "EMAPINFO", "DECORATE", "ZSCRIPT", "SNDINFO", "GLDEFS", "KEYCONF",
+    "LANGUAGE", "DEHACKED", "ANIMDEFS", "TEXTURES", "GAMEINFO", "MENUDEF", "CVARINFO", "LOADACS", "SBARINFO",
+    "MUSINFO", "LOCKDEFS", "MODELDEF", "TERRAIN", "PLAYPAL", "COLORMAP", "TEXTCOLO", "TEXTURE1", "PNAMES",
+];
+
+/// Whether an entry path, relative to some root, is a PK3 resource there.
+fn is_resource(relative: &str) -> bool {
+    match relative.split_once(['/', '\\']) {
+        Some((dir, _)) => RESOURCE_DIRS.contains(&dir.to_lowercase().as_str()),
+        None => {
+            let stem = relative.split('.').next().unwrap_or(relative).to_uppercase();
+            RESOURCE_LUMPS.contains(&stem.as_str())
+        }
+    }
+}
+
+/// Tell a loadable archive (a .pk3/.pke, or a .zip laid out like one) from
+/// one that needs extracting first, by where its resources are: at the
+/// root, one folder down or nowhere, in which case the WADs inside are
+/// what to load.
+pub fn inspect_archive(zip_path: &str) -> Result<ArchiveAdvice, String> {
+    let entries = list_zip_entries(zip_path)?;
+    let advice = |recommendation, files: Vec<String>, folder: Option<String>, message: String| ArchiveAdvice {
+        recommendation,
+        files,
+        folder,
+        message,
+    };
+    if entries.iter().any(|e| is_resource(&e.path)) {
+        return Ok(advice(ArchiveRecommendation::Load, Vec::new(), None, format!("{} is a resource archive; load it as is", zip_path)));
+    }
+    let wrapped = entries.iter().find_map(|e| {
+        let (folder, rest) = e.path.split_once(['/', '\\'])?;
+        is_resource(rest).then(|| folder.to_string())
+    });
+    if let Some(folder) = wrapped {
+        let message = format!("{} keeps its resources in the folder {}/, which the engine won't see; extract it and load the folder", zip_path, folder);
+        return Ok(advice(ArchiveRecommendation::ExtractFolder, Vec::new(), Some(folder), message));
+    }
+    let files: Vec<String> = entries.iter().filter(|e| is_game_file(&e.path)).map(|e| e.path.clone()).collect();
+    if files.is_empty() {
+        return Ok(advice(ArchiveRecommendation::NotAMod, files, None, format!("{} holds no WADs, PK3s or game resources", zip_path)));
+    }
+    let message = format!("{} wraps {}; extract and load those instead", zip_path, files.join(", "));
+    Ok(advice(ArchiveRecommendation::ExtractFiles, files, None, message))
+}
+
 /// Stream a single zip entry to `dest_path`. Returns bytes written.
 pub fn extract_zip_entry(zip_path: &str, entry_path: &str, dest_path: &str) -> Result<u64, String> {
     let mut archive = open_archive(zip_path)?;
@@ -421,4 +542,42 @@ mod tests {
         let err = validate_game_file(wad_bad.to_str().unwrap(), "v4.wad").unwrap_err();
         assert!(err.contains("expected IWAD/PWAD header"), "{}", err);
     }
+
+    #[test]
+    fn recommends_loading_or_extracting_archives() {
+        let inspect = |name: &str, entries: &[(&str, &[u8])]| {
+            let advice = inspect_archive(&make_zip(name, entries)).unwrap();
+            (advice.recommendation, advice.files, advice.folder)
+        };
+        use ArchiveRecommendation::*;
+        assert_eq!(inspect("res.zip", &[("ZSCRIPT.zs", b""), ("maps/map01.wad", b"PWAD")]), (Load, vec![], None));
+        assert_eq!(inspect("res2.zip", &[("Sprites/trooa1.png", b"")]), (Load, vec![], None));
+        assert_eq!(
+            inspect("idgames.zip", &[("sc2.txt", b""), ("sc2/sc2.wad", b"PWAD"), ("extra.pk3", b"PK")]),
+            (ExtractFiles, vec!["sc2/sc2.wad".to_string(), "extra.pk3".to_string()], None)
+        );
+        assert_eq!(inspect("wrapped.zip", &[("MyMod/decorate.txt", b""), ("MyMod/sprites/a.png", b"")]), (ExtractFolder, vec![], Some("MyMod".to_string())));
+        assert_eq!(inspect("junk.zip", &[("screenshot.png", b""), ("readme.txt", b"")]), (NotAMod, vec![], None));
+    }
+
+    #[test]
+    fn extracts_archive_keeping_layout_and_refusing_zip_slip() {
+        let zip = make_zip(
+            "archive.zip",
+            &[("sc2/sc2.wad", b"PWAD"), ("sc2/sc2.deh", b"deh"), ("sc2/sc2.txt", b"readme"), ("sc2/shot.png", b"")],
+        );
+        let dest = temp_path("archive_out");
+        let dest = dest.to_str().unwrap();
+        let files = extract_archive(&zip, dest, false).unwrap();
+        let expected: Vec<String> = ["sc2/sc2.wad", "sc2/sc2.deh"]
+            .iter()
+            .map(|p| Path::new(dest).join(p).to_string_lossy().to_string())
+            .collect();
+        assert_eq!(files, expected);
+        assert_eq!(extract_archive(&zip, dest, true).unwrap().len(), 3);
+
+        let evil = make_zip("evil.zip", &[("../../evil.wad", b"PWAD")]);
+        let err = extract_archive(&evil, dest, false).unwrap_err();
+        assert!(err.contains("escapes"), "{}", err);
+    }
 }
@@ -21,7 +21,7 @@ pub fn make_temp_dir() -> Result<String, String> {
     Ok(create_temp_dir()?.to_string_lossy().to_string())
 }
 
-fn walk_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+pub(crate) fn walk_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
     let entries =
         std::fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
     for entry in entries {
@@ -0,0 +1,192 @@
+// idgames catalog lookup: the title, author and description of a WAD as
+// published on the idgames archive. The archive's API can't search by
+// checksum, so the query is the file name and only an entry with the same
+// name stem counts as a match; the MD5 keys the local cache, so a file is
+// looked up once however it is renamed later. Results, including "not on
+// idgames", live in idgames-cache.json in the app data dir. Requests go
+// through reqwest, the client tauri-plugin-http wraps, as download.rs does.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::wad_parser;
+
+const API_URL: &str = "https://www.doomworld.com/idgames/api/api.php";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IdgamesEntry {
+    pub id: u64,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    /// Archive file name, e.g. "sc2.zip".
+    pub filename: String,
+    /// Directory in the archive, e.g. "levels/doom2/s-u/".
+    pub dir: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdgamesCache {
+    pub version: u8,
+    /// MD5 -> entry, or None for a file idgames doesn't have.
+    pub entries: HashMap<String, Option<IdgamesEntry>>,
+}
+
+impl IdgamesCache {
+    pub fn empty() -> Self {
+        Self {
+            version: 1,
+            entries: HashMap::new(),
+        }
+    }
+}
+
+/// Serializes cache read-modify-write cycles across concurrent lookups.
+static CACHE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Build the full path to idgames-cache.json in the app data directory.
+pub fn cache_path(data_dir: impl AsRef<Path>) -> PathBuf {
+    data_dir.as_ref().join("idgames-cache.json")
+}
+
+/// Read idgames-cache.json, returning an empty cache if the file is missing.
+pub fn read_cache_or_empty(path: impl AsRef<Path>) -> Result<IdgamesCache, String> {
+    let path = path.as_ref();
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str::<IdgamesCache>(&content)
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(IdgamesCache::empty()),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Write idgames-cache.json to disk, creating parent directories if needed.
+pub fn write_cache(path: impl AsRef<Path>, cache: &IdgamesCache) -> Result<(), String> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(cache)
+        .map_err(|e| format!("Failed to serialize idgames cache: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn stem(filename: &str) -> String {
+    let base = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+    base.split('.').next().unwrap_or(base).to_lowercase()
+}
+
+fn text_field(file: &Value, key: &str) -> Option<String> {
+    file.get(key)?.as_str().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// The entry for `wad_filename` in a search response. The API returns
+/// `content.file` as an object for one hit and an array for several.
+fn match_search_response(response: &Value, wad_filename: &str) -> Option<IdgamesEntry> {
+    let files = response.get("content")?.get("file")?;
+    let files: Vec<&Value> = match files {
+        Value::Array(files) => files.iter().collect(),
+        file => vec![file],
+    };
+    let wanted = stem(wad_filename);
+    files.into_iter().find_map(|file| {
+        let filename = text_field(file, "filename")?;
+        if stem(&filename) != wanted {
+            return None;
+        }
+        Some(IdgamesEntry {
+            id: file.get("id")?.as_u64()?,
+            title: text_field(file, "title"),
+            author: text_field(file, "author"),
+            description: text_field(file, "description"),
+            filename,
+            dir: text_field(file, "dir"),
+            url: text_field(file, "url"),
+        })
+    })
+}
+
+async fn search(wad_filename: &str) -> Result<Option<IdgamesEntry>, String> {
+    let response = reqwest::Client::new()
+        .get(API_URL)
+        .query(&[("action", "search"), ("type", "filename"), ("query", &stem(wad_filename)), ("out", "json")])
+        .send()
+        .await
+        .map_err(|e| format!("idgames request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("idgames request failed: HTTP {}", response.status()));
+    }
+    let body: Value = response.json().await.map_err(|e| format!("Bad idgames response: {}", e))?;
+    Ok(match_search_response(&body, wad_filename))
+}
+
+/// The cached lookup result for `md5`; None when it was never looked up.
+fn cached(cache_file: &Path, md5: &str) -> Result<Option<Option<IdgamesEntry>>, String> {
+    let _guard = CACHE_LOCK.lock().unwrap();
+    Ok(read_cache_or_empty(cache_file)?.entries.get(md5).cloned())
+}
+
+/// The idgames entry for a WAD, from the cache when it has been looked up
+/// before. Offline (or when idgames is down) this returns the cached entry
+/// or None instead of failing; only local errors are errors.
+pub async fn identify(cache_file: &Path, wad_path: &str) -> Result<Option<IdgamesEntry>, String> {
+    let md5 = wad_parser::wad_checksum(wad_path)?.md5;
+    if let Some(entry) = cached(cache_file, &md5)? {
+        return Ok(entry);
+    }
+    let entry = match search(wad_path).await {
+        Ok(entry) => entry,
+        Err(e) => {
+            // Not cached, so the next call tries again.
+            eprintln!("idgames lookup for {} failed: {}", wad_path, e);
+            return Ok(None);
+        }
+    };
+    let _guard = CACHE_LOCK.lock().unwrap();
+    let mut cache = read_cache_or_empty(cache_file)?;
+    cache.entries.insert(md5, entry.clone());
+    write_cache(cache_file, &cache)?;
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matches_single_and_multiple_results_by_name() {
+        let one = json!({"content": {"file": {"id": 17951, "title": "Sunder", "filename": "sunder.zip", "author": "Insane_Gazebo"}}});
+        let entry = match_search_response(&one, "/wads/SUNDER.WAD").unwrap();
+        assert_eq!((entry.id, entry.title.as_deref()), (17951, Some("Sunder")));
+        assert_eq!(entry.description, None);
+
+        let many = json!({"content": {"file": [
+            {"id": 1, "title": "Sunder Fix", "filename": "sunderfix.zip"},
+            {"id": 2, "title": "Sunder", "filename": "sunder.zip", "description": " Maps 1-15 "},
+        ]}});
+        let entry = match_search_response(&many, "sunder.wad").unwrap();
+        assert_eq!((entry.id, entry.description.as_deref()), (2, Some("Maps 1-15")));
+        assert_eq!(match_search_response(&json!({"warning": {"type": "No Results"}}), "sunder.wad"), None);
+    }
+
+    #[test]
+    fn caches_misses_by_md5() {
+        let dir = std::env::temp_dir().join(format!("idgames_test_{}", std::process::id()));
+        let path = cache_path(&dir);
+        assert_eq!(cached(&path, "abc").unwrap(), None);
+
+        let mut cache = IdgamesCache::empty();
+        cache.entries.insert("abc".to_string(), None);
+        write_cache(&path, &cache).unwrap();
+        assert_eq!(cached(&path, "abc").unwrap(), Some(None));
+        assert_eq!(cached(&path, "def").unwrap(), None);
+    }
+}
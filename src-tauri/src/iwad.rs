@@ -0,0 +1,540 @@
+// IWAD identification. Settings and the IWAD picker used to disagree about
+// which base games were installed (one matched filenames, the other
+// didn't), so detection lives here: a known-checksum table first, then the
+// characteristic-lump rules source ports use (GZDoom's IWADINFO).
+//
+// Game ids match the frontend's IWADS list (src/lib/schema.ts), plus
+// "freedm" (FreeDM) and "strife", which the frontend doesn't list yet.
+//
+// The open-content IWADs (Freedoom Phase 1/2, FreeDM) are recognized by
+// their marker lumps rather than by checksum: each release has a new hash,
+// while FREEDOOM/FREEDM have been there since the first ones. The marker
+// lump carries the release version when it isn't empty.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::engine_version;
+use crate::wad_parser::{self, WadType};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchedBy {
+    Checksum,
+    Lumps,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IwadInfo {
+    pub path: String,
+    /// Frontend IWAD id ("doom2", "freedoom1", …) when recognized.
+    pub game: Option<String>,
+    /// Release name, e.g. "The Ultimate DOOM".
+    pub title: Option<String>,
+    pub recognized: bool,
+    pub matched_by: Option<MatchedBy>,
+    pub md5: Option<String>,
+    /// Release version, when the file says (Freedoom, FreeDM).
+    pub version: Option<String>,
+    /// Why the file couldn't be inspected (corrupt, unreadable). Scans
+    /// report it per file instead of failing the whole directory.
+    pub error: Option<String>,
+}
+
+/// Retail releases by MD5: (md5, game id, title).
+const KNOWN_IWADS: &[(&str, &str, &str)] = &[
+    ("c4fe9fd920207691a9f493668e0a2083", "doom", "The Ultimate DOOM"),
+    ("1cd63c5ddff1bf8ce844237f580e9cf3", "doom", "DOOM Registered"),
+    ("f0cefca49926d00903cf57551d901abe", "doom", "DOOM Shareware"),
+    ("25e1459ca71d321525f84628f45ca8cd", "doom2", "DOOM II: Hell on Earth"),
+    ("4e158d9953c79ccf97bd0663244cc6b6", "tnt", "Final DOOM: TNT - Evilution"),
+    ("1d39e405bf6ee3df69a8d2646c8d5c49", "tnt", "Final DOOM: TNT - Evilution"),
+    ("75c8cf89566741fa9d22447604053bd7", "plutonia", "Final DOOM: The Plutonia Experiment"),
+    ("3493be7e1e2588bc9c8b31eab2587a04", "plutonia", "Final DOOM: The Plutonia Experiment"),
+    ("66d686b1ed6d35ff103f15dbd30e0341", "heretic", "Heretic: Shadow of the Serpent Riders"),
+    ("abb033caf81e26f12a2103e1fa25453f", "hexen", "Hexen: Beyond Heretic"),
+];
+
+/// Identify by characteristic lumps, most specific rule first — every
+/// Doom II-based IWAD has MAP01, so the Final DOOM and Freedoom checks must
+/// run before the generic one.
+pub fn identify_by_lumps(names: &[String]) -> Option<(&'static str, &'static str)> {
+    let has = |lump: &str| names.iter().any(|n| n == lump);
+    if has("FREEDM") && has("MAP01") {
+        return Some(("freedm", "FreeDM"));
+    }
+    if has("FREEDOOM") {
+        if has("MAP01") {
+            return Some(("freedoom2", "Freedoom: Phase 2"));
+        }
+        if has("E1M1") {
+            return Some(("freedoom1", "Freedoom: Phase 1"));
+        }
+    }
+    if has("ENDSTRF") && has("MAP01") {
+        return Some(("strife", "Strife: Quest for the Sigil"));
+    }
+    if has("TITLE") && has("MAP01") {
+        return Some(("hexen", "Hexen: Beyond Heretic"));
+    }
+    if has("TITLE") && has("E1M1") {
+        return Some(if has("E2M1") {
+            ("heretic", "Heretic")
+        } else {
+            ("heretic", "Heretic Shareware")
+        });
+    }
+    if has("MAP01") && has("CAVERN5") {
+        return Some(("tnt", "Final DOOM: TNT - Evilution"));
+    }
+    if has("MAP01") && has("MC11") && has("MC16") {
+        return Some(("plutonia", "Final DOOM: The Plutonia Experiment"));
+    }
+    if has("MAP01") {
+        return Some(("doom2", "DOOM II: Hell on Earth"));
+    }
+    if has("E1M1") {
+        return Some(if has("E4M1") {
+            ("doom", "The Ultimate DOOM")
+        } else if has("E2M1") {
+            ("doom", "DOOM Registered")
+        } else {
+            ("doom", "DOOM Shareware")
+        });
+    }
+    None
+}
+
+/// Lump names of a WAD, or the lump names GZDoom derives from an IPK3's
+/// entries.
+pub fn lump_names(path: &str, wad_type: WadType) -> Result<Vec<String>, String> {
+    match wad_type {
+        WadType::Iwad | WadType::Pwad => {
+            Ok(wad_parser::read_lumps(path)?.into_iter().map(|l| l.name).collect())
+        }
+        _ => Ok(crate::game_archives::list_zip_entries(path)?
+            .iter()
+            .map(|e| wad_parser::zip_entry_lump_name(&e.path))
+            .collect()),
+    }
+}
+
+/// Marker lumps of the open-content IWADs.
+const OPEN_CONTENT_MARKERS: &[&str] = &["FREEDM", "FREEDOOM"];
+
+/// Version from an open-content IWAD's marker lump ("0.13.0"), if it
+/// holds one.
+fn open_content_version(path: &str) -> Result<Option<String>, String> {
+    let markers = wad_parser::collect_lumps(path, &|name| OPEN_CONTENT_MARKERS.contains(&name))?;
+    Ok(markers.iter().find_map(|lump| {
+        let version = engine_version::parse_version(&String::from_utf8_lossy(&lump.data));
+        let (major, minor) = (version.major?, version.minor?);
+        Some(match version.patch {
+            Some(patch) => format!("{}.{}.{}", major, minor, patch),
+            None => format!("{}.{}", major, minor),
+        })
+    }))
+}
+
+/// Identify one file. Only IWAD-headed WADs and IPK3s can be recognized —
+/// a PWAD with a MAP01 is a map set, not Doom II.
+pub fn identify_iwad(path: &str) -> Result<IwadInfo, String> {
+    let mut info = IwadInfo {
+        path: path.to_string(),
+        game: None,
+        title: None,
+        recognized: false,
+        matched_by: None,
+        md5: None,
+        version: None,
+        error: None,
+    };
+    let wad_type = wad_parser::detect_wad_type(path)?;
+    if !matches!(wad_type, WadType::Iwad | WadType::Ipk3) {
+        return Ok(info);
+    }
+    let md5 = wad_parser::wad_checksum(path)?.md5;
+    let matched = if let Some((_, game, title)) = KNOWN_IWADS.iter().find(|(m, _, _)| *m == md5) {
+        Some((*game, *title, MatchedBy::Checksum))
+    } else {
+        identify_by_lumps(&lump_names(path, wad_type)?)
+            .map(|(game, title)| (game, title, MatchedBy::Lumps))
+    };
+    info.md5 = Some(md5);
+    if let Some((game, title, matched_by)) = matched {
+        if matches!(game, "freedoom1" | "freedoom2" | "freedm") {
+            info.version = open_content_version(path)?;
+        }
+        info.game = Some(game.to_string());
+        info.title = Some(title.to_string());
+        info.recognized = true;
+        info.matched_by = Some(matched_by);
+    }
+    Ok(info)
+}
+
+fn is_iwad_candidate(path: &Path) -> bool {
+    let lower = path.to_string_lossy().to_lowercase();
+    lower.ends_with(".wad") || lower.ends_with(".ipk3")
+}
+
+/// identify_iwad, with a failure reported in the result.
+fn identify_or_error(path: &Path) -> IwadInfo {
+    let path = path.to_string_lossy().to_string();
+    identify_iwad(&path).unwrap_or_else(|e| IwadInfo {
+        path,
+        game: None,
+        title: None,
+        recognized: false,
+        matched_by: None,
+        md5: None,
+        version: None,
+        error: Some(e),
+    })
+}
+
+/// Identify every .wad/.ipk3 under `dir` (recursively), sorted by path.
+pub fn scan_iwads(dir: &str) -> Result<Vec<IwadInfo>, String> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    crate::gog_import::walk_files(Path::new(dir), &mut files)?;
+    files.retain(|p| is_iwad_candidate(p));
+    files.sort();
+    Ok(files.iter().map(|p| identify_or_error(p)).collect())
+}
+
+/// The [IWADSearch.Directories] GZDoom writes into a new gzdoom.ini on
+/// this platform, with $HOME expanded. "." and $PROGDIR (the working and
+/// the engine's directory) and $DOOMWADDIR are added by gzdoom_search_dirs.
+#[cfg(target_os = "linux")]
+fn platform_search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = std::env::var_os("HOME") {
+        let home = Path::new(&home);
+        dirs.push(home.join(".config/gzdoom"));
+        dirs.push(home.join(".local/share/games/doom"));
+    }
+    for dir in ["/usr/local/share/doom", "/usr/local/share/games/doom", "/usr/share/doom", "/usr/share/games/doom"] {
+        dirs.push(PathBuf::from(dir));
+    }
+    dirs
+}
+
+#[cfg(target_os = "macos")]
+fn platform_search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(home) = std::env::var_os("HOME") {
+        let home = Path::new(&home);
+        dirs.push(home.join("Documents/GZDoom"));
+        dirs.push(home.join("Library/Application Support/GZDoom"));
+    }
+    dirs.push(PathBuf::from("/Library/Application Support/GZDoom"));
+    dirs
+}
+
+#[cfg(target_os = "windows")]
+fn platform_search_dirs() -> Vec<PathBuf> {
+    // GZDoom lists $HOME, which Windows only has when something sets it.
+    std::env::var_os("HOME").map(PathBuf::from).into_iter().collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn platform_search_dirs() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// Where GZDoom looks for IWADs without -iwad, in its order: the engine's
+/// directory (standing in for ".", as the launcher starts it there unless
+/// a profile says otherwise), $DOOMWADDIR, the platform defaults and every
+/// $DOOMWADPATH entry.
+pub fn gzdoom_search_dirs(engine_path: Option<&str>) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = engine_path.and_then(|p| Path::new(p).parent()).map(Path::to_path_buf).into_iter().collect();
+    dirs.extend(std::env::var_os("DOOMWADDIR").map(PathBuf::from));
+    dirs.extend(platform_search_dirs());
+    if let Some(path) = std::env::var_os("DOOMWADPATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    dirs
+}
+
+/// The folders to look for IWADs in: the user's own (`extra_dirs`) first,
+/// then gzdoom_search_dirs. Only existing folders, each once however it is
+/// spelled.
+pub fn iwad_search_dirs(engine_path: Option<&str>, extra_dirs: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    extra_dirs
+        .iter()
+        .map(PathBuf::from)
+        .chain(gzdoom_search_dirs(engine_path))
+        .filter(|dir| dir.is_dir() && seen.insert(dir.canonicalize().unwrap_or_else(|_| dir.clone())))
+        .map(|dir| dir.to_string_lossy().to_string())
+        .collect()
+}
+
+/// The .wad/.ipk3 files directly in each of `dirs` (GZDoom doesn't look in
+/// subfolders), identified. A file found twice, by content or by path, is
+/// kept only from the first folder, so the folder GZDoom would pick it from
+/// wins. Folders that can't be read are logged and skipped.
+pub fn scan_iwad_dirs(dirs: &[String]) -> Vec<IwadInfo> {
+    let mut seen_paths = HashSet::new();
+    let mut seen_md5 = HashSet::new();
+    let mut out = Vec::new();
+    for dir in dirs {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Failed to read IWAD folder {}: {}", dir, e);
+                continue;
+            }
+        };
+        let mut files: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| p.is_file() && is_iwad_candidate(p)).collect();
+        files.sort();
+        for file in files {
+            if !seen_paths.insert(file.canonicalize().unwrap_or_else(|_| file.clone())) {
+                continue;
+            }
+            let info = identify_or_error(&file);
+            if let Some(md5) = &info.md5
+                && !seen_md5.insert(md5.clone())
+            {
+                continue;
+            }
+            out.push(info);
+        }
+    }
+    out
+}
+
+/// How import_iwad puts an IWAD into the launcher's iwads folder.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    Copy,
+    /// A link to the file where it is; saves the space of a second copy.
+    Symlink,
+    Move,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedIwad {
+    /// Where the IWAD now is in the iwads folder.
+    pub path: String,
+    pub info: IwadInfo,
+    /// How it got there, which differs from the mode asked for when a
+    /// fallback was needed.
+    pub mode: ImportMode,
+    /// Why a fallback was needed.
+    pub warning: Option<String>,
+}
+
+/// Put the IWAD at `src` into `iwads_dir` as `<game>.wad` (its own name
+/// when unrecognized), the name the IWAD picker looks for. An identical
+/// file already there is left as is; a different one is an error. Where
+/// symlinks need privileges (Windows without Developer Mode) Symlink falls
+/// back to Copy, and a Move across drives to copying and deleting, with a
+/// warning either way.
+pub fn import_iwad(src: &str, iwads_dir: &str, mode: ImportMode) -> Result<ImportedIwad, String> {
+    let src_path = Path::new(src)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", src, e))?;
+    let mut info = identify_iwad(&src_path.to_string_lossy())?;
+    if info.md5.is_none() {
+        return Err(format!("Not an IWAD: {}", src));
+    }
+    let name = match &info.game {
+        Some(game) => {
+            let ext = src_path.extension().map_or("wad".to_string(), |e| e.to_string_lossy().to_lowercase());
+            format!("{}.{}", game, ext)
+        }
+        None => src_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+    };
+    std::fs::create_dir_all(iwads_dir).map_err(|e| format!("Failed to create {}: {}", iwads_dir, e))?;
+    let dest = Path::new(iwads_dir).join(&name);
+    let dest_str = dest.to_string_lossy().to_string();
+    if dest.exists() {
+        if dest.canonicalize().is_ok_and(|d| d == src_path)
+            || wad_parser::wad_checksum(&dest_str).is_ok_and(|c| Some(c.md5) == info.md5)
+        {
+            info.path = dest_str.clone();
+            return Ok(ImportedIwad { path: dest_str, info, mode, warning: None });
+        }
+        return Err(format!("{} already exists in {} and is a different file", name, iwads_dir));
+    }
+
+    let copy = || -> Result<(), String> {
+        // Copied under a temporary name first, so an interrupted copy never
+        // looks like an installed IWAD.
+        let part = dest.with_extension("part");
+        std::fs::copy(&src_path, &part)
+            .and_then(|_| std::fs::rename(&part, &dest))
+            .map_err(|e| format!("Failed to copy {} to {}: {}", src, dest.display(), e))
+    };
+    let (used, warning) = match mode {
+        ImportMode::Copy => {
+            copy()?;
+            (ImportMode::Copy, None)
+        }
+        ImportMode::Symlink => match symlink_file(&src_path, &dest) {
+            Ok(()) => (ImportMode::Symlink, None),
+            Err(e) if cfg!(windows) => {
+                copy()?;
+                let warning = format!("Couldn't create a symlink ({}); copied {} instead. Enabling Developer Mode allows symlinks.", e, name);
+                eprintln!("{}", warning);
+                (ImportMode::Copy, Some(warning))
+            }
+            Err(e) => return Err(format!("Failed to link {} to {}: {}", dest.display(), src, e)),
+        },
+        ImportMode::Move => match std::fs::rename(&src_path, &dest) {
+            Ok(()) => (ImportMode::Move, None),
+            Err(rename_err) => {
+                copy()?;
+                std::fs::remove_file(&src_path).map_err(|e| {
+                    format!("Copied {} to {}, but failed to remove the original: {}", src, dest.display(), e)
+                })?;
+                let warning = format!("Couldn't move {} directly ({}); copied it and removed the original", src, rename_err);
+                eprintln!("{}", warning);
+                (ImportMode::Move, Some(warning))
+            }
+        },
+    };
+    info.path = dest_str.clone();
+    Ok(ImportedIwad { path: dest_str, info, mode: used, warning })
+}
+
+#[cfg(unix)]
+fn symlink_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(src, dest)
+}
+
+#[cfg(windows)]
+fn symlink_file(src: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(src, dest)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink_file(_src: &Path, _dest: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "symlinks are not supported here"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_archives::create_temp_dir;
+    use crate::wad_parser::test_util::build_wad;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn identifies_games_by_lumps() {
+        let id = |l: &[&str]| identify_by_lumps(&names(l)).map(|(g, _)| g);
+        assert_eq!(id(&["MAP01", "TITLEPIC"]), Some("doom2"));
+        assert_eq!(id(&["MAP01", "CAVERN5"]), Some("tnt"));
+        assert_eq!(id(&["MAP01", "MC11", "MC16"]), Some("plutonia"));
+        assert_eq!(id(&["E1M1", "E4M1"]), Some("doom"));
+        assert_eq!(id(&["E1M1", "E2M1", "TITLE"]), Some("heretic"));
+        assert_eq!(id(&["MAP01", "TITLE"]), Some("hexen"));
+        assert_eq!(id(&["MAP01", "ENDSTRF", "TITLEPIC"]), Some("strife"));
+        assert_eq!(id(&["E1M1", "FREEDOOM"]), Some("freedoom1"));
+        assert_eq!(id(&["MAP01", "FREEDOOM"]), Some("freedoom2"));
+        assert_eq!(id(&["MAP01", "FREEDM", "FREEDOOM"]), Some("freedm"));
+        assert_eq!(id(&["PLAYPAL"]), None);
+    }
+
+    #[test]
+    fn scans_directory_and_skips_pwads_and_other_files() {
+        let dir = create_temp_dir().unwrap();
+        std::fs::write(dir.join("DOOM2.WAD"), build_wad(b"IWAD", &[("MAP01", b"")])).unwrap();
+        std::fs::write(dir.join("mymaps.wad"), build_wad(b"PWAD", &[("MAP01", b"")])).unwrap();
+        std::fs::write(dir.join("broken.wad"), b"IWAD").unwrap();
+        std::fs::write(dir.join("readme.txt"), b"hi").unwrap();
+        let found = scan_iwads(dir.to_str().unwrap()).unwrap();
+        assert_eq!(found.len(), 3);
+
+        let doom2 = found.iter().find(|i| i.path.ends_with("DOOM2.WAD")).unwrap();
+        assert_eq!(doom2.game.as_deref(), Some("doom2"));
+        assert_eq!(doom2.matched_by, Some(MatchedBy::Lumps));
+        assert!(doom2.recognized);
+
+        let pwad = found.iter().find(|i| i.path.ends_with("mymaps.wad")).unwrap();
+        assert!(!pwad.recognized);
+        assert_eq!(pwad.error, None);
+
+        let broken = found.iter().find(|i| i.path.ends_with("broken.wad")).unwrap();
+        assert!(!broken.recognized);
+        assert!(broken.error.is_some());
+    }
+
+    #[test]
+    fn scans_search_dirs_once_per_iwad() {
+        let first = create_temp_dir().unwrap();
+        let second = create_temp_dir().unwrap();
+        let doom2 = build_wad(b"IWAD", &[("MAP01", b"")]);
+        std::fs::write(first.join("doom2.wad"), &doom2).unwrap();
+        std::fs::write(second.join("DOOM2.WAD"), &doom2).unwrap();
+        std::fs::write(second.join("tnt.wad"), build_wad(b"IWAD", &[("MAP01", b""), ("CAVERN5", b"")])).unwrap();
+        std::fs::create_dir_all(second.join("nested")).unwrap();
+        std::fs::write(second.join("nested/doom.wad"), build_wad(b"IWAD", &[("E1M1", b"")])).unwrap();
+
+        let dirs = [first.to_string_lossy().to_string(), second.to_string_lossy().to_string()];
+        let spelled_twice = [dirs[0].clone(), format!("{}/.", dirs[0]), dirs[1].clone(), "/no/such/dir".to_string()];
+        assert_eq!(iwad_search_dirs(None, &spelled_twice)[..2], dirs);
+
+        let found = scan_iwad_dirs(&dirs);
+        let games: Vec<(&str, bool)> =
+            found.iter().map(|i| (i.game.as_deref().unwrap(), i.path.starts_with(&dirs[0]))).collect();
+        assert_eq!(games, vec![("doom2", true), ("tnt", false)]);
+    }
+
+    #[test]
+    fn imports_iwads_under_their_game_name() {
+        let origin = create_temp_dir().unwrap();
+        let iwads = create_temp_dir().unwrap();
+        let iwads_dir = iwads.to_string_lossy().to_string();
+        let doom2 = origin.join("DOOM2.WAD");
+        std::fs::write(&doom2, build_wad(b"IWAD", &[("MAP01", b"")])).unwrap();
+        let tnt = origin.join("TNT.WAD");
+        std::fs::write(&tnt, build_wad(b"IWAD", &[("MAP01", b""), ("CAVERN5", b"")])).unwrap();
+        let pwad = origin.join("maps.wad");
+        std::fs::write(&pwad, build_wad(b"PWAD", &[("MAP01", b"")])).unwrap();
+
+        let copied = import_iwad(&doom2.to_string_lossy(), &iwads_dir, ImportMode::Copy).unwrap();
+        assert_eq!((copied.path.clone(), copied.mode, copied.info.game.as_deref()), (iwads.join("doom2.wad").to_string_lossy().to_string(), ImportMode::Copy, Some("doom2")));
+        assert!(doom2.exists());
+        let again = import_iwad(&doom2.to_string_lossy(), &iwads_dir, ImportMode::Move).unwrap();
+        assert_eq!((again.path, again.warning), (copied.path, None));
+        assert!(doom2.exists(), "an identical IWAD already in place is left alone");
+
+        std::fs::write(origin.join("other.wad"), build_wad(b"IWAD", &[("MAP01", b""), ("MAP02", b"")])).unwrap();
+        let clash = import_iwad(&origin.join("other.wad").to_string_lossy(), &iwads_dir, ImportMode::Copy).unwrap_err();
+        assert!(clash.contains("already exists"), "{}", clash);
+        assert!(import_iwad(&pwad.to_string_lossy(), &iwads_dir, ImportMode::Copy).unwrap_err().starts_with("Not an IWAD"));
+
+        let moved = import_iwad(&tnt.to_string_lossy(), &iwads_dir, ImportMode::Move).unwrap();
+        assert!(moved.path.ends_with("tnt.wad") && !tnt.exists());
+
+        #[cfg(unix)]
+        {
+            let linked = import_iwad(&origin.join("other.wad").to_string_lossy(), &origin.join("linked").to_string_lossy(), ImportMode::Symlink).unwrap();
+            assert_eq!(linked.mode, ImportMode::Symlink);
+            assert!(std::fs::symlink_metadata(&linked.path).unwrap().file_type().is_symlink());
+        }
+    }
+
+    #[test]
+    fn reads_open_content_versions() {
+        let dir = create_temp_dir().unwrap();
+        let freedm = dir.join("freedm.wad");
+        std::fs::write(&freedm, build_wad(b"IWAD", &[("FREEDM", b"FreeDM 0.13.0\n"), ("MAP01", b"")])).unwrap();
+        let info = identify_iwad(freedm.to_str().unwrap()).unwrap();
+        assert_eq!((info.game.as_deref(), info.version.as_deref()), (Some("freedm"), Some("0.13.0")));
+
+        let phase1 = dir.join("freedoom1.wad");
+        std::fs::write(&phase1, build_wad(b"IWAD", &[("FREEDOOM", b""), ("E1M1", b"")])).unwrap();
+        let info = identify_iwad(phase1.to_str().unwrap()).unwrap();
+        assert_eq!((info.title.as_deref(), info.version), (Some("Freedoom: Phase 1"), None));
+    }
+}
@@ -0,0 +1,36 @@
+// The JSON files the launcher keeps its state in: launcher-downloads.json
+// in the library, profiles.json, recent.json and play-stats.json in the app
+// config dir. Each is a versioned envelope ({"version": 1, ...}) read whole
+// and written whole, pretty-printed so it stays hand-editable.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::Path;
+
+/// Read and parse `path`.
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str::<T>(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Read `path`, returning `empty()` if the file is missing.
+pub fn read_or_empty<T: DeserializeOwned>(path: &Path, empty: fn() -> T) -> Result<T, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            serde_json::from_str::<T>(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(empty()),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Write `state` to `path`, creating parent directories if needed. `what`
+/// names the state in the serialization error ("profiles").
+pub fn write<T: Serialize>(path: &Path, state: &T, what: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(state).map_err(|e| format!("Failed to serialize {}: {}", what, e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
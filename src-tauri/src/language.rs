@@ -0,0 +1,164 @@
+// LANGUAGE: the ZDoom-family string table that MAPINFO's `lookup` and
+// "$KEY" values point into. Sections name the languages they apply to:
+//
+//     [enu default]
+//     HUSTR_1 = "level 1: entryway";
+//     C1TEXT = "Once you beat the big badasses and\n"
+//              "clean out the moon base ...";
+//     $ifgame(doom2) GOTREDSKUL = "Picked up a red skull key.";
+//
+//     [fr]
+//     HUSTR_1 = "niveau 1: entree";
+//
+// English and default strings are always read; a requested locale's
+// strings take precedence over them, whichever lump they come from.
+
+use std::collections::HashMap;
+
+use crate::mapinfo;
+
+/// Section language ids read as English; ZDoom's "default" holds the
+/// fallbacks.
+const ENGLISH: &[&str] = &["enu", "en", "eng", "default"];
+
+/// Whether a section id serves `locale`: the same id ("ptb"), or the same
+/// two-letter language of a two- or three-letter code ("fr" for "fra").
+/// "default" is the English fallback, whatever it starts with.
+fn serves_locale(id: &str, locale: &str) -> bool {
+    id != "default" && (id == locale || language(id).is_some_and(|l| language(locale) == Some(l)))
+}
+
+/// "fr" of "fr" or "fra"; None for longer ids and non-ASCII prefixes.
+fn language(code: &str) -> Option<&str> {
+    if (2..=3).contains(&code.len()) { code.get(..2) } else { None }
+}
+
+/// Entries of one lump as (section ids, uppercase key, value). Strings
+/// written back to back are concatenated; a lump with no section header is
+/// all default strings.
+fn entries(src: &str) -> Vec<(Vec<String>, String, String)> {
+    let tokens = mapinfo::tokenize(src);
+    let mut out = Vec::new();
+    let mut section = vec!["default".to_string()];
+    let mut i = 0;
+    while i < tokens.len() {
+        let t = &tokens[i];
+        if !t.quoted && t.text.starts_with('[') {
+            section.clear();
+            while let Some(t) = tokens.get(i) {
+                section.extend(t.text.trim_matches(['[', ']']).split_whitespace().map(str::to_lowercase));
+                i += 1;
+                if t.text.ends_with(']') {
+                    break;
+                }
+            }
+            continue;
+        }
+        if t.quoted || !tokens.get(i + 1).is_some_and(|n| n.is("=")) {
+            // `$ifgame(...)` prefixes and stray tokens.
+            i += 1;
+            continue;
+        }
+        let key = t.text.to_uppercase();
+        i += 2;
+        let mut value = String::new();
+        while let Some(part) = tokens.get(i).filter(|t| t.quoted) {
+            value.push_str(&part.text);
+            i += 1;
+        }
+        out.push((section.clone(), key, value));
+    }
+    out
+}
+
+/// The strings of a load order's LANGUAGE lumps: English, plus one locale
+/// when asked for. Later lumps replace earlier ones' strings.
+#[derive(Debug, Default)]
+pub struct Strings {
+    locale: Option<String>,
+    english: HashMap<String, String>,
+    localized: HashMap<String, String>,
+}
+
+impl Strings {
+    /// An empty table preferring `locale` ("fr", "de", "ptb", …) when given.
+    pub fn new(locale: Option<&str>) -> Self {
+        let locale = locale.map(|l| l.trim().to_lowercase()).filter(|l| !l.is_empty());
+        Strings { locale, ..Default::default() }
+    }
+
+    pub fn add_lump(&mut self, src: &str) {
+        for (section, key, value) in entries(src) {
+            if let Some(locale) = &self.locale
+                && section.iter().any(|id| serves_locale(id, locale))
+            {
+                self.localized.insert(key.clone(), value.clone());
+            }
+            if section.iter().any(|id| ENGLISH.contains(&id.as_str())) {
+                self.english.insert(key, value);
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let key = key.to_uppercase();
+        self.localized.get(&key).or_else(|| self.english.get(&key)).map(String::as_str)
+    }
+
+    /// `text` with a leading "$KEY" replaced; text that isn't a lookup, or
+    /// whose key no lump defines, is returned as is.
+    pub fn resolve(&self, text: &str) -> String {
+        text.strip_prefix('$').and_then(|key| self.get(key)).unwrap_or(text).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"
+        [enu default]
+        HUSTR_1 = "level 1: entryway";
+        C1TEXT = "Once you beat\n"
+                 "the big badasses";
+        $ifgame(doom2) GOTREDSKUL = "Red skull.";
+
+        [fr]
+        HUSTR_1 = "niveau 1: entree";
+        FR_ONLY = "oui";
+    "#;
+
+    #[test]
+    fn reads_english_and_default_sections() {
+        let mut strings = Strings::new(None);
+        strings.add_lump(SRC);
+        assert_eq!(strings.get("HUSTR_1"), Some("level 1: entryway"));
+        assert_eq!(strings.get("C1TEXT"), Some("Once you beat\nthe big badasses"));
+        assert_eq!(strings.get("GOTREDSKUL"), Some("Red skull."));
+        assert_eq!(strings.get("FR_ONLY"), None);
+
+        assert_eq!(strings.resolve("$hustr_1"), "level 1: entryway");
+        assert_eq!(strings.resolve("$MISSING"), "$MISSING");
+        assert_eq!(strings.resolve("Plain text"), "Plain text");
+    }
+
+    #[test]
+    fn prefers_the_requested_locale() {
+        let mut strings = Strings::new(Some("fra"));
+        strings.add_lump(SRC);
+        // A later lump's English string doesn't beat an earlier French one.
+        strings.add_lump("[enu]\nHUSTR_1 = \"Entryway (patched)\";\n");
+        assert_eq!(strings.get("HUSTR_1"), Some("niveau 1: entree"));
+        assert_eq!(strings.get("FR_ONLY"), Some("oui"));
+        assert_eq!(strings.get("C1TEXT"), Some("Once you beat\nthe big badasses"));
+    }
+
+    #[test]
+    fn matches_locales_by_language_code() {
+        assert!(serves_locale("fr", "fra") && serves_locale("ptb", "ptb"));
+        assert!(!serves_locale("default", "de"));
+        assert!(!serves_locale("deutsch", "de"));
+        // Byte 2 inside a character: no match, and no panic.
+        assert!(!serves_locale("aé", "ab") && !serves_locale("ab", "aé"));
+    }
+}
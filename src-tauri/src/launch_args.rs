@@ -0,0 +1,140 @@
+// Validation of the arguments a launch passes to the engine, so a broken
+// profile or a frontend bug can't smuggle in something else. Every path
+// after -iwad, -file, -deh or -bex must exist (relative ones against the
+// working directory, as the engine resolves them, and a bare IWAD name in
+// the folders the engine searches for IWADs) and, when allowed directories
+// are configured, lie inside one of them once symlinks and ".." are
+// resolved. No argument may hold control characters, which would end a
+// console command or a line of the log. Shell metacharacters are fine: the
+// engine is started without a shell, and command_line quotes every
+// argument for one.
+
+use std::path::{Path, PathBuf};
+
+use crate::iwad;
+use crate::launch_error::LaunchError;
+
+/// Flags followed by one or more paths, up to the next flag.
+const PATH_FLAGS: &[&str] = &["-iwad", "-file", "-deh", "-bex"];
+
+fn invalid(index: usize, arg: &str, message: String) -> LaunchError {
+    LaunchError::InvalidArgument { index, arg: arg.to_string(), message }
+}
+
+fn is_flag(arg: &str) -> bool {
+    arg.starts_with('-') || arg.starts_with('+')
+}
+
+/// Check `args` as described above; the error names the first argument
+/// that fails and its index. `engine_path` is the engine being started,
+/// whose folder it searches for IWADs. An empty `allowed_dirs` allows any
+/// location.
+pub fn check_args(
+    args: &[String],
+    engine_path: Option<&str>,
+    cwd: Option<&str>,
+    allowed_dirs: &[String],
+) -> Result<(), LaunchError> {
+    let allowed: Vec<PathBuf> = allowed_dirs
+        .iter()
+        .map(|dir| std::fs::canonicalize(dir).map_err(|e| format!("Allowed directory {} is unusable: {}", dir, e)))
+        .collect::<Result<_, _>>()?;
+    let missing_path = |at: usize| invalid(at, &args[at], format!("{} is missing its path", args[at]));
+    let mut in_paths = false;
+    let mut in_iwad = false;
+    // A path flag not yet followed by a path.
+    let mut expecting: Option<usize> = None;
+    for (index, arg) in args.iter().enumerate() {
+        if arg.chars().any(char::is_control) {
+            return Err(invalid(index, arg, format!("Argument {} contains control characters", index)));
+        }
+        if is_flag(arg) {
+            if let Some(at) = expecting {
+                return Err(missing_path(at));
+            }
+            let flag = arg.to_lowercase();
+            in_paths = PATH_FLAGS.contains(&flag.as_str());
+            in_iwad = flag == "-iwad";
+            expecting = in_paths.then_some(index);
+        } else if in_paths {
+            let search = if in_iwad { iwad::gzdoom_search_dirs(engine_path) } else { Vec::new() };
+            check_path(index, arg, cwd, &search, &allowed)?;
+            expecting = None;
+        }
+    }
+    expecting.map_or(Ok(()), |at| Err(missing_path(at)))
+}
+
+/// `search` holds the folders a bare file name is also looked for in.
+fn check_path(
+    index: usize,
+    arg: &str,
+    cwd: Option<&str>,
+    search: &[PathBuf],
+    allowed: &[PathBuf],
+) -> Result<(), LaunchError> {
+    let path = match cwd {
+        Some(cwd) => Path::new(cwd).join(arg),
+        None => PathBuf::from(arg),
+    };
+    let bare = Path::new(arg).file_name().is_some_and(|name| name == arg);
+    // Directories count: GZDoom loads one with -file like an archive.
+    let resolved = std::fs::canonicalize(&path)
+        .ok()
+        .or_else(|| bare.then(|| search.iter().find_map(|dir| std::fs::canonicalize(dir.join(arg)).ok())).flatten())
+        .ok_or_else(|| invalid(index, arg, format!("File not found: {}", arg)))?;
+    if !allowed.is_empty() && !allowed.iter().any(|dir| resolved.starts_with(dir)) {
+        return Err(invalid(index, arg, format!("{} is outside the allowed directories", arg)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::write_temp;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn failed_at(result: Result<(), LaunchError>) -> Option<(usize, String)> {
+        match result {
+            Err(LaunchError::InvalidArgument { index, arg, .. }) => Some((index, arg)),
+            other => panic!("expected an invalid argument, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn names_the_argument_that_fails() {
+        let iwad = write_temp("args_doom2.wad", b"IWAD");
+        let pwad = write_temp("args_mod.wad", b"PWAD");
+        let dir = Path::new(&iwad).parent().unwrap().to_string_lossy().to_string();
+        let name = Path::new(&pwad).file_name().unwrap().to_string_lossy().to_string();
+
+        let good = args(&["-iwad", &iwad, "-file", &pwad, &name, "-warp", "1", "+set", "sv_cheats", "1"]);
+        assert!(check_args(&good, None, Some(&dir), std::slice::from_ref(&dir)).is_ok());
+        let ampersand = write_temp("args tom & jerry.wad", b"PWAD");
+        assert!(check_args(&args(&["-file", &ampersand]), None, None, &[]).is_ok());
+        assert!(check_args(&args(&["-iwad", &iwad]), None, None, &[]).is_ok());
+
+        let missing = args(&["-iwad", &iwad, "-file", &pwad, "nowhere.wad"]);
+        assert_eq!(failed_at(check_args(&missing, None, None, &[])), Some((4, "nowhere.wad".to_string())));
+        let outside = check_args(&args(&["-file", &pwad]), None, None, &["/nonexistent-dir-for-test".to_string()]);
+        assert!(matches!(outside, Err(LaunchError::Other { .. })));
+        let confined = check_args(&args(&["-file", &pwad]), None, None, &[std::env::current_dir().unwrap().to_string_lossy().to_string()]);
+        assert_eq!(failed_at(confined), Some((1, pwad.clone())));
+        assert!(check_args(&args(&["+echo", "a;b | c"]), None, None, &[]).is_ok());
+        assert_eq!(failed_at(check_args(&args(&["+map", "MAP01\nquit"]), None, None, &[])), Some((1, "MAP01\nquit".to_string())));
+        assert_eq!(failed_at(check_args(&args(&["-file", "-warp", "1"]), None, None, &[])), Some((0, "-file".to_string())));
+        assert_eq!(failed_at(check_args(&args(&["-warp", "1", "-IWAD"]), None, None, &[])), Some((2, "-IWAD".to_string())));
+
+        // A bare IWAD name is found where the engine looks for IWADs: here
+        // the engine's own folder.
+        let engine = Path::new(&dir).join("args_engine").to_string_lossy().to_string();
+        let iwad_name = Path::new(&iwad).file_name().unwrap().to_string_lossy().to_string();
+        assert!(check_args(&args(&["-iwad", &iwad_name]), Some(&engine), Some("/"), &[]).is_ok());
+        let as_file = check_args(&args(&["-file", &iwad_name]), Some(&engine), Some("/"), &[]);
+        assert_eq!(failed_at(as_file), Some((1, iwad_name)));
+    }
+}
@@ -0,0 +1,684 @@
+// Structured launch options. The frontend used to assemble engine flags by
+// hand, which is how `-warp` with no value and GZDoom-only flags passed to
+// PrBoom+ reached the command line. LaunchConfig describes what to play;
+// build_args turns it into the argument vector for one engine.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::bundles::ModBundle;
+use crate::compat;
+use crate::demo::{self, DemoAction};
+use crate::engine::EngineKind;
+use crate::game_archives::{self, ArchiveRecommendation};
+use crate::launch_error::LaunchError;
+use crate::netplay::{self, Netplay};
+use crate::wad_parser::{self, WadType};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchConfig {
+    pub iwad: String,
+    /// Loaded in order; later files override earlier ones.
+    #[serde(default)]
+    pub pwads: Vec<String>,
+    /// WADs with their companion patches, loaded after `pwads`; each
+    /// bundle's patches follow `dehacked`.
+    #[serde(default)]
+    pub bundles: Vec<ModBundle>,
+    /// Folder of always-on mods: every WAD/PK3 in it (not subfolders) is
+    /// loaded after `pwads`, sorted by file name.
+    pub autoload_dir: Option<String>,
+    /// Standalone .deh/.bex patches, applied in order after the files'
+    /// own DEHACKED lumps.
+    #[serde(default)]
+    pub dehacked: Vec<String>,
+    /// "MAP05", "E2M3", "5" (Doom II-style IWADs) or "2 3" (episodic).
+    /// ZDoom-family engines also accept any map lump name ("TITLEMAP"),
+    /// started with `+map`. The map must exist in the IWAD or a PWAD.
+    pub warp: Option<String>,
+    /// 1 (ITYTD) to 5 (Nightmare). ZDoom-family engines allow higher values
+    /// for skills a mod defines in MAPINFO.
+    pub skill: Option<u8>,
+    #[serde(default)]
+    pub fast_monsters: bool,
+    #[serde(default)]
+    pub no_monsters: bool,
+    #[serde(default)]
+    pub respawn: bool,
+    /// PrBoom+/DSDA-Doom/Woof! `-complevel`: 2-4 vanilla, 9 Boom, 11 MBF,
+    /// 21 MBF21. Left unset, PrBoom+ and DSDA-Doom get the one the loaded
+    /// files call for (see auto_complevel); setting it overrides that.
+    /// Other ports have no complevels, so setting it for them is an error.
+    pub complevel: Option<u32>,
+    pub config_path: Option<String>,
+    pub savedir: Option<String>,
+    /// Record, play back or time a demo.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub demo: Option<DemoAction>,
+    /// Host or join a multiplayer game.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub netplay: Option<Netplay>,
+    /// Extra environment variables for the engine process, e.g. DOOMWADDIR.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Working directory for the engine process; must exist.
+    pub cwd: Option<String>,
+}
+
+fn is_zdoom_family(kind: EngineKind) -> bool {
+    matches!(kind, EngineKind::GZDoom | EngineKind::ZDoom | EngineKind::Zandronum)
+}
+
+/// A warp target after parsing, before it is checked against the IWAD.
+#[derive(Debug, Clone, PartialEq)]
+enum WarpTarget {
+    Map(u32),
+    Episode(u32, u32),
+    /// A bare number: MAPxx or episode-less, depending on the IWAD.
+    Number(u32),
+    /// Anything else: a map lump name only ZDoom-family engines can start.
+    Named(String),
+}
+
+fn parse_warp(warp: &str) -> WarpTarget {
+    let upper = warp.to_uppercase();
+    let b = upper.as_bytes();
+    let digit = |c: u8| c.is_ascii_digit().then(|| u32::from(c - b'0'));
+    if let Some(n) = upper.strip_prefix("MAP").and_then(|r| r.parse().ok()) {
+        return WarpTarget::Map(n);
+    }
+    if b.len() == 4 && b[0] == b'E' && b[2] == b'M'
+        && let (Some(e), Some(m)) = (digit(b[1]), digit(b[3]))
+    {
+        return WarpTarget::Episode(e, m);
+    }
+    let numbers: Vec<u32> = upper.split_whitespace().map_while(|p| p.parse().ok()).collect();
+    match (numbers.as_slice(), upper.split_whitespace().count()) {
+        ([n], 1) => WarpTarget::Number(*n),
+        ([e, m], 2) => WarpTarget::Episode(*e, *m),
+        _ => WarpTarget::Named(upper),
+    }
+}
+
+/// Turn a warp spec into engine arguments plus the map lump it targets.
+/// `episodic` says whether the IWAD uses ExMy maps (Doom, Heretic) or
+/// MAPxx (Doom II and the rest): `-warp 07` for the latter, `-warp 1 7`
+/// for the former. A map of the other style, or a named map, needs
+/// ZDoom's `+map`; other engines can't start those.
+fn warp_args(warp: &str, episodic: bool, kind: EngineKind) -> Result<(Vec<String>, String), String> {
+    let zdoom = is_zdoom_family(kind);
+    let by_name = |id: String| -> Result<(Vec<String>, String), String> {
+        if zdoom {
+            Ok((vec!["+map".to_string(), id.clone()], id))
+        } else {
+            let style = if episodic { "ExMy" } else { "MAPxx" };
+            Err(format!(
+                "{} can't warp to {} with this IWAD, which uses {} maps",
+                kind.display_name(),
+                id,
+                style
+            ))
+        }
+    };
+    match (parse_warp(warp), episodic) {
+        (WarpTarget::Number(n), true) => Err(format!(
+            "Warp '{}' needs an episode and map for this IWAD, e.g. E1M{} or \"1 {}\"",
+            warp, n, n
+        )),
+        (WarpTarget::Number(n) | WarpTarget::Map(n), false) => {
+            Ok((vec!["-warp".to_string(), format!("{:02}", n)], format!("MAP{:02}", n)))
+        }
+        (WarpTarget::Episode(e, m), true) => Ok((
+            vec!["-warp".to_string(), e.to_string(), m.to_string()],
+            format!("E{}M{}", e, m),
+        )),
+        (WarpTarget::Map(n), true) => by_name(format!("MAP{:02}", n)),
+        (WarpTarget::Episode(e, m), false) => by_name(format!("E{}M{}", e, m)),
+        (WarpTarget::Named(name), _) => by_name(name),
+    }
+}
+
+/// Check the warp target against the maps in the IWAD and PWADs and build
+/// its arguments. GZDoom silently ignores a warp to a missing map, so this
+/// catches it before launch. Patches and folders in the load order hold no
+/// maps and are skipped.
+pub(crate) fn checked_warp_args(
+    config: &LaunchConfig,
+    files: &[String],
+    warp: &str,
+    kind: EngineKind,
+) -> Result<Vec<String>, String> {
+    let iwad_maps = wad_parser::collect_lumps(&config.iwad, &|n| {
+        wad_parser::is_mapxx_marker(n) || wad_parser::is_exmy_marker(n)
+    })?;
+    let episodic = !iwad_maps.iter().any(|l| wad_parser::is_mapxx_marker(&l.name))
+        && iwad_maps.iter().any(|l| wad_parser::is_exmy_marker(&l.name));
+    let (args, map_id) = warp_args(warp, episodic, kind)?;
+    if iwad_maps.iter().any(|l| l.name == map_id) {
+        return Ok(args);
+    }
+    for pwad in files {
+        if wad_parser::has_map(pwad, &map_id)? {
+            return Ok(args);
+        }
+    }
+    Err(format!("Map {} is not in the IWAD or any loaded file", map_id))
+}
+
+/// The IWAD must still be where the config says and be an IWAD: a WAD with
+/// the IWAD header, an IPK3, or a PK3 with an IWADINFO lump at its root,
+/// which GZDoom loads as a game. Otherwise the engine starts only to show
+/// its own "no IWAD" dialog. Both are LaunchError::InvalidIwad, for which
+/// the frontend offers to locate the file.
+pub(crate) fn check_iwad(path: &str) -> Result<(), LaunchError> {
+    let invalid = |message| Err(LaunchError::InvalidIwad { path: path.to_string(), message });
+    if path.trim().is_empty() {
+        return invalid("No IWAD selected".to_string());
+    }
+    if !std::path::Path::new(path).is_file() {
+        return invalid(format!("IWAD not found: {}", path));
+    }
+    match wad_parser::detect_wad_type(path)? {
+        WadType::Iwad | WadType::Ipk3 => Ok(()),
+        WadType::Pk3 if has_iwadinfo(path)? => Ok(()),
+        WadType::Pwad => invalid(format!("Not an IWAD: {} is a PWAD, load it as a file instead", path)),
+        _ => invalid(format!("Not an IWAD: {}", path)),
+    }
+}
+
+/// Whether a PK3 carries IWADINFO at its root.
+fn has_iwadinfo(path: &str) -> Result<bool, String> {
+    Ok(game_archives::list_zip_entries(path)?
+        .iter()
+        .any(|entry| !entry.path.contains(['/', '\\']) && wad_parser::zip_entry_lump_name(&entry.path) == "IWADINFO"))
+}
+
+/// The WADs, PK3s and ZIPs directly in `dir`, sorted by file name
+/// (case-insensitively, so the order is the same on every OS). Other files
+/// are skipped, as IWADs are: the launch already has one.
+fn autoload_files(dir: &str) -> Result<Vec<String>, String> {
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read autoload folder {}: {}", dir, e))?;
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let path = path.to_string_lossy().to_string();
+        match wad_parser::detect_wad_type(&path) {
+            Ok(WadType::Pwad | WadType::Pk3 | WadType::Zip) => files.push(path),
+            Ok(_) => {}
+            Err(e) => eprintln!("Skipping autoload file {}: {}", path, e),
+        }
+    }
+    files.sort_by_key(|path| path.to_lowercase());
+    Ok(files)
+}
+
+/// Flag that sets the save directory for each engine.
+fn savedir_flag(kind: EngineKind) -> &'static str {
+    match kind {
+        EngineKind::PrBoom | EngineKind::Dsda | EngineKind::Woof => "-save",
+        _ => "-savedir",
+    }
+}
+
+/// Whether a load-order file is a DEHACKED patch, which the engines also
+/// take with -file.
+pub(crate) fn is_patch_file(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    lower.ends_with(".deh") || lower.ends_with(".bex")
+}
+
+/// The complevel PrBoom+/DSDA-Doom should run `files` and `patches` at:
+/// the highest any WAD/PK3 needs by compat::detect_complevel, vanilla
+/// levels going by the IWAD, or a patch needs for its BEX or MBF21
+/// features. A wrong one desyncs recorded demos and changes how Boom maps
+/// play. Folders and other files are skipped. None when nothing asks for
+/// a level, or when a file needs a ZDoom-family port or can't be
+/// inspected (logged); the engine's own default applies then.
+fn auto_complevel(iwad: &str, files: &[String], patches: &[&String]) -> Option<u32> {
+    let vanilla = match compat::game_complevel(iwad) {
+        Ok(vanilla) => vanilla,
+        Err(e) => {
+            eprintln!("Not choosing a complevel: failed to identify {}: {}", iwad, e);
+            return None;
+        }
+    };
+    let mut highest: Option<u32> = None;
+    let load_order_patches = files.iter().filter(|file| is_patch_file(file));
+    for patch in patches.iter().copied().chain(load_order_patches) {
+        match compat::patch_complevel(patch) {
+            Ok(level) => highest = highest.max(level),
+            Err(e) => {
+                eprintln!("Not choosing a complevel: failed to inspect {}: {}", patch, e);
+                return None;
+            }
+        }
+    }
+    for file in files {
+        if std::path::Path::new(file).is_dir() || is_patch_file(file) {
+            continue;
+        }
+        match wad_parser::detect_wad_type(file) {
+            Ok(WadType::Iwad | WadType::Pwad | WadType::Pk3 | WadType::Zip) => {}
+            Ok(_) => continue,
+            Err(e) => {
+                eprintln!("Not choosing a complevel: failed to inspect {}: {}", file, e);
+                return None;
+            }
+        }
+        match compat::detect_complevel_at(file, vanilla) {
+            Ok(detected) => highest = highest.max(Some(detected.complevel?)),
+            Err(e) => {
+                eprintln!("Not choosing a complevel: failed to inspect {}: {}", file, e);
+                return None;
+            }
+        }
+    }
+    highest
+}
+
+/// The files loaded with -file, in order: `pwads`, the bundles' primaries,
+/// then the autoload folder.
+pub(crate) fn load_order_files(config: &LaunchConfig) -> Result<Vec<String>, String> {
+    let mut files = config.pwads.clone();
+    files.extend(config.bundles.iter().map(|bundle| bundle.primary.clone()));
+    if let Some(dir) = &config.autoload_dir {
+        files.extend(autoload_files(dir)?);
+    }
+    Ok(files)
+}
+
+/// A .zip the engine would load without finding anything in it (WADs
+/// wrapped inside, or resources a folder down) fails with the advice
+/// instead. PK3s and files that can't be inspected are left to the engine.
+pub(crate) fn check_plain_zip(path: &str) -> Result<(), LaunchError> {
+    if !path.to_lowercase().ends_with(".zip") || !std::path::Path::new(path).is_file() {
+        return Ok(());
+    }
+    match game_archives::inspect_archive(path) {
+        Ok(advice) if advice.recommendation != ArchiveRecommendation::Load => {
+            Err(LaunchError::InvalidWad { path: path.to_string(), message: advice.message })
+        }
+        Ok(_) => Ok(()),
+        Err(e) => {
+            eprintln!("Failed to inspect {} before launch: {}", path, e);
+            Ok(())
+        }
+    }
+}
+
+/// Serialize `config` into the argument vector for `kind`. Options that
+/// can't be expressed — an empty warp, a skill out of range, a map that
+/// doesn't exist — are errors rather than being dropped, so the user sees
+/// why the launch didn't start. Files are checked before the engine sees
+/// them: the IWAD's header, the autoload folder's files by type, DEHACKED
+/// patches for readability, and for a warp the WAD directories holding
+/// the map. Nothing is created, so a dry run can call it too; a launch
+/// calls prepare_savedir first. PrBoom+ and DSDA-Doom get a
+/// `-complevel` even when the config sets none. A .zip that needs
+/// extracting before it can be loaded is refused with the reason.
+pub fn build_args(config: &LaunchConfig, kind: EngineKind) -> Result<Vec<String>, LaunchError> {
+    check_iwad(&config.iwad)?;
+    let mut args = vec!["-iwad".to_string(), config.iwad.clone()];
+    let files = load_order_files(config)?;
+    for pwad in &files {
+        if pwad.trim().is_empty() {
+            return Err("Empty file path in the load order".to_string().into());
+        }
+        check_plain_zip(pwad)?;
+        args.push("-file".to_string());
+        args.push(pwad.clone());
+    }
+    let patches: Vec<&String> =
+        config.dehacked.iter().chain(config.bundles.iter().flat_map(|bundle| &bundle.dehacked)).collect();
+    if !patches.is_empty() {
+        args.push("-deh".to_string());
+        for &patch in &patches {
+            // The engine skips a patch it can't open, leaving only a console line.
+            std::fs::File::open(patch).map_err(|e| format!("Cannot read DEHACKED patch {}: {}", patch, e))?;
+            args.push(patch.clone());
+        }
+    }
+
+    if let Some(warp) = &config.warp {
+        let warp = warp.trim();
+        if warp.is_empty() {
+            return Err("Warp target is empty".to_string().into());
+        }
+        args.extend(checked_warp_args(config, &files, warp, kind)?);
+    }
+
+    if let Some(skill) = config.skill {
+        let max = if is_zdoom_family(kind) { u8::MAX } else { 5 };
+        if skill == 0 || skill > max {
+            return Err(format!("Skill {} is out of range for {} (1-{})", skill, kind.display_name(), max).into());
+        }
+        args.push("-skill".to_string());
+        args.push(skill.to_string());
+    }
+
+    if config.fast_monsters {
+        args.push("-fast".to_string());
+    }
+    if config.no_monsters {
+        args.push("-nomonsters".to_string());
+    }
+    if config.respawn {
+        args.push("-respawn".to_string());
+    }
+    let complevel = match (config.complevel, kind) {
+        (Some(level), _) => Some(level),
+        (None, EngineKind::PrBoom | EngineKind::Dsda) => auto_complevel(&config.iwad, &files, &patches),
+        (None, _) => None,
+    };
+    if let Some(level) = complevel {
+        if !matches!(kind, EngineKind::PrBoom | EngineKind::Dsda | EngineKind::Woof) {
+            return Err(format!("{} has no complevels; -complevel is for PrBoom+, DSDA-Doom and Woof!", kind.display_name()).into());
+        }
+        if !matches!(level, 0..=17 | 21) {
+            return Err(format!("Complevel {} is not one of 0-17 or 21", level).into());
+        }
+        args.push("-complevel".to_string());
+        args.push(level.to_string());
+    }
+    if let Some(path) = &config.config_path {
+        args.push("-config".to_string());
+        args.push(path.clone());
+    }
+    if let Some(dir) = &config.savedir {
+        args.push(savedir_flag(kind).to_string());
+        args.push(dir.clone());
+    }
+    if let Some(action) = &config.demo {
+        args.extend(demo::demo_args(action)?);
+    }
+    if let Some(netplay) = &config.netplay {
+        args.extend(netplay::netplay_args(netplay, kind)?);
+    }
+    Ok(args)
+}
+
+/// Create the config's save directory if missing and check that it can be
+/// written, so saving doesn't fail once the game is under way. Launches
+/// call this before build_args; dry runs don't.
+pub fn prepare_savedir(config: &LaunchConfig) -> Result<(), String> {
+    let Some(dir) = &config.savedir else { return Ok(()) };
+    std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create save directory {}: {}", dir, e))?;
+    demo::check_writable_dir(std::path::Path::new(dir), "Save directory")
+}
+
+/// Quote one argument for pasting into a terminal. POSIX shells get single
+/// quotes; on Windows the rules are those of CommandLineToArgvW, where
+/// backslashes are literal except in front of a quote.
+fn quote_arg(arg: &str, windows: bool) -> String {
+    if windows {
+        if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+            return arg.to_string();
+        }
+        let mut out = String::from('"');
+        let mut backslashes = 0;
+        for c in arg.chars() {
+            match c {
+                '\\' => {
+                    backslashes += 1;
+                    continue;
+                }
+                '"' => out.push_str(&"\\".repeat(backslashes * 2 + 1)),
+                _ => out.push_str(&"\\".repeat(backslashes)),
+            }
+            out.push(c);
+            backslashes = 0;
+        }
+        out.push_str(&"\\".repeat(backslashes * 2));
+        out.push('"');
+        out
+    } else if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:+,@%".contains(c)) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// The command line for `program` and `args`, quoted for this platform's
+/// shell.
+pub fn command_line(program: &str, args: &[String]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(|arg| quote_arg(arg, cfg!(windows)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, build_zip, write_temp};
+
+    fn config() -> LaunchConfig {
+        LaunchConfig {
+            iwad: write_temp("lc_iwad.wad", &build_wad(b"IWAD", &[("MAP01", b"")])),
+            pwads: vec!["/wads/sc2.wad".into(), "/mods/smooth.pk3".into()],
+            ..Default::default()
+        }
+    }
+
+    /// A save directory path under the temp dir, not yet created.
+    fn temp_savedir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("lc_saves_{}", std::process::id())).join(name);
+        dir.to_string_lossy().to_string()
+    }
+
+    /// A Doom II-style IWAD plus a PK3 adding MAP33.
+    fn doom2_files() -> LaunchConfig {
+        let iwad = write_temp("lc_doom2.wad", &build_wad(b"IWAD", &[("MAP01", b""), ("MAP07", b"")]));
+        let map33 = build_wad(b"PWAD", &[("MAP33", b"")]);
+        let pk3 = write_temp("lc_extra.pk3", &build_zip(&[("maps/map33.wad", &map33)]));
+        LaunchConfig { iwad, pwads: vec![pk3], ..Default::default() }
+    }
+
+    #[test]
+    fn builds_gzdoom_arguments() {
+        let files = doom2_files();
+        let savedir = temp_savedir("sc2");
+        let c = LaunchConfig {
+            warp: Some("MAP07".into()),
+            skill: Some(4),
+            fast_monsters: true,
+            savedir: Some(savedir.clone()),
+            ..files.clone()
+        };
+        assert_eq!(
+            build_args(&c, EngineKind::GZDoom).unwrap(),
+            vec![
+                "-iwad", &files.iwad, "-file", &files.pwads[0],
+                "-warp", "07", "-skill", "4", "-fast", "-savedir", &savedir,
+            ]
+        );
+        assert!(!std::path::Path::new(&savedir).exists(), "building arguments creates nothing");
+        prepare_savedir(&c).unwrap();
+        assert!(std::path::Path::new(&savedir).is_dir());
+        let c = LaunchConfig { warp: Some("33".into()), ..files };
+        assert!(build_args(&c, EngineKind::GZDoom).unwrap().ends_with(&["-warp".into(), "33".into()]));
+    }
+
+    #[test]
+    fn adapts_flags_per_engine() {
+        let savedir = temp_savedir("x");
+        let c = LaunchConfig {
+            no_monsters: true,
+            respawn: true,
+            config_path: Some("/cfg/dsda.cfg".into()),
+            savedir: Some(savedir.clone()),
+            ..config()
+        };
+        let args = build_args(&c, EngineKind::Dsda).unwrap();
+        assert_eq!(
+            args[6..],
+            ["-nomonsters", "-respawn", "-config", "/cfg/dsda.cfg", "-save", &savedir]
+        );
+        let args = build_args(&LaunchConfig { complevel: Some(21), ..config() }, EngineKind::Woof).unwrap();
+        assert!(args.ends_with(&["-complevel".into(), "21".into()]));
+    }
+
+    #[test]
+    fn picks_a_complevel_for_prboom_and_dsda() {
+        let boom = build_wad(b"PWAD", &[("COMPLVL", b"boom"), ("MAP01", b"")]);
+        let vanilla = build_wad(b"PWAD", &[("MAP02", b"")]);
+        let c = LaunchConfig {
+            pwads: vec![write_temp("lc_vanilla.wad", &vanilla), write_temp("lc_boom.wad", &boom)],
+            ..config()
+        };
+        let complevel = |c: &LaunchConfig, kind| {
+            let args = build_args(c, kind).unwrap();
+            args.iter().position(|a| a == "-complevel").map(|i| args[i + 1].clone())
+        };
+        assert_eq!(complevel(&c, EngineKind::Dsda).as_deref(), Some("9"));
+        assert_eq!(complevel(&LaunchConfig { pwads: c.pwads[..1].to_vec(), ..c.clone() }, EngineKind::PrBoom).as_deref(), Some("2"));
+        assert_eq!(complevel(&LaunchConfig { complevel: Some(21), ..c.clone() }, EngineKind::PrBoom).as_deref(), Some("21"));
+        assert_eq!(complevel(&c, EngineKind::Woof), None);
+        assert_eq!(complevel(&LaunchConfig { pwads: vec![], ..c.clone() }, EngineKind::Dsda), None);
+
+        // Patches raise the level of a vanilla map set; folders and other
+        // files in the load order are passed over.
+        let mbf21 = write_temp("lc_mbf21.deh", b"Patch File for DeHackEd v3.0\nDoom version = 2021\n");
+        let bex = write_temp("lc_boom.bex", b"[CODEPTR]\nFRAME 1 = Look\n");
+        let dir = std::env::temp_dir().to_string_lossy().to_string();
+        let txt = write_temp("lc_complevel.txt", b"readme");
+        let vanilla_set = LaunchConfig {
+            pwads: vec![c.pwads[0].clone(), dir, txt],
+            dehacked: vec![mbf21],
+            ..c.clone()
+        };
+        assert_eq!(complevel(&vanilla_set, EngineKind::Dsda).as_deref(), Some("21"));
+        let bex_in_load_order = LaunchConfig { pwads: vec![c.pwads[0].clone(), bex], ..c };
+        assert_eq!(complevel(&bex_in_load_order, EngineKind::PrBoom).as_deref(), Some("9"));
+    }
+
+    #[test]
+    fn warps_by_iwad_map_style() {
+        let args = |w: &str, episodic, kind| warp_args(w, episodic, kind).map(|(a, id)| (a.join(" "), id));
+        let ok = |a: &str, id: &str| Ok((a.to_string(), id.to_string()));
+        assert_eq!(args("map7", false, EngineKind::PrBoom), ok("-warp 07", "MAP07"));
+        assert_eq!(args("7", false, EngineKind::PrBoom), ok("-warp 07", "MAP07"));
+        assert_eq!(args("e2m3", true, EngineKind::PrBoom), ok("-warp 2 3", "E2M3"));
+        assert_eq!(args("2 3", true, EngineKind::Chocolate), ok("-warp 2 3", "E2M3"));
+        assert!(args("3", true, EngineKind::PrBoom).unwrap_err().contains("episode"));
+        // Other-style and named maps: +map on ZDoom-family engines only.
+        assert_eq!(args("E1M1", false, EngineKind::GZDoom), ok("+map E1M1", "E1M1"));
+        assert_eq!(args("titlemap", false, EngineKind::GZDoom), ok("+map TITLEMAP", "TITLEMAP"));
+        assert!(args("E1M1", false, EngineKind::PrBoom).unwrap_err().contains("MAPxx"));
+    }
+
+    #[test]
+    fn rejects_malformed_options() {
+        let err = |c: LaunchConfig, kind| build_args(&c, kind).unwrap_err().to_string();
+        assert!(err(LaunchConfig { warp: Some(" ".into()), ..config() }, EngineKind::GZDoom).contains("empty"));
+        assert!(err(LaunchConfig { skill: Some(0), ..config() }, EngineKind::GZDoom).contains("out of range"));
+        assert!(err(LaunchConfig { skill: Some(6), ..config() }, EngineKind::Chocolate).contains("1-5"));
+        assert!(err(LaunchConfig { complevel: Some(9), ..config() }, EngineKind::GZDoom).contains("no complevels"));
+        assert!(err(LaunchConfig { complevel: Some(19), ..config() }, EngineKind::Dsda).contains("0-17 or 21"));
+        assert!(err(LaunchConfig { iwad: String::new(), ..config() }, EngineKind::GZDoom).contains("IWAD"));
+        assert!(build_args(&LaunchConfig { skill: Some(6), ..config() }, EngineKind::GZDoom).is_ok());
+        let missing = LaunchConfig { warp: Some("MAP12".into()), ..doom2_files() };
+        assert!(err(missing, EngineKind::GZDoom).contains("MAP12 is not in"));
+    }
+
+    #[test]
+    fn warps_past_patches_and_folders_in_the_load_order() {
+        let deh = write_temp("lc_warp.deh", b"Patch File for DeHackEd v3.0\n");
+        let pk3 = write_temp("lc_warp.pk3", &build_zip(&[("maps/map31.wad", &build_wad(b"PWAD", &[("MAP01", b"")]))]));
+        let dir = std::env::temp_dir().to_string_lossy().to_string();
+        let c = LaunchConfig { pwads: vec![deh, dir, pk3], warp: Some("MAP31".into()), ..doom2_files() };
+        assert!(build_args(&c, EngineKind::GZDoom).unwrap().ends_with(&["-warp".to_string(), "31".to_string()]));
+    }
+
+    #[test]
+    fn requires_an_existing_iwad() {
+        let err = |iwad: &str| build_args(&LaunchConfig { iwad: iwad.into(), ..config() }, EngineKind::GZDoom).unwrap_err();
+        assert_eq!(
+            err("/moved/DOOM2.WAD"),
+            LaunchError::InvalidIwad { path: "/moved/DOOM2.WAD".into(), message: "IWAD not found: /moved/DOOM2.WAD".into() }
+        );
+        let pwad = write_temp("lc_not_iwad.wad", &build_wad(b"PWAD", &[("MAP01", b"")]));
+        assert!(matches!(err(&pwad), LaunchError::InvalidIwad { message, .. } if message.contains("is a PWAD")));
+
+        let game = write_temp("lc_game.pk3", &build_zip(&[("iwadinfo.txt", b"IWad { Name = \"Game\" }")]));
+        assert!(build_args(&LaunchConfig { iwad: game, ..config() }, EngineKind::GZDoom).is_ok());
+        let mod_pk3 = write_temp("lc_mod.pk3", &build_zip(&[("mod/iwadinfo.txt", b""), ("zscript.zs", b"")]));
+        assert!(matches!(err(&mod_pk3), LaunchError::InvalidIwad { .. }));
+    }
+
+    #[test]
+    fn passes_readable_dehacked_patches() {
+        let patch = write_temp("lc_patch.deh", b"Patch File for DeHackEd v3.0\n");
+        let bex = write_temp("lc_patch.bex", b"[STRINGS]\n");
+        let c = LaunchConfig { dehacked: vec![patch.clone(), bex.clone()], ..config() };
+        assert_eq!(build_args(&c, EngineKind::Woof).unwrap()[6..], ["-deh", &patch, &bex]);
+        let c = LaunchConfig { dehacked: vec!["/nonexistent/x.deh".into()], ..config() };
+        assert!(build_args(&c, EngineKind::Woof).unwrap_err().to_string().contains("Cannot read DEHACKED patch"));
+    }
+
+    #[test]
+    fn loads_bundles_after_pwads() {
+        let patch = write_temp("lc_bundle.deh", b"Patch File for DeHackEd v3.0\n");
+        let bundle = ModBundle { primary: "/wads/scythe2.wad".into(), dehacked: vec![patch.clone()], text: Some("/wads/scythe2.txt".into()), ..Default::default() };
+        let own = write_temp("lc_own.bex", b"[STRINGS]\n");
+        let c = LaunchConfig { bundles: vec![bundle], dehacked: vec![own.clone()], ..config() };
+        assert_eq!(
+            build_args(&c, EngineKind::GZDoom).unwrap()[2..],
+            ["-file", "/wads/sc2.wad", "-file", "/mods/smooth.pk3", "-file", "/wads/scythe2.wad", "-deh", &own, &patch]
+        );
+    }
+
+    #[test]
+    fn appends_netplay_arguments() {
+        let netplay = Netplay { host: Some(2), deathmatch: true, ..Default::default() };
+        let c = LaunchConfig { netplay: Some(netplay), ..config() };
+        assert_eq!(build_args(&c, EngineKind::GZDoom).unwrap()[6..], ["-host", "2", "-deathmatch"]);
+        let netplay = Netplay { host: Some(2), join: Some("10.0.0.2".into()), ..Default::default() };
+        let c = LaunchConfig { netplay: Some(netplay), ..config() };
+        assert!(build_args(&c, EngineKind::GZDoom).unwrap_err().to_string().contains("both host and join"));
+    }
+
+    #[test]
+    fn quotes_command_lines_per_platform() {
+        let args = ["-file".to_string(), "/wads/My Mod's.wad".to_string(), "".to_string()];
+        assert_eq!(quote_arg("/usr/bin/gzdoom", false), "/usr/bin/gzdoom");
+        assert_eq!(
+            args.iter().map(|a| quote_arg(a, false)).collect::<Vec<_>>(),
+            vec!["-file", "'/wads/My Mod'\\''s.wad'", "''"]
+        );
+        assert_eq!(quote_arg("C:\\Program Files\\GZDoom\\", true), "\"C:\\Program Files\\GZDoom\\\\\"");
+        assert_eq!(quote_arg("say \"hi\"", true), "\"say \\\"hi\\\"\"");
+        assert_eq!(quote_arg("C:\\wads\\doom2.wad", true), "C:\\wads\\doom2.wad");
+    }
+
+    #[test]
+    fn appends_autoload_folder_in_name_order() {
+        let dir = std::env::temp_dir().join(format!("lc_autoload_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        let map33 = build_wad(b"PWAD", &[("MAP33", b"")]);
+        std::fs::write(dir.join("b_maps.wad"), &map33).unwrap();
+        std::fs::write(dir.join("A_brightmaps.pk3"), build_zip(&[("gldefs.txt", b"")])).unwrap();
+        std::fs::write(dir.join("readme.txt"), b"not a wad").unwrap();
+        std::fs::write(dir.join("nested").join("c.wad"), &map33).unwrap();
+
+        let iwad = doom2_files().iwad;
+        let c = LaunchConfig {
+            iwad: iwad.clone(),
+            autoload_dir: Some(dir.to_string_lossy().to_string()),
+            warp: Some("MAP33".into()),
+            ..Default::default()
+        };
+        let args = build_args(&c, EngineKind::GZDoom).unwrap();
+        let files: Vec<&str> = args.iter().skip(3).step_by(2).take(2).map(|a| a.rsplit('/').next().unwrap()).collect();
+        assert_eq!(files, vec!["A_brightmaps.pk3", "b_maps.wad"]);
+        assert_eq!(args.len(), 2 + 4 + 2);
+
+        let missing = LaunchConfig { iwad, autoload_dir: Some("/nonexistent/autoload".into()), ..Default::default() };
+        assert!(build_args(&missing, EngineKind::GZDoom).unwrap_err().to_string().contains("autoload folder"));
+    }
+}
@@ -0,0 +1,92 @@
+// Structured errors for the launch and parser commands. Tauri sends a
+// command's error to the frontend as its JSON, so these serialize as a
+// tagged object the UI can branch on — a "locate IWAD" button for
+// invalidIwad, "launch anyway" for alreadyRunning — while `message` stays
+// the same human-readable text the plain String errors had:
+//
+//     {"kind": "invalidIwad", "path": "/wads/DOOM2.WAD", "message": "IWAD not found: /wads/DOOM2.WAD"}
+//
+// Errors from code that still returns String arrive as `other`, and a
+// LaunchError converts back to its message where a String is expected.
+// The commands for the JSON stores, archives, downloads, engine discovery
+// and running sessions keep plain String errors: none of their failures
+// has a recovery the UI would offer by kind.
+
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LaunchError {
+    /// The engine, a working directory or a file to parse doesn't exist.
+    NotFound { path: String, message: String },
+    /// The engine lacks the execute bit or isn't a regular file.
+    NotExecutable { path: String, message: String },
+    /// The executable isn't a known source port (and no kind was given).
+    UnknownEngine { path: String, message: String },
+    /// The IWAD is missing or isn't an IWAD.
+    InvalidIwad { path: String, message: String },
+    /// A file to parse is neither a WAD nor a ZIP/PK3.
+    InvalidWad { path: String, message: String },
+    /// A copy of the engine is already open.
+    AlreadyRunning { path: String, message: String },
+    /// The OS refused to start the engine.
+    SpawnFailed { path: String, message: String },
+    /// A launch argument failed validation; `index` is its position.
+    InvalidArgument { index: usize, arg: String, message: String },
+    Other { message: String },
+}
+
+impl LaunchError {
+    pub fn message(&self) -> &str {
+        match self {
+            LaunchError::NotFound { message, .. }
+            | LaunchError::NotExecutable { message, .. }
+            | LaunchError::UnknownEngine { message, .. }
+            | LaunchError::InvalidIwad { message, .. }
+            | LaunchError::InvalidWad { message, .. }
+            | LaunchError::AlreadyRunning { message, .. }
+            | LaunchError::SpawnFailed { message, .. }
+            | LaunchError::InvalidArgument { message, .. }
+            | LaunchError::Other { message } => message,
+        }
+    }
+}
+
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for LaunchError {}
+
+impl From<String> for LaunchError {
+    fn from(message: String) -> Self {
+        LaunchError::Other { message }
+    }
+}
+
+impl From<LaunchError> for String {
+    fn from(e: LaunchError) -> Self {
+        e.message().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_as_tagged_object_with_message() {
+        let e = LaunchError::InvalidIwad { path: "/w/DOOM2.WAD".into(), message: "IWAD not found: /w/DOOM2.WAD".into() };
+        assert_eq!(
+            serde_json::to_value(&e).unwrap(),
+            serde_json::json!({"kind": "invalidIwad", "path": "/w/DOOM2.WAD", "message": "IWAD not found: /w/DOOM2.WAD"})
+        );
+        assert_eq!(e.to_string(), "IWAD not found: /w/DOOM2.WAD");
+        let other: LaunchError = "Failed to read x".to_string().into();
+        assert_eq!(serde_json::to_value(&other).unwrap(), serde_json::json!({"kind": "other", "message": "Failed to read x"}));
+        assert_eq!(String::from(other), "Failed to read x");
+    }
+}
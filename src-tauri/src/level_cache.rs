@@ -0,0 +1,95 @@
+// Level-name cache: `{wad}.levels.json` next to the WAD, so the mod list
+// doesn't re-parse hundreds of files on every start. The sidecar records
+// the WAD's size and mtime and is re-parsed when either changes.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use crate::wad_parser;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelCache {
+    pub version: u8,
+    pub wad_size: u64,
+    /// Modification time, milliseconds since the Unix epoch.
+    pub wad_modified_ms: u64,
+    pub names: HashMap<String, String>,
+}
+
+/// Bumped when parsing changes what names come out; version 2 resolves
+/// "$KEY" names from LANGUAGE.
+const CACHE_VERSION: u8 = 2;
+
+/// Build the sidecar path: "/wads/sc2.wad" -> "/wads/sc2.wad.levels.json".
+pub fn cache_path(wad_path: impl AsRef<Path>) -> PathBuf {
+    let mut path = wad_path.as_ref().as_os_str().to_owned();
+    path.push(".levels.json");
+    PathBuf::from(path)
+}
+
+/// Size and mtime of the WAD, the cache's validity key.
+fn wad_stamp(wad_path: &str) -> Result<(u64, u64), String> {
+    let meta = fs::metadata(wad_path).map_err(|e| format!("Failed to stat {}: {}", wad_path, e))?;
+    let modified = meta
+        .modified()
+        .map_err(|e| format!("Failed to read mtime of {}: {}", wad_path, e))?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    Ok((meta.len(), modified))
+}
+
+/// The cached names if the sidecar exists, parses and matches the WAD.
+fn read_valid_cache(path: &Path, size: u64, modified_ms: u64) -> Option<HashMap<String, String>> {
+    let content = fs::read_to_string(path).ok()?;
+    let cache: LevelCache = serde_json::from_str(&content).ok()?;
+    (cache.version == CACHE_VERSION && cache.wad_size == size && cache.wad_modified_ms == modified_ms)
+        .then_some(cache.names)
+}
+
+/// Level names for `wad_path`, from the sidecar when it is up to date, else
+/// parsed and written back. A sidecar that can't be written (read-only
+/// library) only costs the speed-up, so it is logged rather than failing.
+pub fn level_names_cached(wad_path: &str) -> Result<HashMap<String, String>, String> {
+    let (wad_size, wad_modified_ms) = wad_stamp(wad_path)?;
+    let path = cache_path(wad_path);
+    if let Some(names) = read_valid_cache(&path, wad_size, wad_modified_ms) {
+        return Ok(names);
+    }
+    let names = wad_parser::extract_level_names(wad_path)?;
+    let cache = LevelCache { version: CACHE_VERSION, wad_size, wad_modified_ms, names };
+    let json = serde_json::to_string_pretty(&cache)
+        .map_err(|e| format!("Failed to serialize level cache: {}", e))?;
+    if let Err(e) = fs::write(&path, json) {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+    }
+    Ok(cache.names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, write_temp};
+
+    #[test]
+    fn caches_until_the_wad_changes() {
+        let wad = write_temp("cached.wad", &build_wad(b"PWAD", &[("MAPINFO", b"map MAP01 \"First\" { }")]));
+        let sidecar = cache_path(&wad);
+        assert!(sidecar.to_string_lossy().ends_with("cached.wad.levels.json"));
+        assert_eq!(level_names_cached(&wad).unwrap()["MAP01"], "First");
+        assert!(sidecar.is_file());
+
+        // A matching sidecar is trusted without re-parsing.
+        let mut cache: LevelCache = serde_json::from_str(&fs::read_to_string(&sidecar).unwrap()).unwrap();
+        cache.names.insert("MAP01".into(), "From cache".into());
+        fs::write(&sidecar, serde_json::to_string(&cache).unwrap()).unwrap();
+        assert_eq!(level_names_cached(&wad).unwrap()["MAP01"], "From cache");
+
+        // Rewriting the WAD (a different size) invalidates it.
+        fs::write(&wad, build_wad(b"PWAD", &[("MAPINFO", b"map MAP01 \"Second map\" { }")])).unwrap();
+        assert_eq!(level_names_cached(&wad).unwrap()["MAP01"], "Second map");
+    }
+}
@@ -1,13 +1,50 @@
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::sync::Arc;
 use tauri::{Emitter, Manager, State};
 
+pub mod actors;
+pub mod backup;
+pub mod bundles;
+pub mod compat;
+pub mod crash_report;
+pub mod dehacked;
+pub mod demo;
+pub mod difficulty;
+pub mod download;
+pub mod duplicates;
+pub mod engine;
+pub mod engine_discovery;
+pub mod engine_flags;
+pub mod engine_version;
 pub mod game_archives;
 pub mod gog_import;
+pub mod idgames;
+pub mod iwad;
+pub mod json_store;
+pub mod language;
+pub mod launch_args;
+pub mod launch_config;
+pub mod launch_error;
 pub mod launcher_downloads;
+pub mod level_cache;
+pub mod load_order;
+pub mod log_analysis;
+pub mod map_stats;
+pub mod mapinfo;
+pub mod musinfo;
+pub mod netplay;
+pub mod picture;
+pub mod play_stats;
+pub mod preflight;
+pub mod process;
+pub mod profiles;
+pub mod quick_test;
+pub mod recent;
+pub mod reveal;
+pub mod session;
+pub mod sndinfo;
+pub mod startup_banner;
+pub mod timestamp;
+pub mod wad_parser;
 
 #[tauri::command]
 async fn read_launcher_downloads(library_path: String) -> Result<launcher_downloads::LauncherDownloads, String> {
@@ -24,6 +61,101 @@ async fn write_launcher_downloads(
     launcher_downloads::write_launcher_downloads(path, &state)
 }
 
+fn app_config_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve app config dir: {}", e))
+}
+
+fn app_data_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))
+}
+
+fn profiles_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(profiles::profiles_path(app_config_dir(app)?))
+}
+
+/// Save a named launch profile (engine, IWAD, load order, flags),
+/// replacing any profile with the same name.
+#[tauri::command]
+async fn save_profile(
+    app: tauri::AppHandle,
+    name: String,
+    engine_path: String,
+    engine_kind: Option<engine::EngineKind>,
+    config: launch_config::LaunchConfig,
+) -> Result<(), String> {
+    let profile = profiles::Profile { name, engine_path, engine_kind, config };
+    profiles::save_profile(profiles_path(&app)?, profile)
+}
+
+/// Saved launch profiles, oldest first.
+#[tauri::command]
+async fn list_profiles(app: tauri::AppHandle) -> Result<Vec<profiles::Profile>, String> {
+    profiles::list_profiles(profiles_path(&app)?)
+}
+
+/// One saved profile by name; errors if there is none.
+#[tauri::command]
+async fn load_profile(app: tauri::AppHandle, name: String) -> Result<profiles::Profile, String> {
+    profiles::load_profile(profiles_path(&app)?, &name)
+}
+
+/// Point a profile at its own engine config (created by cloning
+/// `base_config` if given) and return the path it launches with.
+#[tauri::command]
+async fn prepare_profile_config(
+    app: tauri::AppHandle,
+    name: String,
+    base_config: Option<String>,
+) -> Result<String, String> {
+    profiles::prepare_profile_config(profiles_path(&app)?, app_config_dir(&app)?, &name, base_config.as_deref())
+}
+
+/// Point a profile at its own save directory under the app data dir,
+/// creating it, and return the path saves will go to.
+#[tauri::command]
+async fn prepare_profile_savedir(app: tauri::AppHandle, name: String) -> Result<String, String> {
+    profiles::prepare_profile_savedir(profiles_path(&app)?, app_data_dir(&app)?, &name)
+}
+
+fn play_stats_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(play_stats::play_stats_path(app_config_dir(app)?))
+}
+
+/// Total play time per WAD (the first -file of each launch, else the
+/// IWAD), most played first. A renamed file keeps its total.
+#[tauri::command]
+async fn get_play_stats(app: tauri::AppHandle) -> Result<Vec<play_stats::PlayStat>, String> {
+    play_stats::get_play_stats(play_stats_path(&app)?)
+}
+
+fn recent_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    Ok(recent::recent_path(app_config_dir(app)?))
+}
+
+/// Record a WAD/PK3 as just played, moving it to the top of the recent
+/// list (which keeps the 20 latest, one entry per file).
+#[tauri::command]
+async fn add_recent(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    recent::add_recent(recent_path(&app)?, &path)
+}
+
+/// Recently played files, newest first, with when each was last played
+/// (ms since the Unix epoch).
+#[tauri::command]
+async fn get_recent(app: tauri::AppHandle) -> Result<Vec<recent::RecentEntry>, String> {
+    recent::get_recent(recent_path(&app)?)
+}
+
+/// Empty the recent list.
+#[tauri::command]
+async fn clear_recent(app: tauri::AppHandle) -> Result<(), String> {
+    recent::clear_recent(recent_path(&app)?)
+}
+
 /// Copy a user-picked file into the library. Bypasses fs:scope so the source
 /// path can be anywhere on disk; the target is constrained to a path the
 /// frontend computes from the library root.
@@ -60,6 +192,59 @@ async fn extract_game_files(
     game_archives::extract_game_files(&zip_path, &dest_dir)
 }
 
+/// Whether an archive can be loaded as is (a PK3, or a ZIP laid out like
+/// one) or needs extracting first, with the files or folder to load then
+/// and a message for the user. Launch configs refuse a .zip that isn't
+/// loadable with the same message.
+#[tauri::command]
+async fn inspect_archive(zip_path: String) -> Result<game_archives::ArchiveAdvice, String> {
+    game_archives::inspect_archive(&zip_path)
+}
+
+/// Back up GZDoom's config and saves into a new gzdoom-backup-<time>.zip in
+/// `dest_dir`: the given folders and files, or GZDoom's default locations
+/// on this platform. Returns the zip's path.
+#[tauri::command]
+async fn backup_gzdoom_data(dest_dir: String, sources: Option<Vec<String>>) -> Result<String, String> {
+    backup::backup_gzdoom_data(&dest_dir, &sources.unwrap_or_default())
+}
+
+/// The files restoring a backup would write, each saying whether it would
+/// replace one, for confirming before restore_gzdoom_data. Only GZDoom's
+/// default folders and `allowed_dirs` may be restored to.
+#[tauri::command]
+async fn plan_gzdoom_restore(
+    zip_path: String,
+    allowed_dirs: Option<Vec<String>>,
+) -> Result<Vec<backup::RestoreTarget>, String> {
+    backup::plan_restore(&zip_path, &allowed_dirs.unwrap_or_default())
+}
+
+/// Restore a backup made by backup_gzdoom_data to where it was taken from,
+/// if that is GZDoom's default folders or within `allowed_dirs`. Without
+/// `overwrite`, existing files fail the restore with a "Restoring would
+/// overwrite" error listing them, for the UI to confirm and retry.
+/// Returns the restored paths.
+#[tauri::command]
+async fn restore_gzdoom_data(
+    zip_path: String,
+    allowed_dirs: Option<Vec<String>>,
+    overwrite: Option<bool>,
+) -> Result<Vec<String>, String> {
+    backup::restore_gzdoom_data(&zip_path, &allowed_dirs.unwrap_or_default(), overwrite.unwrap_or(false))
+}
+
+/// Unpack a downloaded mod zip into `dest_dir`, keeping its layout, and
+/// return the WAD/PK3/DEH files extracted (.txt too with `include_text`).
+#[tauri::command]
+async fn extract_archive(
+    zip_path: String,
+    dest_dir: String,
+    include_text: Option<bool>,
+) -> Result<Vec<String>, String> {
+    game_archives::extract_archive(&zip_path, &dest_dir, include_text.unwrap_or(false))
+}
+
 /// List zip entries (path + uncompressed size) without reading contents.
 #[tauri::command]
 async fn list_zip_entries(zip_path: String) -> Result<Vec<game_archives::ZipEntryInfo>, String> {
@@ -81,6 +266,387 @@ async fn extract_zip_entry_to_temp(zip_path: String, entry_path: String) -> Resu
     game_archives::extract_zip_entry_to_temp(&zip_path, &entry_path)
 }
 
+/// MD5/SHA-1 of a WAD/PK3 plus a hash of its lump directory, streamed so
+/// large files never load whole. Bypasses fs:scope.
+#[tauri::command]
+async fn wad_checksum(wad_path: String) -> Result<wad_parser::WadHashes, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::wad_checksum(&wad_path)?)
+}
+
+/// Classify a file as IWAD/PWAD/PK3/IPK3/ZIP by its magic bytes; anything
+/// else is unknown rather than an error.
+#[tauri::command]
+async fn detect_wad_type(path: String) -> Result<wad_parser::WadType, launch_error::LaunchError> {
+    wad_parser::check_exists(&path)?;
+    Ok(wad_parser::detect_wad_type(&path)?)
+}
+
+/// Best guess at the IWAD a PWAD/PK3 needs, so the launcher can pick it.
+/// This and the other parser commands fail with a LaunchError: notFound
+/// or invalidWad for an unusable file, otherwise the parser's message.
+#[tauri::command]
+async fn detect_required_iwad(wad_path: String) -> Result<wad_parser::IwadGuess, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::detect_required_iwad(&wad_path)?)
+}
+
+/// The 256 RGB colours graphics in a WAD/PK3 are drawn with: its own
+/// PLAYPAL, falling back to the one in `iwad_path`.
+#[tauri::command]
+async fn get_palette(wad_path: String, iwad_path: Option<String>) -> Result<Vec<[u8; 3]>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::read_palette(&wad_path, iwad_path.as_deref())?.to_vec())
+}
+
+/// Plain-text description embedded in a WAD/PK3/ZIP (README lump, the
+/// idgames .txt), or null if there is none.
+#[tauri::command]
+async fn extract_description(
+    wad_path: String,
+) -> Result<Option<wad_parser::ModDescription>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::extract_description(&wad_path)?)
+}
+
+/// Estimate the minimum source-port features (vanilla, Boom, MBF21, ZDoom,
+/// GZDoom) a WAD/PK3 needs, with the evidence and the engines that qualify.
+#[tauri::command]
+async fn detect_port_features(wad_path: String) -> Result<compat::Compatibility, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(compat::detect_compatibility(&wad_path)?)
+}
+
+/// The -complevel to run a WAD/PK3 at in PrBoom+/DSDA-Doom/Woof!, from its
+/// COMPLVL lump or else inferred from what it uses; `iwad_path` decides
+/// which vanilla level. complevel is null for ZDoom-only files.
+#[tauri::command]
+async fn detect_complevel(
+    wad_path: String,
+    iwad_path: Option<String>,
+) -> Result<compat::Complevel, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(compat::detect_complevel(&wad_path, iwad_path.as_deref())?)
+}
+
+/// Doom-format maps too big for vanilla ports (Chocolate Doom and kin):
+/// over a hard 16-bit limit, so the map won't load, or large enough that
+/// visplane and drawseg overflows are likely. Empty when nothing stands out.
+#[tauri::command]
+async fn check_vanilla_limits(wad_path: String) -> Result<Vec<compat::LimitWarning>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(compat::check_vanilla_limits(&wad_path)?)
+}
+
+/// check_file on each file of a load order, so the commands below fail the
+/// way the single-file parser commands do.
+fn check_files(files: &[String]) -> Result<(), launch_error::LaunchError> {
+    files.iter().try_for_each(|file| wad_parser::check_file(file).map(|_| ()))
+}
+
+/// Lumps each PWAD replaces in the files loaded before it (the IWAD first,
+/// when given), with readable warnings like "DOOM2.WAD defines MAP01,
+/// mymod.wad overrides it".
+#[tauri::command]
+async fn check_load_order(
+    iwad: Option<String>,
+    pwads: Vec<String>,
+) -> Result<load_order::LoadOrderReport, launch_error::LaunchError> {
+    let files: Vec<String> = iwad.into_iter().chain(pwads).collect();
+    check_files(&files)?;
+    Ok(load_order::check_load_order(&files)?)
+}
+
+/// SNDINFO sound and music definitions across the load order (the IWAD
+/// first, when given), each with the file it comes from and the earlier
+/// file it replaces, to flag mods with their own sounds or soundtrack.
+#[tauri::command]
+async fn list_sound_definitions(
+    iwad: Option<String>,
+    pwads: Vec<String>,
+) -> Result<Vec<sndinfo::SoundDefinition>, launch_error::LaunchError> {
+    let files: Vec<String> = iwad.into_iter().chain(pwads).collect();
+    check_files(&files)?;
+    Ok(sndinfo::list_sound_definitions(&files)?)
+}
+
+/// The level names a load order shows in game (the IWAD first, when given),
+/// later files winning, each with the file it comes from and the earlier
+/// one it replaces.
+#[tauri::command]
+async fn merge_level_names(
+    iwad: Option<String>,
+    pwads: Vec<String>,
+) -> Result<Vec<wad_parser::MergedLevelName>, launch_error::LaunchError> {
+    let files: Vec<String> = iwad.into_iter().chain(pwads).collect();
+    check_files(&files)?;
+    Ok(wad_parser::merge_level_names(&files)?)
+}
+
+/// The maps more than one file of a load order (the IWAD first, when given)
+/// names, with every file's name for each, to explain a level title that
+/// isn't the expected one.
+#[tauri::command]
+async fn level_name_collisions(
+    iwad: Option<String>,
+    pwads: Vec<String>,
+) -> Result<Vec<wad_parser::LevelNameCollision>, launch_error::LaunchError> {
+    let files: Vec<String> = iwad.into_iter().chain(pwads).collect();
+    check_files(&files)?;
+    Ok(wad_parser::level_name_collisions(&files)?)
+}
+
+/// The actor classes a WAD/PK3 declares in DECORATE and ZSCRIPT, following
+/// #include within the file: each with its parent, what it replaces, its
+/// editor number and whether it is a monster, for "adds 14 new monsters".
+#[tauri::command]
+async fn list_actor_classes(wad_path: String) -> Result<Vec<actors::ActorClass>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(actors::list_actor_classes(&wad_path)?)
+}
+
+/// MUSINFO tracks across the load order (the IWAD first, when given): the
+/// music each map switches to when the player touches a music changer,
+/// with the file each track comes from and the earlier one it replaces.
+#[tauri::command]
+async fn list_music_changes(
+    iwad: Option<String>,
+    pwads: Vec<String>,
+) -> Result<Vec<musinfo::MusicChange>, launch_error::LaunchError> {
+    let files: Vec<String> = iwad.into_iter().chain(pwads).collect();
+    check_files(&files)?;
+    Ok(musinfo::list_music_changes(&files)?)
+}
+
+/// Download a mod to `dest_path`, resuming an earlier partial download,
+/// emitting "mod-download-progress" events ({url, downloaded, total}) along
+/// the way. When `expected_sha1` is given a mismatching file is discarded
+/// and the command fails. Returns the final path.
+#[tauri::command]
+async fn download_mod(
+    app: tauri::AppHandle,
+    url: String,
+    dest_path: String,
+    expected_sha1: Option<String>,
+) -> Result<String, String> {
+    download::download(&url, &dest_path, expected_sha1.as_deref(), |progress| {
+        if let Err(e) = app.emit("mod-download-progress", progress) {
+            eprintln!("Failed to emit download progress: {}", e);
+        }
+    })
+    .await
+}
+
+/// The idgames archive entry (title, author, description) for a WAD, or
+/// null if it isn't there. Answers from the local cache when offline.
+#[tauri::command]
+async fn identify_idgames(app: tauri::AppHandle, wad_path: String) -> Result<Option<idgames::IdgamesEntry>, String> {
+    idgames::identify(&idgames::cache_path(app_data_dir(&app)?), &wad_path).await
+}
+
+/// Title screen of a WAD/PK3 as a PNG data URL for mod previews, or null if
+/// it has none. `iwad_path` supplies the palette for PWADs without one.
+#[tauri::command]
+async fn extract_titlepic(
+    wad_path: String,
+    iwad_path: Option<String>,
+) -> Result<Option<wad_parser::Graphic>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::extract_titlepic(&wad_path, iwad_path.as_deref())?)
+}
+
+/// Status bar graphic of a WAD/PK3 (STBAR, or Heretic/Hexen's) as a PNG
+/// data URL for previewing HUD mods, or null if it doesn't replace it.
+/// `iwad_path` supplies the palette, as for extract_titlepic.
+#[tauri::command]
+async fn extract_status_bar(
+    wad_path: String,
+    iwad_path: Option<String>,
+) -> Result<Option<wad_parser::Graphic>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::extract_status_bar(&wad_path, iwad_path.as_deref())?)
+}
+
+/// Every lump of a WAD (name, size, offset, flats/sprites/patches
+/// namespace), or every file of a PK3, for inspecting it. Only the
+/// directory is read.
+#[tauri::command]
+async fn list_wad_lumps(wad_path: String) -> Result<Vec<wad_parser::LumpEntry>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::list_wad_lumps(&wad_path)?)
+}
+
+/// One lump's raw bytes, base64-encoded, for viewing MAPINFO or DECORATE
+/// as text or exporting a lump. In a PK3 a lump name finds root entries;
+/// one in a folder needs its entry path. A name can appear more than once;
+/// without `index` the last one (the lump engines use) is read, and with
+/// it the entry at that position in list_wad_lumps.
+#[tauri::command]
+async fn read_wad_lump(
+    wad_path: String,
+    lump_name: String,
+    index: Option<usize>,
+) -> Result<String, launch_error::LaunchError> {
+    use base64::Engine;
+    wad_parser::check_file(&wad_path)?;
+    let bytes = wad_parser::read_wad_lump(&wad_path, &lump_name, index)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// The frames of one sprite ("TROO", "PISG") a WAD/PK3 defines, as PNG
+/// data URLs for previewing replaced monsters and weapons. `iwad_path`
+/// supplies the palette, as for extract_titlepic.
+#[tauri::command]
+async fn extract_sprites(
+    wad_path: String,
+    prefix: String,
+    iwad_path: Option<String>,
+) -> Result<Vec<wad_parser::SpriteFrame>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::extract_sprites(&wad_path, &prefix, iwad_path.as_deref())?)
+}
+
+/// Episodes a WAD defines for the new-game menu (start map, name, picture).
+/// `clearsDefaults` says whether they replace the IWAD's episodes. Names are
+/// looked up in LANGUAGE, in `locale` when given, else English.
+#[tauri::command]
+async fn extract_episodes(
+    wad_path: String,
+    locale: Option<String>,
+) -> Result<mapinfo::EpisodeList, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::extract_episodes(&wad_path, locale.as_deref())?)
+}
+
+/// MAPINFO gameinfo (player classes, title and credit pages, base game)
+/// and IPK3 IWADINFO, with `totalConversion` set for standalone games and
+/// mods that replace the player. Null when the file declares neither.
+#[tauri::command]
+async fn extract_gameinfo(wad_path: String) -> Result<Option<mapinfo::GameInfo>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::extract_gameinfo(&wad_path)?)
+}
+
+/// The story screens of each MAPINFO cluster, with LANGUAGE lookups and
+/// text lumps resolved, for previewing a campaign's between-map text.
+#[tauri::command]
+async fn extract_cluster_texts(
+    wad_path: String,
+    locale: Option<String>,
+) -> Result<Vec<wad_parser::ClusterText>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::extract_cluster_texts(&wad_path, locale.as_deref())?)
+}
+
+/// Per-level MAPINFO data (name, par time, exits, music) from a WAD,
+/// PK3/IPK3 or ZIP, including WADs nested inside archives. "$KEY" names
+/// come from the file's LANGUAGE, in `locale` when given, else English.
+#[tauri::command]
+async fn extract_levels(
+    wad_path: String,
+    locale: Option<String>,
+) -> Result<Vec<mapinfo::LevelInfo>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::extract_levels(&wad_path, locale.as_deref())?)
+}
+
+/// Level names like extract_level_names, served from `{wad}.levels.json`
+/// while the WAD is unchanged and re-parsed (and re-cached) when it isn't.
+#[tauri::command]
+async fn get_cached_level_names(
+    wad_path: String,
+) -> Result<std::collections::HashMap<String, String>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(level_cache::level_names_cached(&wad_path)?)
+}
+
+/// Every map in a WAD/PK3, with its MAPINFO or DEHACKED name when it has
+/// one, and monster/item/secret totals at `skill` (default 4, Ultra-Violence).
+#[tauri::command]
+async fn list_maps(
+    wad_path: String,
+    skill: Option<u8>,
+) -> Result<Vec<wad_parser::MapEntry>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::list_maps(&wad_path, skill.unwrap_or(4))?)
+}
+
+/// A rough 1-5 difficulty rating per map at `skill` (default 4, Ultra-
+/// Violence), as [mapId, score] pairs in directory order; score carries the
+/// monster count, hit points and density it was based on. A companion
+/// .deh/.bex patch's monster health counts, as for extract_level_names.
+#[tauri::command]
+async fn estimate_difficulty(
+    wad_path: String,
+    skill: Option<u8>,
+    deh_path: Option<String>,
+) -> Result<Vec<(String, difficulty::DifficultyScore)>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    let patches: Vec<&str> = deh_path.as_deref().into_iter().collect();
+    Ok(difficulty::estimate_difficulty(&wad_path, skill.unwrap_or(4), &patches)?)
+}
+
+/// list_maps plus the game the file is (or is for) and, for Hexen, the
+/// hubs the maps are grouped in.
+#[tauri::command]
+async fn list_levels(
+    wad_path: String,
+    skill: Option<u8>,
+) -> Result<wad_parser::LevelListing, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    Ok(wad_parser::list_levels(&wad_path, skill.unwrap_or(4))?)
+}
+
+/// Level names only (map id -> name), from MAPINFO and any embedded
+/// DEHACKED, plus a companion .deh/.bex patch when given; see extract_levels.
+#[tauri::command]
+async fn extract_level_names(
+    wad_path: String,
+    deh_path: Option<String>,
+) -> Result<std::collections::HashMap<String, String>, launch_error::LaunchError> {
+    wad_parser::check_file(&wad_path)?;
+    let patches: Vec<&str> = deh_path.as_deref().into_iter().collect();
+    Ok(wad_parser::extract_level_names_with_patches(&wad_path, &patches)?)
+}
+
+/// Identify every .wad/.ipk3 under `dir` as a known IWAD (by checksum or
+/// characteristic lumps). Shared by the browse button and the default
+/// iwads folder so both agree on what is installed.
+#[tauri::command]
+async fn scan_iwads(dir: String) -> Result<Vec<iwad::IwadInfo>, String> {
+    iwad::scan_iwads(&dir)
+}
+
+/// The folders to look for IWADs in: the user's `extra_dirs`, then the ones
+/// GZDoom itself searches (next to `engine_path`, $DOOMWADDIR, the
+/// platform defaults, $DOOMWADPATH). Only existing folders, each once.
+#[tauri::command]
+async fn iwad_search_dirs(engine_path: Option<String>, extra_dirs: Option<Vec<String>>) -> Vec<String> {
+    iwad::iwad_search_dirs(engine_path.as_deref(), &extra_dirs.unwrap_or_default())
+}
+
+/// Identify the IWADs directly in each of `dirs` (as from iwad_search_dirs),
+/// keeping one entry per IWAD: the copy in the earliest folder.
+#[tauri::command]
+async fn scan_iwad_dirs(dirs: Vec<String>) -> Vec<iwad::IwadInfo> {
+    iwad::scan_iwad_dirs(&dirs)
+}
+
+/// Copy, link or move the IWAD at `src` into the library's `iwads_dir`,
+/// named for its game so the IWAD picker finds it. The result says which
+/// mode was used and why, when a fallback was needed.
+#[tauri::command]
+async fn import_iwad(src: String, iwads_dir: String, mode: iwad::ImportMode) -> Result<iwad::ImportedIwad, String> {
+    iwad::import_iwad(&src, &iwads_dir, mode)
+}
+
+/// Groups of identical mod files (same MD5) under `dir`, for offering
+/// cleanup of copies saved under different names.
+#[tauri::command]
+async fn find_duplicate_wads(dir: String) -> Result<Vec<Vec<String>>, String> {
+    duplicates::find_duplicate_wads(&dir)
+}
+
 /// Create a unique temp directory (innoextract's --output-dir target).
 #[tauri::command]
 async fn make_temp_dir() -> Result<String, String> {
@@ -104,6 +670,14 @@ async fn collect_known_wads(
     gog_import::collect_known_wads(&src_dir, &dest_dir, &wanted)
 }
 
+/// Group the WADs/PK3s directly in `dir` with the .deh/.bex patches and
+/// .txt sharing their name, for loading as one bundle through the launch
+/// config's `bundles`.
+#[tauri::command]
+async fn scan_mod_bundles(dir: String) -> Result<Vec<bundles::ModBundle>, String> {
+    bundles::scan_bundles(&dir)
+}
+
 /// Look for an idgames-style metadata sidecar next to a user-picked file.
 /// Tries `<basename>.txt` (case-insensitive) first, then any single `*.txt`
 /// in the same directory. Returns "" when nothing matches. Bypasses fs:scope.
@@ -185,66 +759,40 @@ async fn write_custom_wads(library_path: String, state: serde_json::Value) -> Re
         .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
 }
 
-// Output collector for the most recent GZDoom launch, held in Tauri managed
-// state. Mutex<Option<...>> so each launch replaces the previous session.
-struct GzdoomLog(Mutex<Option<Arc<Mutex<GZDoomSession>>>>);
-
-struct GZDoomSession {
-    start_time: std::time::Instant,
-    lines: Vec<(u64, String)>, // (time_ms, line)
-    finished: bool,
-}
-
-impl GZDoomSession {
-    fn new() -> Self {
-        Self {
-            start_time: std::time::Instant::now(),
-            lines: Vec::new(),
-            finished: false,
-        }
+/// Get the version of GZDoom/UZDoom from the app bundle's Info.plist or
+/// the executable's version resource, else as for gzdoom_version.
+/// Returns the version string (e.g., "g4.14.2") or an error.
+#[tauri::command]
+async fn get_engine_version(
+    sessions: State<'_, session::Sessions>,
+    engine_path: String,
+    engine_kind: Option<engine::EngineKind>,
+) -> Result<String, String> {
+    let engine_path = engine::bundle_executable(&engine_path)?;
+    let kind = engine::resolve_engine(&engine_path, engine_kind)?;
+    if let Ok(version) = get_engine_version_impl(&engine_path) {
+        return Ok(version);
     }
-}
-
-/// Sanity check that a configured engine path names a GZDoom-family binary
-/// before we exec it. This guards against misconfiguration (picking the
-/// wrong file in Settings), not a hostile webview — it is not a security
-/// boundary. The executable's basename must name the engine; a mere
-/// substring anywhere in the path (/tmp/gzdoom-stuff/other) doesn't pass.
-fn validate_engine_path(engine_path: &str) -> Result<(), String> {
-    let stem = Path::new(engine_path)
-        .file_stem()
-        .map(|s| s.to_string_lossy().to_lowercase())
-        .unwrap_or_default();
-    if stem.contains("gzdoom") || stem.contains("uzdoom") {
-        Ok(())
+    let info = engine_version_with_banner(&sessions, &engine_path, Some(kind));
+    if info.is_known() {
+        Ok(info.raw)
     } else {
-        Err(format!(
-            "Invalid engine path: expected a GZDoom/UZDoom executable, got '{}'",
-            engine_path
-        ))
+        Err("Could not determine the engine version; launch it once to read it from its startup banner".to_string())
     }
 }
 
-/// Get the version of GZDoom/UZDoom from the app bundle's Info.plist.
-/// Returns the version string (e.g., "g4.14.2") or an error.
-#[tauri::command]
-async fn get_engine_version(engine_path: String) -> Result<String, String> {
-    validate_engine_path(&engine_path)?;
-    get_engine_version_impl(&engine_path)
-}
-
 #[cfg(target_os = "macos")]
 fn get_engine_version_impl(engine_path: &str) -> Result<String, String> {
     // Extract app bundle path from executable path
     // e.g., /Applications/GZDoom.app/Contents/MacOS/gzdoom -> /Applications/GZDoom.app
-    let path = Path::new(engine_path);
+    let path = std::path::Path::new(engine_path);
     let app_path = path
         .ancestors()
         .find(|p| p.extension().is_some_and(|ext| ext == "app"))
         .ok_or("Could not find .app bundle")?;
 
     let info_plist = app_path.join("Contents/Info.plist");
-    let output = Command::new("defaults")
+    let output = std::process::Command::new("defaults")
         .arg("read")
         .arg(&info_plist)
         .arg("CFBundleShortVersionString")
@@ -263,21 +811,127 @@ fn get_engine_version_impl(engine_path: &str) -> Result<String, String> {
 
 #[cfg(target_os = "linux")]
 fn get_engine_version_impl(engine_path: &str) -> Result<String, String> {
-    let output = Command::new(engine_path)
-        .arg("--version")
-        .output()
-        .map_err(|e| format!("Failed to run engine with --version: {}", e))?;
+    Err(format!("{} has no version metadata (Linux executables carry none)", engine_path))
+}
+
+/// How long a `--version`/`-version` probe may run before it is assumed to
+/// have started the game instead.
+const VERSION_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Version from platform metadata, else from running the engine with its
+/// version flag when it has one that exits (engine_version::version_flag);
+/// "unknown" if neither works. `kind` is inferred from the name if None.
+fn probe_engine_version(path: &str, kind: Option<engine::EngineKind>) -> engine_version::VersionInfo {
+    if let Ok(raw) = get_engine_version_impl(path) {
+        let info = engine_version::parse_version(&raw);
+        if info.is_known() {
+            return info;
+        }
+    }
+    let flag = kind.or_else(|| engine::EngineKind::from_executable(path)).and_then(engine_version::version_flag);
+    if let Some(flag) = flag
+        && let Ok(Some(raw)) = engine_version::probe(path, flag, VERSION_PROBE_TIMEOUT)
+    {
+        let info = engine_version::parse_version(&raw);
+        if info.is_known() {
+            return info;
+        }
+    }
+    engine_version::VersionInfo::unknown()
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let first_line = stdout
-        .lines()
-        .chain(stderr.lines())
-        .map(str::trim)
-        .find(|line| !line.is_empty())
-        .ok_or("Engine did not return a version string")?;
+/// GZDoom-family engines installed in the usual places for this OS, each
+/// with its detected version (unknown on Linux until it has been run), for
+/// first-run setup.
+#[tauri::command]
+async fn find_gzdoom_installs(
+    sessions: State<'_, session::Sessions>,
+) -> Result<Vec<engine_discovery::EngineInstall>, String> {
+    Ok(engine_discovery::existing(engine_discovery::candidate_paths())
+        .into_iter()
+        .map(|path| {
+            let path = path.to_string_lossy().to_string();
+            let binary = engine::bundle_executable(&path).unwrap_or_else(|e| {
+                eprintln!("Failed to resolve {} for its version: {}", path, e);
+                path.clone()
+            });
+            let version = engine_version_with_banner(&sessions, &binary, None);
+            engine_discovery::EngineInstall { path, version }
+        })
+        .collect())
+}
+
+/// Parsed engine version for feature gating (MBF21, ZScript versions).
+/// Tries platform version metadata, then the version flag of ports that
+/// print it and exit, then the startup banner of the last session run with
+/// this engine; "unknown" if all fail. Ports that would start the game on
+/// a version flag aren't run.
+#[tauri::command]
+async fn gzdoom_version(
+    sessions: State<'_, session::Sessions>,
+    path: String,
+    engine_kind: Option<engine::EngineKind>,
+) -> Result<engine_version::VersionInfo, String> {
+    let path = engine::bundle_executable(&path)?;
+    let kind = engine::resolve_engine(&path, engine_kind)?;
+    Ok(engine_version_with_banner(&sessions, &path, Some(kind)))
+}
+
+/// probe_engine_version, falling back to the banner of a session run with
+/// this engine.
+fn engine_version_with_banner(
+    sessions: &session::Sessions,
+    path: &str,
+    kind: Option<engine::EngineKind>,
+) -> engine_version::VersionInfo {
+    let info = probe_engine_version(path, kind);
+    if info.is_known() {
+        return info;
+    }
+    sessions
+        .first_lines_for(path, 5)
+        .iter()
+        .map(|line| engine_version::parse_version(line))
+        .find(engine_version::VersionInfo::is_known)
+        .unwrap_or_else(engine_version::VersionInfo::unknown)
+}
+
+/// Whether the engine takes a command-line `flag` ("-complevel"; a value
+/// after it is ignored), from a table of which ports take which flags and
+/// since which release. The version is probed only when that matters. An
+/// engine whose kind can't be inferred, and any flag not in the table,
+/// count as unsupported, so the UI leaves the flag out.
+#[tauri::command]
+async fn engine_supports_flag(
+    sessions: State<'_, session::Sessions>,
+    engine_path: String,
+    flag: String,
+    engine_kind: Option<engine::EngineKind>,
+) -> Result<bool, String> {
+    let engine_path = engine::bundle_executable(&engine_path)?;
+    let Some(kind) = engine_kind.or_else(|| engine::EngineKind::from_executable(&engine_path)) else {
+        return Ok(false);
+    };
+    Ok(engine_flags::supports_flag(kind, &flag, || engine_version_with_banner(&sessions, &engine_path, Some(kind))))
+}
 
-    Ok(first_line.to_string())
+/// Compare the ZScript `version` the files declare with the engine's
+/// version, warning before launch when the engine is too old to load them.
+/// VKDoom numbers its releases separately from GZDoom, so it isn't compared.
+#[tauri::command]
+async fn check_zscript_version(
+    sessions: State<'_, session::Sessions>,
+    engine_path: String,
+    files: Vec<String>,
+) -> Result<compat::ZscriptCheck, String> {
+    let engine_path = engine::bundle_executable(&engine_path)?;
+    let name = engine_path.rsplit(['/', '\\']).next().unwrap_or(&engine_path).to_lowercase();
+    let engine = if name.starts_with("vkdoom") {
+        engine_version::VersionInfo::unknown()
+    } else {
+        engine_version_with_banner(&sessions, &engine_path, None)
+    };
+    compat::check_zscript_version(&files, &engine)
 }
 
 #[cfg(target_os = "windows")]
@@ -385,108 +1039,376 @@ fn get_engine_version_impl(engine_path: &str) -> Result<String, String> {
 }
 
 /// Launch GZDoom/UZDoom with the specified executable path and arguments.
-/// Captures stdout/stderr for later retrieval via get_gzdoom_log and emits
-/// a "gzdoom-exited" event when the process ends.
+/// Captures stdout/stderr for later retrieval via get_gzdoom_log, streams
+/// each line as a "gzdoom-log-line" event while running, and emits a
+/// "gzdoom-exited" event when the process ends. Returns the session id that
+/// those events carry and the other session commands take. Several engines
+/// can run at once. A path to a macOS .app bundle launches the binary
+/// inside it, so its output can be captured. Despite the name it drives
+/// any supported source port: `engine_kind` is inferred from the executable
+/// name when omitted, and must be given for renamed binaries. A binary
+/// without the execute bit is an error unless `make_executable` is set, in
+/// which case it is chmod +x'd first. `spawnOptions` ({env, cwd,
+/// startupTimeoutMs, killIfHung}) adds environment variables such as
+/// DOOMWADDIR and sets the working directory, which must exist; with a
+/// startup timeout, an engine that stays silent that long before finishing
+/// startup gets a "gzdoom-possibly-hung" event ({sessionId, silentMs,
+/// killed}) and, with killIfHung, is stopped. Every -iwad/-file/-deh/-bex
+/// path must exist and, if allowedDirs are given, lie inside one of them;
+/// a failing argument is an invalidArgument error naming it. With
+/// `check_running`, a copy of the engine that is already open (which would
+/// fight over its config) fails the launch with an alreadyRunning error,
+/// for the UI to confirm and retry without the check. Errors are
+/// LaunchErrors, tagged by kind for the UI to act on.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn launch_gzdoom(
     app: tauri::AppHandle,
-    log: State<'_, GzdoomLog>,
+    sessions: State<'_, session::Sessions>,
     gzdoom_path: String,
     args: Vec<String>,
-) -> Result<(), String> {
-    validate_engine_path(&gzdoom_path)?;
-
-    // Create a new session (replaces any previous one)
-    let session = Arc::new(Mutex::new(GZDoomSession::new()));
-    *log.0.lock().unwrap() = Some(session.clone());
-
-    // Spawn GZDoom with piped stdout/stderr
-    let mut child = Command::new(&gzdoom_path)
-        .args(&args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to launch engine at '{}': {}", gzdoom_path, e))?;
-
-    // Take ownership of stdout and stderr
-    let stdout = child.stdout.take();
-    let stderr = child.stderr.take();
-
-    // Spawn thread to read stdout
-    if let Some(stdout) = stdout {
-        let session_clone = session.clone();
-        thread::spawn(move || {
-            let reader = BufReader::new(stdout);
-            for line in reader.lines().map_while(Result::ok) {
-                let mut guard = session_clone.lock().unwrap();
-                let elapsed = guard.start_time.elapsed().as_millis() as u64;
-                guard.lines.push((elapsed, line));
-            }
-        });
+    engine_kind: Option<engine::EngineKind>,
+    make_executable: Option<bool>,
+    spawn_options: Option<session::SpawnOptions>,
+    check_running: Option<bool>,
+) -> Result<session::SessionId, launch_error::LaunchError> {
+    let gzdoom_path = engine::bundle_executable(&gzdoom_path)?;
+    engine::resolve_engine(&gzdoom_path, engine_kind)?;
+    engine::check_executable(&gzdoom_path, make_executable.unwrap_or(false))?;
+    if check_running.unwrap_or(false) {
+        process::check_not_running(&gzdoom_path)?;
     }
+    spawn_engine(app, &sessions, &gzdoom_path, &args, &spawn_options.unwrap_or_default())
+}
 
-    // Spawn thread to read stderr (merge with stdout)
-    if let Some(stderr) = stderr {
-        let session_clone = session.clone();
-        thread::spawn(move || {
-            let reader = BufReader::new(stderr);
-            for line in reader.lines().map_while(Result::ok) {
-                let mut guard = session_clone.lock().unwrap();
-                let elapsed = guard.start_time.elapsed().as_millis() as u64;
-                guard.lines.push((elapsed, line));
-            }
-        });
+/// Like launch_gzdoom, but builds the engine-specific arguments from a
+/// structured LaunchConfig. `extra_args` are appended verbatim;
+/// `spawn_options` and `check_running` are as for launch_gzdoom, with the
+/// config's `env` added to the options' and its `cwd` taking precedence.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn launch_gzdoom_with_config(
+    app: tauri::AppHandle,
+    sessions: State<'_, session::Sessions>,
+    gzdoom_path: String,
+    config: launch_config::LaunchConfig,
+    engine_kind: Option<engine::EngineKind>,
+    extra_args: Option<Vec<String>>,
+    make_executable: Option<bool>,
+    spawn_options: Option<session::SpawnOptions>,
+    check_running: Option<bool>,
+) -> Result<session::SessionId, launch_error::LaunchError> {
+    let gzdoom_path = engine::bundle_executable(&gzdoom_path)?;
+    let kind = engine::resolve_engine(&gzdoom_path, engine_kind)?;
+    engine::check_executable(&gzdoom_path, make_executable.unwrap_or(false))?;
+    if check_running.unwrap_or(false) {
+        process::check_not_running(&gzdoom_path)?;
     }
+    launch_config::prepare_savedir(&config)?;
+    let mut args = launch_config::build_args(&config, kind)?;
+    args.extend(extra_args.unwrap_or_default());
+    let mut options = spawn_options.unwrap_or_default();
+    options.env.extend(config.env);
+    options.cwd = config.cwd.or(options.cwd);
+    spawn_engine(app, &sessions, &gzdoom_path, &args, &options)
+}
 
-    // Wait for process exit, mark the session finished, then tell the
-    // frontend. The output-reader threads above may still be draining their
-    // final lines when wait() returns, so mark finished under the same lock
-    // they take per line — get_gzdoom_log snapshots whatever has been read.
-    thread::spawn(move || {
-        let _ = child.wait();
-        {
-            let mut guard = session.lock().unwrap();
-            guard.finished = true;
+/// Mapper's quick test: launch `config` warped to `map` ("MAP07", "E1M1")
+/// and wait up to `timeout_ms` (default 30 s) for the map to load — the
+/// level banner on ZDoom-family ports, on others still running without
+/// errors when the time is up. The engine is then killed unless
+/// `keep_running`; its log and events are those of any session.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn quick_test_map(
+    app: tauri::AppHandle,
+    sessions: State<'_, session::Sessions>,
+    gzdoom_path: String,
+    config: launch_config::LaunchConfig,
+    map: String,
+    engine_kind: Option<engine::EngineKind>,
+    timeout_ms: Option<u64>,
+    keep_running: Option<bool>,
+) -> Result<quick_test::QuickTestResult, launch_error::LaunchError> {
+    let gzdoom_path = engine::bundle_executable(&gzdoom_path)?;
+    let kind = engine::resolve_engine(&gzdoom_path, engine_kind)?;
+    engine::check_executable(&gzdoom_path, false)?;
+    let map = map.trim().to_uppercase();
+    let config = launch_config::LaunchConfig { warp: Some(map.clone()), ..config };
+    launch_config::prepare_savedir(&config)?;
+    let args = launch_config::build_args(&config, kind)?;
+    let options = session::SpawnOptions { env: config.env, cwd: config.cwd, ..Default::default() };
+    let id = spawn_engine(app, &sessions, &gzdoom_path, &args, &options)?;
+
+    let session = sessions.get(id)?;
+    let timeout = std::time::Duration::from_millis(timeout_ms.unwrap_or(quick_test::DEFAULT_TIMEOUT_MS));
+    let banners = matches!(kind, engine::EngineKind::GZDoom | engine::EngineKind::ZDoom | engine::EngineKind::Zandronum);
+    let mut result = tauri::async_runtime::spawn_blocking(move || quick_test::wait_for_map(&session, &map, timeout, banners))
+        .await
+        .map_err(|e| format!("Quick test stopped: {}", e))?;
+    if result.running && !keep_running.unwrap_or(false) {
+        match sessions.kill(id) {
+            Ok(()) => result.running = false,
+            // It exited on its own in the meantime.
+            Err(e) => eprintln!("Failed to stop quick test session {}: {}", id, e),
         }
-        if let Err(e) = app.emit("gzdoom-exited", ()) {
-            eprintln!("Failed to emit gzdoom-exited: {}", e);
+    }
+    Ok(result)
+}
+
+/// Dry run of launch_gzdoom_with_config: the same checks, then the command
+/// line it would run, quoted for this platform's shell, for copying into a
+/// terminal or a bug report, the -complevel chosen for PrBoom+/DSDA-Doom
+/// included. The config's `env` and `cwd` are checked but not part of the
+/// string.
+#[tauri::command]
+async fn build_command_line(
+    engine_path: String,
+    config: launch_config::LaunchConfig,
+    engine_kind: Option<engine::EngineKind>,
+    extra_args: Option<Vec<String>>,
+) -> Result<String, launch_error::LaunchError> {
+    let engine_path = engine::bundle_executable(&engine_path)?;
+    let kind = engine::resolve_engine(&engine_path, engine_kind)?;
+    engine::check_executable(&engine_path, false)?;
+    let mut args = launch_config::build_args(&config, kind)?;
+    args.extend(extra_args.unwrap_or_default());
+    let options = session::SpawnOptions { env: config.env, cwd: config.cwd, ..Default::default() };
+    options.check_cwd()?;
+    launch_args::check_args(&args, Some(&engine_path), options.cwd.as_deref(), &options.allowed_dirs)?;
+    Ok(launch_config::command_line(&engine_path, &args))
+}
+
+/// Pre-flight check of a launch without starting it: the engine, the IWAD,
+/// every file and patch, each file's port compatibility with the engine,
+/// the warp map and the remaining options, as a list of issues with
+/// severity "error" (the launch would fail) or "warning". Empty when the
+/// launch is expected to work.
+#[tauri::command]
+async fn validate_profile(
+    engine_path: String,
+    config: launch_config::LaunchConfig,
+    engine_kind: Option<engine::EngineKind>,
+) -> Vec<preflight::ValidationIssue> {
+    preflight::validate_profile(&engine_path, engine_kind, &config)
+}
+
+/// Start the engine as a new session, forwarding its output and exit to the
+/// frontend as "gzdoom-log-line" ([session_id, time_ms, line, stream]) and
+/// "gzdoom-exited" ({sessionId, exitCode, signal, runtimeMs, lineCount})
+/// events, a startup hang as "gzdoom-possibly-hung", and an engine with no
+/// console output at all as "gzdoom-no-output" (the session id). The
+/// arguments are validated first (launch_args::check_args). The
+/// session's runtime is added to the play stats once it exits.
+fn spawn_engine(
+    app: tauri::AppHandle,
+    sessions: &session::Sessions,
+    engine_path: &str,
+    args: &[String],
+    options: &session::SpawnOptions,
+) -> Result<session::SessionId, launch_error::LaunchError> {
+    launch_args::check_args(args, Some(engine_path), options.cwd.as_deref(), &options.allowed_dirs)?;
+    let counted = play_stats::counted_file(args);
+    let sink: session::EventSink = Arc::new(move |event| {
+        let result = match event {
+            session::SessionEvent::Line { session, elapsed, line, stream } => {
+                app.emit("gzdoom-log-line", (session, elapsed, line, stream))
+            }
+            session::SessionEvent::PossiblyHung(warning) => app.emit("gzdoom-possibly-hung", warning),
+            session::SessionEvent::NoOutput(session) => app.emit("gzdoom-no-output", session),
+            session::SessionEvent::Exited(summary) => {
+                let runtime_ms = summary.runtime_ms;
+                let result = app.emit("gzdoom-exited", summary);
+                // After the event: hashing a large file takes a moment.
+                if let Some(file) = &counted
+                    && let Err(e) = play_stats_path(&app).and_then(|path| play_stats::record_session(path, file, runtime_ms))
+                {
+                    eprintln!("Failed to record play time for {}: {}", file, e);
+                }
+                result
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to emit engine event: {}", e);
         }
     });
+    sessions.start_with(engine_path, args, options, sink)
+}
+
+/// Stop an engine session: a polite terminate first, then a hard kill if
+/// it is still alive after a short grace period.
+#[tauri::command]
+async fn kill_gzdoom(sessions: State<'_, session::Sessions>, session_id: session::SessionId) -> Result<(), String> {
+    sessions.kill(session_id)
+}
+
+/// Whether a process with this executable name is running, e.g. "gzdoom".
+/// Case-insensitive and .exe-tolerant on Windows. `also_names` are further
+/// names to look for (a fork's binaries); `match_mode` is "exact" (the
+/// default), "prefix" or "substring", so renamed and versioned builds like
+/// "gzdoom-g4.11" are found.
+#[tauri::command]
+async fn is_process_running(
+    name: String,
+    also_names: Option<Vec<String>>,
+    match_mode: Option<process::MatchMode>,
+) -> Result<bool, String> {
+    let names: Vec<String> = std::iter::once(name).chain(also_names.unwrap_or_default()).collect();
+    process::is_any_process_running(&names, match_mode.unwrap_or_default())
+}
 
+/// Show a WAD, save or folder in the OS file manager (selected in its
+/// folder in Finder and Explorer; the folder is opened on Linux).
+#[tauri::command]
+async fn reveal_in_file_manager(path: String) -> Result<(), String> {
+    reveal::reveal_in_file_manager(&path)
+}
+
+/// Get a session's captured console log after the game exits.
+/// Returns the [time_ms, text, stream] lines, stream being "stdout" or
+/// "stderr" (the most recent 50k in arrival order, with a count of
+/// dropped earlier lines) plus the exit code (and, on Unix, the terminating
+/// signal), or null while it is still running. Errors for an
+/// unknown (or long-pruned) session id.
+#[tauri::command]
+async fn get_gzdoom_log(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+) -> Result<Option<session::GZDoomLogResult>, String> {
+    sessions.finished_log(session_id)
+}
+
+/// What a bug report about a crashed engine needs: exit code or signal
+/// (named when known), the last `tail_lines` (default 100) log lines, the
+/// crash handler's backtrace, the log's errors and the crash dump file the
+/// engine wrote, if one can be found. Null while the session runs, after
+/// a clean exit or after kill_gzdoom stopped it.
+#[tauri::command]
+async fn get_crash_report(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+    tail_lines: Option<usize>,
+) -> Result<Option<crash_report::CrashReport>, String> {
+    let session = sessions.get(session_id)?;
+    let guard = session.lock().unwrap();
+    Ok(crash_report::crash_report(&guard, tail_lines.unwrap_or(crash_report::DEFAULT_TAIL_LINES)))
+}
+
+/// A session's log lines from `since_index` on, running or finished, and
+/// the index to pass next: [lines, nextIndex]. Lines are [time_ms, text,
+/// stream] as in get_gzdoom_log. Polling this is the alternative to the
+/// gzdoom-log-line events for a console that fills in while the game runs;
+/// start from 0.
+#[tauri::command]
+async fn get_gzdoom_log_partial(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+    since_index: u64,
+) -> Result<(Vec<session::LogLine>, u64), String> {
+    sessions.log_since(session_id, since_index)
+}
+
+/// Whether a session is running or finished (with its exit code and
+/// signal), or unknown: {state: "none" | "running" | "runningNoOutput" |
+/// "finished", …}. "runningNoOutput" is a running engine that has printed
+/// nothing since it started, so the console will stay empty.
+/// get_gzdoom_log's null only ever means "still running"; this also
+/// answers for ids that were never valid or have been pruned.
+#[tauri::command]
+async fn session_status(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+) -> Result<session::SessionStatus, String> {
+    Ok(sessions.status(session_id))
+}
+
+/// Set how many lines of each session's log are kept, the oldest dropped
+/// first: session::DEFAULT_MAX_LOG_LINES until a setting changes it.
+/// Applies to sessions started from now on.
+#[tauri::command]
+async fn set_max_log_lines(sessions: State<'_, session::Sessions>, max_lines: usize) -> Result<(), String> {
+    sessions.set_max_lines(max_lines);
     Ok(())
 }
 
-/// Get the captured GZDoom console log after the game exits.
-/// Returns JSON array of [time_ms, text] pairs, or null if no session/not finished.
+/// Empty a session's console ("Clear console"). A finished session keeps
+/// its exit status; a running one keeps logging into an empty log.
+/// Unknown session ids are ignored.
 #[tauri::command]
-async fn get_gzdoom_log(log: State<'_, GzdoomLog>) -> Result<Option<Vec<(u64, String)>>, String> {
-    let slot = log.0.lock().unwrap();
-    match slot.as_ref() {
-        Some(session) => {
-            let guard = session.lock().unwrap();
-            if guard.finished {
-                Ok(Some(guard.lines.clone()))
-            } else {
-                Ok(None) // Still running
-            }
-        }
-        None => Ok(None), // No session started
-    }
+async fn clear_gzdoom_log(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+) -> Result<(), String> {
+    sessions.clear(session_id);
+    Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::validate_engine_path;
+/// Write a session's log (so far) to a user-chosen file as text or JSON.
+/// Returns the path written, which gains a .log/.json extension if it had
+/// none.
+#[tauri::command]
+async fn save_gzdoom_log(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+    path: String,
+    format: Option<session::LogFormat>,
+) -> Result<String, String> {
+    sessions
+        .snapshot(session_id)?
+        .save(&path, format.unwrap_or(session::LogFormat::Text))
+}
 
-    #[test]
-    fn accepts_engine_basenames_rejects_substring_paths() {
-        assert!(validate_engine_path("/Applications/GZDoom.app/Contents/MacOS/gzdoom").is_ok());
-        assert!(validate_engine_path("C:\\Program Files\\UZDoom\\uzdoom.exe").is_ok());
-        assert!(validate_engine_path("/opt/homebrew/bin/gzdoom-4.11").is_ok());
-        // "gzdoom" in a parent directory is not enough.
-        assert!(validate_engine_path("/tmp/gzdoom-evil/malware").is_err());
-        assert!(validate_engine_path("/usr/bin/doom").is_err());
+/// Write a session's log (so far) to a temp file and open it in the OS text
+/// editor, for a quick look or copying into a bug report. Returns the temp
+/// path; files from earlier runs are deleted when the app starts.
+#[tauri::command]
+async fn open_log_in_editor(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+) -> Result<String, String> {
+    let path = sessions.snapshot(session_id)?.save_temp()?;
+    reveal::open_in_text_editor(&path)?;
+    Ok(path)
+}
+
+/// Errors and warnings from a session's log (running or finished), with the
+/// map and lump they mention, for a diagnostics summary.
+#[tauri::command]
+async fn analyze_gzdoom_log(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+) -> Result<log_analysis::LogDiagnostics, String> {
+    let session = sessions.get(session_id)?;
+    let guard = session.lock().unwrap();
+    Ok(log_analysis::analyze(&guard.lines))
+}
+
+/// What a session's startup banner says: engine version and build date,
+/// the files actually loaded (to compare with what was launched) and the
+/// renderer. Null until the engine has finished starting up.
+#[tauri::command]
+async fn get_session_info(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+) -> Result<Option<startup_banner::SessionInfo>, String> {
+    let session = sessions.get(session_id)?;
+    let guard = session.lock().unwrap();
+    Ok(startup_banner::parse_banner(guard.lines.iter().map(|(_, line, _)| line.as_str()), guard.finished))
+}
+
+/// Frame-rate summary of a finished -timedemo run, parsed from the captured
+/// log. Null while the engine is running, or if it printed no timing line.
+#[tauri::command]
+async fn get_timedemo_result(
+    sessions: State<'_, session::Sessions>,
+    session_id: session::SessionId,
+) -> Result<Option<demo::TimedemoResult>, String> {
+    let session = sessions.get(session_id)?;
+    let guard = session.lock().unwrap();
+    if !guard.finished {
+        return Ok(None);
     }
+    Ok(demo::parse_timedemo(guard.lines.iter().map(|(_, line, _)| line.as_str())))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -499,32 +1421,104 @@ pub fn run() {
         // restarts; without this a custom Data Folder breaks on next launch.
         .plugin(tauri_plugin_persisted_scope::init())
         .plugin(tauri_plugin_upload::init())
-        .manage(GzdoomLog(Mutex::new(None)))
+        .manage(session::Sessions::new())
         .setup(|app| {
             let app_data_dir = app.path().app_data_dir()?;
             std::fs::create_dir_all(&app_data_dir)
                 .map_err(|e| format!("Failed to create app data dir: {e}"))?;
+            session::cleanup_temp_logs();
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             launch_gzdoom,
+            launch_gzdoom_with_config,
+            build_command_line,
+            validate_profile,
+            quick_test_map,
             get_gzdoom_log,
+            get_gzdoom_log_partial,
+            get_crash_report,
+            clear_gzdoom_log,
+            set_max_log_lines,
+            session_status,
+            get_timedemo_result,
+            analyze_gzdoom_log,
+            get_session_info,
+            save_gzdoom_log,
+            open_log_in_editor,
+            kill_gzdoom,
             get_engine_version,
+            gzdoom_version,
+            check_zscript_version,
+            engine_supports_flag,
+            find_gzdoom_installs,
+            is_process_running,
+            reveal_in_file_manager,
             read_launcher_downloads,
             write_launcher_downloads,
             import_custom_wad,
             read_file_range,
             read_sibling_text,
+            scan_mod_bundles,
             read_custom_wads,
             write_custom_wads,
             validate_game_file,
             extract_game_files,
+            extract_archive,
+            inspect_archive,
+            backup_gzdoom_data,
+            plan_gzdoom_restore,
+            restore_gzdoom_data,
             extract_zip_entry_to_temp,
             list_zip_entries,
             read_zip_entry,
             make_temp_dir,
             cleanup_temp_dir,
-            collect_known_wads
+            collect_known_wads,
+            wad_checksum,
+            detect_wad_type,
+            extract_levels,
+            extract_episodes,
+            extract_gameinfo,
+            extract_cluster_texts,
+            extract_level_names,
+            get_cached_level_names,
+            merge_level_names,
+            level_name_collisions,
+            list_maps,
+            estimate_difficulty,
+            list_levels,
+            scan_iwads,
+            iwad_search_dirs,
+            scan_iwad_dirs,
+            import_iwad,
+            find_duplicate_wads,
+            detect_required_iwad,
+            detect_port_features,
+            check_vanilla_limits,
+            detect_complevel,
+            check_load_order,
+            list_sound_definitions,
+            list_music_changes,
+            list_actor_classes,
+            download_mod,
+            identify_idgames,
+            extract_titlepic,
+            extract_status_bar,
+            extract_sprites,
+            list_wad_lumps,
+            read_wad_lump,
+            extract_description,
+            get_palette,
+            save_profile,
+            list_profiles,
+            load_profile,
+            prepare_profile_config,
+            prepare_profile_savedir,
+            add_recent,
+            get_recent,
+            clear_recent,
+            get_play_stats
         ]);
 
     // MCP bridge for Claude Code debugging (dev mode only)
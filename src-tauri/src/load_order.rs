@@ -0,0 +1,152 @@
+// Load-order check: which lumps a later file replaces in an earlier one.
+// When two mods "don't work together" it is usually because the second
+// silently replaces the first one's maps, sprites or status bar. Lumps
+// that engines merge across files (MAPINFO, DECORATE, …) are not
+// overrides and are skipped, as are per-map data lumps, which are
+// reported once under the map's marker.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::game_archives;
+use crate::wad_parser::{self, WadType, MAP_DATA_LUMPS};
+
+/// Lumps ZDoom-family engines (and Boom, for the DEHACKED lumps) combine
+/// from every file instead of taking the last one.
+const MERGED_LUMPS: &[&str] = &[
+    "MAPINFO", "ZMAPINFO", "EMAPINFO", "UMAPINFO", "DECORATE", "ZSCRIPT", "SNDINFO", "GLDEFS",
+    "KEYCONF", "LANGUAGE", "DEHACKED", "ANIMDEFS", "TEXTURES", "LOCKDEFS", "MODELDEF", "LOADACS",
+];
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LumpOverride {
+    pub lump: String,
+    /// The file whose copy is replaced.
+    pub defined_in: String,
+    pub overridden_by: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadOrderReport {
+    pub overrides: Vec<LumpOverride>,
+    /// Human-readable summaries: duplicate files, and one line per pair of
+    /// files with overrides.
+    pub warnings: Vec<String>,
+}
+
+fn basename(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// The lumps a file contributes, without namespace markers (S_START, …) or
+/// map data. PK3 entries count by their lump name, maps/*.wad as the map.
+fn overridable_lumps(path: &str) -> Result<Vec<String>, String> {
+    let names: Vec<String> = match wad_parser::detect_wad_type(path)? {
+        WadType::Iwad | WadType::Pwad => wad_parser::read_lumps(path)?.into_iter().map(|l| l.name).collect(),
+        WadType::Pk3 | WadType::Ipk3 | WadType::Zip => game_archives::list_zip_entries(path)?
+            .into_iter()
+            .map(|e| wad_parser::zip_entry_lump_name(&e.path))
+            .collect(),
+        WadType::Unknown => return Err(format!("Not a WAD or ZIP/PK3 file: {}", path)),
+    };
+    let mut seen = HashSet::new();
+    Ok(names
+        .into_iter()
+        .filter(|n| !n.is_empty() && !n.ends_with("_START") && !n.ends_with("_END"))
+        .filter(|n| !MAP_DATA_LUMPS.contains(&n.as_str()) && !MERGED_LUMPS.contains(&n.as_str()))
+        .filter(|n| seen.insert(n.clone()))
+        .collect())
+}
+
+/// Up to this many lump names are listed in a warning.
+const WARNING_LUMPS: usize = 5;
+
+/// Report the lumps each file replaces in files loaded before it. `files`
+/// is the full load order, IWAD first when it should be included.
+pub fn check_load_order(files: &[String]) -> Result<LoadOrderReport, String> {
+    let mut report = LoadOrderReport::default();
+    let mut seen_files = HashSet::new();
+    // Lump -> index into `files` of the copy currently in effect.
+    let mut owner: HashMap<String, usize> = HashMap::new();
+    for (index, file) in files.iter().enumerate() {
+        if !seen_files.insert(file.as_str()) {
+            report.warnings.push(format!("{} is loaded more than once", basename(file)));
+            continue;
+        }
+        // (earlier file index) -> lumps this file replaces in it.
+        let mut replaced: Vec<(usize, Vec<String>)> = Vec::new();
+        for lump in overridable_lumps(file)? {
+            if let Some(previous) = owner.insert(lump.clone(), index) {
+                report.overrides.push(LumpOverride {
+                    lump: lump.clone(),
+                    defined_in: files[previous].clone(),
+                    overridden_by: file.clone(),
+                });
+                match replaced.iter_mut().find(|(i, _)| *i == previous) {
+                    Some((_, lumps)) => lumps.push(lump),
+                    None => replaced.push((previous, vec![lump])),
+                }
+            }
+        }
+        for (previous, lumps) in replaced {
+            let mut listed = lumps.iter().take(WARNING_LUMPS).cloned().collect::<Vec<_>>().join(", ");
+            if lumps.len() > WARNING_LUMPS {
+                listed.push_str(&format!(" and {} more", lumps.len() - WARNING_LUMPS));
+            }
+            report.warnings.push(format!(
+                "{} defines {}, {} overrides {}",
+                basename(&files[previous]),
+                listed,
+                basename(file),
+                if lumps.len() == 1 { "it" } else { "them" }
+            ));
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, build_zip, write_temp};
+
+    #[test]
+    fn reports_overrides_in_load_order() {
+        let iwad = write_temp(
+            "lo_doom2.wad",
+            &build_wad(b"IWAD", &[("PLAYPAL", b""), ("MAP01", b""), ("THINGS", b""), ("MAP02", b""), ("THINGS", b"")]),
+        );
+        let first = write_temp(
+            "lo_first.wad",
+            &build_wad(b"PWAD", &[("MAPINFO", b""), ("MAP01", b""), ("THINGS", b""), ("S_START", b""), ("TROOA1", b""), ("S_END", b"")]),
+        );
+        let map = build_wad(b"PWAD", &[("MAP01", b""), ("THINGS", b"")]);
+        let second = write_temp("lo_second.pk3", &build_zip(&[("maps/map01.wad", &map), ("sprites/trooa1.png", b""), ("mapinfo.txt", b"")]));
+
+        let files = vec![iwad.clone(), first.clone(), second.clone(), first.clone()];
+        let report = check_load_order(&files).unwrap();
+        let overrides: Vec<(&str, &str, &str)> = report
+            .overrides
+            .iter()
+            .map(|o| (o.lump.as_str(), basename(&o.defined_in), basename(&o.overridden_by)))
+            .collect();
+        assert_eq!(
+            overrides,
+            vec![
+                ("MAP01", "lo_doom2.wad", "lo_first.wad"),
+                ("MAP01", "lo_first.wad", "lo_second.pk3"),
+                ("TROOA1", "lo_first.wad", "lo_second.pk3"),
+            ]
+        );
+        assert_eq!(
+            report.warnings,
+            vec![
+                "lo_doom2.wad defines MAP01, lo_first.wad overrides it",
+                "lo_first.wad defines MAP01, TROOA1, lo_second.pk3 overrides them",
+                "lo_first.wad is loaded more than once",
+            ]
+        );
+    }
+}
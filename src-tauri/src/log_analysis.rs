@@ -0,0 +1,193 @@
+// Diagnostics from a captured engine log. GZDoom's console mixes startup
+// chatter with the few lines that explain a failed load — "Script error,",
+// "Execution could not continue.", "Warning:" — so this pulls those out,
+// with the map being played and the lump named when the engine says so.
+//
+//     Script error, "mymod.pk3:zscript/monsters.zs" line 41:
+//     Unexpected identifier
+//
+// A message ending in ':' continues on the next line, which is folded in.
+
+use serde::Serialize;
+
+use crate::session::Stream;
+use crate::wad_parser::{is_exmy_marker, is_mapxx_marker};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub time_ms: u64,
+    /// Where the line was printed; GZDoom sends some errors only to stderr.
+    pub stream: Stream,
+    pub severity: Severity,
+    pub message: String,
+    /// The map named in the message, else the map being played at the time.
+    pub map: Option<String>,
+    /// Lump (or PK3 entry) the message points at, e.g. "zscript/monsters.zs".
+    pub lump: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogDiagnostics {
+    pub errors: Vec<Diagnostic>,
+    pub warnings: Vec<Diagnostic>,
+    /// Lines that are neither: startup output, level banners, chat.
+    pub info_lines: usize,
+}
+
+const ERROR_PREFIXES: &[&str] = &[
+    "script error",
+    "execution could not continue",
+    "fatal error",
+    "error:",
+    "vm execution aborted",
+    "unknown class name",
+    "could not find script",
+];
+
+const WARNING_PREFIXES: &[&str] = &["warning:", "script warning", "warning,"];
+
+/// Lumps worth naming when a message mentions them unquoted.
+const KNOWN_LUMPS: &[&str] = &[
+    "MAPINFO", "ZMAPINFO", "UMAPINFO", "EMAPINFO", "DECORATE", "ZSCRIPT", "SNDINFO", "LANGUAGE",
+    "ANIMDEFS", "GLDEFS", "KEYCONF", "DEHACKED", "TEXTURES", "SBARINFO", "TERRAIN", "LOCKDEFS",
+];
+
+pub fn classify(line: &str) -> Severity {
+    let lower = line.trim_start().to_lowercase();
+    if ERROR_PREFIXES.iter().any(|p| lower.starts_with(p)) {
+        Severity::Error
+    } else if WARNING_PREFIXES.iter().any(|p| lower.starts_with(p)) {
+        Severity::Warning
+    } else {
+        Severity::Info
+    }
+}
+
+fn words(line: &str) -> impl Iterator<Item = String> + '_ {
+    line.split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+        .map(str::to_uppercase)
+}
+
+/// A map id mentioned in `line` (MAP07, E2M4).
+fn mentioned_map(line: &str) -> Option<String> {
+    words(line).find(|w| is_mapxx_marker(w) || is_exmy_marker(w))
+}
+
+/// The map a level banner announces: "MAP01 - Entryway".
+pub(crate) fn banner_map(line: &str) -> Option<String> {
+    let (id, _) = line.trim().split_once(" - ")?;
+    let id = id.trim().to_uppercase();
+    (is_mapxx_marker(&id) || is_exmy_marker(&id)).then_some(id)
+}
+
+/// The lump a message points at: the entry of a quoted "archive:lump"
+/// location, else a well-known lump name.
+fn mentioned_lump(line: &str) -> Option<String> {
+    let mut quoted = line.split('"').skip(1).step_by(2);
+    if let Some(location) = quoted.find(|q| q.contains(':')) {
+        let lump = location.rsplit(':').next().unwrap_or(location);
+        if !lump.is_empty() {
+            return Some(lump.to_string());
+        }
+    }
+    words(line).find(|w| KNOWN_LUMPS.contains(&w.as_str()))
+}
+
+/// Classify every line of a log into errors, warnings and informational
+/// output.
+pub fn analyze<'a>(lines: impl IntoIterator<Item = &'a (u64, String, Stream)>) -> LogDiagnostics {
+    let lines: Vec<&(u64, String, Stream)> = lines.into_iter().collect();
+    let mut out = LogDiagnostics::default();
+    let mut current_map: Option<String> = None;
+    let mut i = 0;
+    while i < lines.len() {
+        let (time_ms, line, stream) = lines[i];
+        i += 1;
+        let severity = classify(line);
+        if severity == Severity::Info {
+            if let Some(map) = banner_map(line) {
+                current_map = Some(map);
+            }
+            out.info_lines += 1;
+            continue;
+        }
+        let mut message = line.trim().to_string();
+        if message.ends_with(':')
+            && let Some((_, next, _)) = lines.get(i)
+            && classify(next) == Severity::Info
+            && !next.trim().is_empty()
+        {
+            message.push(' ');
+            message.push_str(next.trim());
+            i += 1;
+        }
+        let diagnostic = Diagnostic {
+            time_ms: *time_ms,
+            stream: *stream,
+            severity,
+            map: mentioned_map(&message).or_else(|| current_map.clone()),
+            lump: mentioned_lump(&message),
+            message,
+        };
+        match severity {
+            Severity::Error => out.errors.push(diagnostic),
+            _ => out.warnings.push(diagnostic),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(lines: &[&str]) -> Vec<(u64, String, Stream)> {
+        lines.iter().enumerate().map(|(i, l)| (i as u64 * 10, l.to_string(), Stream::Stdout)).collect()
+    }
+
+    #[test]
+    fn finds_errors_with_lump_and_detail() {
+        let result = analyze(&log(&[
+            "GZDoom g4.14.2 - 2025-05-01",
+            "Script error, \"mymod.pk3:zscript/monsters.zs\" line 41:",
+            "Unexpected identifier",
+            "Execution could not continue.",
+        ]));
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.info_lines, 1);
+        let first = &result.errors[0];
+        assert_eq!(first.time_ms, 10);
+        assert_eq!(first.message, "Script error, \"mymod.pk3:zscript/monsters.zs\" line 41: Unexpected identifier");
+        assert_eq!(first.lump.as_deref(), Some("zscript/monsters.zs"));
+        assert_eq!(first.map, None);
+
+        let mut lines = log(&["Execution could not continue."]);
+        lines[0].2 = Stream::Stderr;
+        assert_eq!(analyze(&lines).errors[0].stream, Stream::Stderr);
+    }
+
+    #[test]
+    fn tracks_current_map_for_warnings() {
+        let result = analyze(&log(&[
+            "MAP03 - The Gantlet",
+            "Warning: unknown texture SKY9 on sidedef 12",
+            "WARNING: Unknown sound 'monster/idle' in SNDINFO",
+            "Warning: MAP05 has no player starts",
+        ]));
+        let maps: Vec<Option<&str>> = result.warnings.iter().map(|w| w.map.as_deref()).collect();
+        assert_eq!(maps, vec![Some("MAP03"), Some("MAP03"), Some("MAP05")]);
+        assert_eq!(result.warnings[1].lump.as_deref(), Some("SNDINFO"));
+        assert!(result.errors.is_empty());
+    }
+}
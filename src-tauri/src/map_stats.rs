@@ -0,0 +1,265 @@
+// Per-map totals for the level browser: monsters and items the map counts
+// toward the intermission percentages, and secret sectors, at one skill.
+// Thing types are Doom's editor numbers (doomednums); in other games the
+// monster and item totals are not meaningful.
+//
+// THINGS is 10 bytes per thing in Doom format (x, y, angle, type, flags),
+// 20 in Hexen format (tid, x, y, z, angle, type, flags, special, args);
+// SECTORS is 26 bytes per sector with the special at offset 22. UDMF keeps
+// the same data as `thing { … }` / `sector { … }` blocks in TEXTMAP.
+
+use serde::Serialize;
+
+use crate::wad_parser::MapFormat;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MapStats {
+    pub monsters: u32,
+    pub items: u32,
+    pub secrets: u32,
+}
+
+/// Monsters that count toward kills (MF_COUNTKILL). The Icon of Sin's
+/// brain (88) doesn't, nor do lost souls (3006).
+const MONSTERS: &[u16] = &[
+    3004, 9, 65, 3001, 3002, 58, 3005, 69, 3003, 68, 71, 66, 67, 64, 16, 7, 84, 72,
+];
+
+/// Pickups that count toward items (MF_COUNTITEM): health and armor
+/// bonuses, spheres and powerups. Weapons, ammo and keys don't.
+const ITEMS: &[u16] = &[2014, 2015, 2013, 83, 2022, 2023, 2024, 2026, 2045];
+
+/// Thing flag for `skill` (1-5): easy covers 1-2, medium 3, hard 4-5.
+fn skill_bit(skill: u8) -> u16 {
+    match skill {
+        0..=2 => 1,
+        3 => 2,
+        _ => 4,
+    }
+}
+
+const DOOM_NOT_SINGLE: u16 = 0x10;
+const HEXEN_SINGLE: u16 = 0x100;
+const SECRET_SPECIAL: i16 = 9;
+/// Boom's generalized sector secret bit.
+const BOOM_SECRET: i16 = 0x80;
+/// ZDoom's secret bit in Hexen-format sector specials.
+const ZDOOM_SECRET: i16 = 0x400;
+
+fn u16_at(data: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([data[at], data[at + 1]])
+}
+
+/// A thing placed for single player at the skill asked for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PlacedThing {
+    pub kind: u16,
+    pub x: f64,
+    pub y: f64,
+}
+
+fn count_thing(stats: &mut MapStats, kind: u16) {
+    if MONSTERS.contains(&kind) {
+        stats.monsters += 1;
+    } else if ITEMS.contains(&kind) {
+        stats.items += 1;
+    }
+}
+
+/// The single-player things at `skill` in a binary THINGS lump. `format`
+/// must be Doom or Hexen; a trailing partial record is ignored.
+pub(crate) fn binary_things(things: &[u8], format: MapFormat, skill: u8) -> Vec<PlacedThing> {
+    let (record, x_at, type_at, flags_at) = match format {
+        MapFormat::Hexen => (20, 2, 14, 16),
+        _ => (10, 0, 6, 8),
+    };
+    things
+        .chunks_exact(record)
+        .filter(|thing| {
+            let flags = u16_at(thing, flags_at);
+            let single = match format {
+                MapFormat::Hexen => flags & HEXEN_SINGLE != 0,
+                _ => flags & DOOM_NOT_SINGLE == 0,
+            };
+            single && flags & skill_bit(skill) != 0
+        })
+        .map(|thing| PlacedThing {
+            kind: u16_at(thing, type_at),
+            x: u16_at(thing, x_at) as i16 as f64,
+            y: u16_at(thing, x_at + 2) as i16 as f64,
+        })
+        .collect()
+}
+
+/// Totals from binary THINGS and SECTORS lumps. `format` must be Doom or
+/// Hexen; trailing partial records are ignored.
+pub fn binary_stats(things: &[u8], sectors: &[u8], format: MapFormat, skill: u8) -> MapStats {
+    let mut stats = MapStats::default();
+    for thing in binary_things(things, format, skill) {
+        count_thing(&mut stats, thing.kind);
+    }
+    let secret_bit = if format == MapFormat::Hexen { ZDOOM_SECRET } else { BOOM_SECRET };
+    stats.secrets = sectors
+        .chunks_exact(26)
+        .map(|sector| u16_at(sector, 22) as i16)
+        .filter(|special| *special == SECRET_SPECIAL || special & secret_bit != 0)
+        .count() as u32;
+    stats
+}
+
+/// Strip // and /* */ comments, leaving string literals alone.
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                out.extend(chars.next());
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+                out.push(' ');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// The `key = value;` fields of each `kind { … }` block, keys lowercased.
+fn udmf_blocks<'a>(text: &'a str, kind: &'a str) -> impl Iterator<Item = Vec<(String, String)>> + 'a {
+    text.split('}').filter_map(move |chunk| {
+        let (head, body) = chunk.split_once('{')?;
+        // The block name is the last word before '{', after the previous
+        // top-level assignment (namespace = "zdoom";).
+        let name = head.rsplit(';').next()?.trim();
+        name.eq_ignore_ascii_case(kind).then(|| {
+            body.split(';')
+                .filter_map(|field| {
+                    let (key, value) = field.split_once('=')?;
+                    Some((key.trim().to_lowercase(), value.trim().trim_matches('"').to_string()))
+                })
+                .collect()
+        })
+    })
+}
+
+fn is_true(fields: &[(String, String)], key: &str) -> bool {
+    fields.iter().any(|(k, v)| k == key && v.eq_ignore_ascii_case("true"))
+}
+
+/// The things of a comment-stripped TEXTMAP with `single = true` and the
+/// skill's `skillN = true`, as the engine reads them.
+fn udmf_placed(text: &str, skill: u8) -> Vec<PlacedThing> {
+    let skill_key = format!("skill{}", skill.max(1));
+    let number = |fields: &[(String, String)], key: &str| -> Option<f64> {
+        fields.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.parse().ok())
+    };
+    udmf_blocks(text, "thing")
+        .filter(|thing| is_true(thing, "single") && is_true(thing, &skill_key))
+        .filter_map(|thing| {
+            let kind = thing.iter().find(|(k, _)| k == "type").and_then(|(_, v)| v.parse().ok())?;
+            Some(PlacedThing { kind, x: number(&thing, "x").unwrap_or(0.0), y: number(&thing, "y").unwrap_or(0.0) })
+        })
+        .collect()
+}
+
+/// The single-player things at `skill` in a UDMF TEXTMAP.
+pub(crate) fn udmf_things(textmap: &str, skill: u8) -> Vec<PlacedThing> {
+    udmf_placed(&strip_comments(textmap), skill)
+}
+
+/// Totals from a UDMF TEXTMAP. Things need `single = true` and the
+/// skill's `skillN = true`, as the engine reads them.
+pub fn udmf_stats(textmap: &str, skill: u8) -> MapStats {
+    let text = strip_comments(textmap);
+    let mut stats = MapStats::default();
+    for thing in udmf_placed(&text, skill) {
+        count_thing(&mut stats, thing.kind);
+    }
+    stats.secrets = udmf_blocks(&text, "sector")
+        .filter(|sector| {
+            let special: i32 = sector.iter().find(|(k, _)| k == "special").and_then(|(_, v)| v.parse().ok()).unwrap_or(0);
+            is_true(sector, "secret") || special == SECRET_SPECIAL as i32 || special & ZDOOM_SECRET as i32 != 0
+        })
+        .count() as u32;
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doom_thing(kind: u16, flags: u16) -> Vec<u8> {
+        [0u16, 0, 90, kind, flags].iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    fn sector(special: i16) -> Vec<u8> {
+        let mut s = vec![0u8; 26];
+        s[22..24].copy_from_slice(&special.to_le_bytes());
+        s
+    }
+
+    #[test]
+    fn counts_binary_things_by_skill() {
+        let things: Vec<u8> = [
+            doom_thing(3004, 0b111),  // zombieman, all skills
+            doom_thing(3001, 0b100),  // imp, UV only
+            doom_thing(3003, 0b10111), // baron, multiplayer only
+            doom_thing(2014, 0b111),  // health bonus
+            doom_thing(2001, 0b111),  // shotgun: not an item
+            doom_thing(88, 0b111),    // boss brain: not counted
+            doom_thing(3006, 0b111),  // lost soul: not counted
+        ]
+        .concat();
+        let sectors = [sector(0), sector(9), sector(0x80 | 3), sector(17)].concat();
+        assert_eq!(
+            binary_stats(&things, &sectors, MapFormat::Doom, 4),
+            MapStats { monsters: 2, items: 1, secrets: 2 }
+        );
+        assert_eq!(binary_stats(&things, &sectors, MapFormat::Doom, 2).monsters, 1);
+    }
+
+    #[test]
+    fn counts_udmf_things() {
+        let textmap = r#"
+            namespace = "zdoom";
+            // comment { with braces }
+            thing { x = 0.0; y = 0.0; type = 3004; skill1 = true; skill4 = true; single = true; }
+            thing /* block */ { type = 3001; skill1 = true; single = true; }
+            thing { type = 2015; skill4 = true; coop = true; }
+            sector { texturefloor = "FLAT1"; special = 9; }
+            sector { secret = true; }
+            sector { special = 0; }
+        "#;
+        assert_eq!(udmf_stats(textmap, 4), MapStats { monsters: 1, items: 0, secrets: 2 });
+        assert_eq!(udmf_stats(textmap, 1).monsters, 2);
+    }
+}
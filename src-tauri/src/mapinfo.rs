@@ -0,0 +1,1027 @@
+// MAPINFO-family text parsing: MAPINFO (old Hexen/ZDoom style and new
+// brace style), ZMAPINFO, UMAPINFO and EMAPINFO.
+//
+// The brace dialects and old-style MAPINFO share one block parser:
+//
+//     map MAP01 "Entryway" { par = 30  next = "MAP02" }   // ZMAPINFO
+//     MAP MAP01 { levelname = "Entryway" }                  // UMAPINFO
+//     map 1 "Winnowing Hall"                                // Hexen,
+//     cluster 1                                             // one property
+//     next 2                                                // per line
+//
+// A block is a keyword (`map`, `cluster`, `episode`, …), header arguments
+// on the same line, then properties — inside braces, or on the following
+// lines for the old style. A property is a key followed by an optional `=`
+// and the rest of the line; a trailing comma continues it onto the next
+// line (UMAPINFO intertext). EMAPINFO is ini-style and parsed separately.
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub(crate) text: String,
+    pub(crate) quoted: bool,
+    pub(crate) line: usize,
+}
+
+impl Token {
+    pub(crate) fn is(&self, punct: &str) -> bool {
+        !self.quoted && self.text == punct
+    }
+}
+
+/// Split into quoted strings, punctuation ({ } = ,) and bare words. Skips
+/// `//`, `/* */` and Hexen-style `;` comments. SNDINFO shares the syntax.
+pub(crate) fn tokenize(src: &str) -> Vec<Token> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == ';' || (c == '/' && chars.get(i + 1) == Some(&'/')) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' {
+            let start_line = line;
+            let mut text = String::new();
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                    text.push(match chars[i] {
+                        'n' => '\n',
+                        other => other,
+                    });
+                } else {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    text.push(chars[i]);
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token { text, quoted: true, line: start_line });
+        } else if "{}=,".contains(c) {
+            tokens.push(Token { text: c.to_string(), quoted: false, line });
+            i += 1;
+        } else {
+            let ends_word = |j: usize| {
+                chars[j].is_whitespace()
+                    || "{}=,\";".contains(chars[j])
+                    || (chars[j] == '/' && matches!(chars.get(j + 1), Some('/') | Some('*')))
+            };
+            let start = i;
+            while i < chars.len() && !ends_word(i) {
+                i += 1;
+            }
+            tokens.push(Token { text: chars[start..i].iter().collect(), quoted: false, line });
+        }
+    }
+    tokens
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    /// Lowercased key.
+    pub key: String,
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    /// Lowercased keyword: "map", "cluster", "episode", "gameinfo", …
+    pub kind: String,
+    /// Header arguments, e.g. ["MAP01", "Entryway"] or ["MAP01", "lookup", "HUSTR_1"].
+    pub args: Vec<String>,
+    pub props: Vec<Property>,
+}
+
+impl Block {
+    /// First value of the last occurrence of `key` (later definitions win).
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.props
+            .iter()
+            .rev()
+            .find(|p| p.key == key)
+            .and_then(|p| p.values.first())
+            .map(String::as_str)
+    }
+
+    /// All values of the last occurrence of `key`.
+    pub fn values(&self, key: &str) -> Option<&[String]> {
+        self.props.iter().rev().find(|p| p.key == key).map(|p| p.values.as_slice())
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        self.props.iter().any(|p| p.key == key)
+    }
+}
+
+/// Keywords that start a new top-level block. Anything else at top level
+/// is a property of the preceding old-style (brace-less) block.
+const TOP_LEVEL: &[&str] = &[
+    "map",
+    "defaultmap",
+    "adddefaultmap",
+    "gamedefaults",
+    "cluster",
+    "clusterdef",
+    "episode",
+    "clearepisodes",
+    "gameinfo",
+    "intermission",
+    "skill",
+    "clearskills",
+    "include",
+    "doomednums",
+    "spawnnums",
+    "conversationids",
+    "damagetype",
+    "automap",
+    "automap_overlay",
+    // IWADINFO's only block.
+    "iwad",
+];
+
+/// Read one property starting at tokens[*i]: key, optional '=', then the
+/// rest of the line (honouring trailing-comma continuation), stopping at
+/// a brace or the next `key =`.
+fn read_property(tokens: &[Token], i: &mut usize) -> Property {
+    let key = tokens[*i].text.to_lowercase();
+    let mut line = tokens[*i].line;
+    *i += 1;
+    let mut values = Vec::new();
+    let mut continued = false;
+    while *i < tokens.len() {
+        let t = &tokens[*i];
+        if t.is("{") || t.is("}") || (t.line != line && !continued) {
+            break;
+        }
+        // `key = v key2 = v2` on one line: a bare word followed by '='
+        // starts the next property.
+        if !values.is_empty() && !continued && !t.quoted && tokens.get(*i + 1).is_some_and(|n| n.is("=")) {
+            break;
+        }
+        line = t.line;
+        continued = t.is(",");
+        if !t.is("=") && !t.is(",") {
+            values.push(t.text.clone());
+        }
+        *i += 1;
+    }
+    Property { key, values }
+}
+
+/// Whether the header starting at tokens[i] is followed by a `{` (on its
+/// own line or the next). Old-style Hexen maps carry a `cluster N`
+/// property, while ZMAPINFO's `cluster N { }` opens a block.
+fn opens_brace(tokens: &[Token], i: usize) -> bool {
+    let line = tokens[i].line;
+    tokens[i..]
+        .iter()
+        .find(|t| t.line != line || t.is("{"))
+        .is_some_and(|t| t.is("{"))
+}
+
+/// Parse MAPINFO/ZMAPINFO/UMAPINFO source into blocks. Unbalanced braces
+/// and unknown keys are tolerated: this feeds previews, not the engine.
+pub fn parse_blocks(src: &str) -> Vec<Block> {
+    let tokens = tokenize(src);
+    let mut blocks: Vec<Block> = Vec::new();
+    // Whether the last block is old-style and still collecting lines.
+    let mut open_old_style = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let t = &tokens[i];
+        let keyword = t.text.to_lowercase();
+        if t.quoted || t.is("{") || t.is("}") || t.is("=") || t.is(",") {
+            // Stray punctuation or a string where a key belongs.
+            i += 1;
+            continue;
+        }
+        if !TOP_LEVEL.contains(&keyword.as_str())
+            || (open_old_style && keyword == "cluster" && !opens_brace(&tokens, i))
+        {
+            if open_old_style && let Some(block) = blocks.last_mut() {
+                let prop = read_property(&tokens, &mut i);
+                block.props.push(prop);
+            } else {
+                // Unknown top-level statement: skip the line.
+                let line = t.line;
+                while i < tokens.len() && tokens[i].line == line {
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        let line = t.line;
+        i += 1;
+        let mut args = Vec::new();
+        while i < tokens.len() && tokens[i].line == line && !tokens[i].is("{") {
+            if !tokens[i].is(",") && !tokens[i].is("=") {
+                args.push(tokens[i].text.clone());
+            }
+            i += 1;
+        }
+        // A brace may also open on the next line.
+        let mut props = Vec::new();
+        if i < tokens.len() && tokens[i].is("{") {
+            i += 1;
+            let mut depth = 1;
+            while i < tokens.len() {
+                let t = &tokens[i];
+                if t.is("}") {
+                    depth -= 1;
+                    i += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                } else if t.is("{") {
+                    // Nested block (e.g. gameinfo sub-sections): flatten.
+                    depth += 1;
+                    i += 1;
+                } else if t.quoted || t.is("=") || t.is(",") {
+                    i += 1;
+                } else {
+                    props.push(read_property(&tokens, &mut i));
+                }
+            }
+            open_old_style = false;
+        } else {
+            open_old_style = true;
+        }
+        blocks.push(Block { kind: keyword, args, props });
+    }
+    blocks
+}
+
+/// Parse EMAPINFO (Eternity, ini-style):
+///
+/// ```text
+/// [MAP01]
+/// levelname = MAP01: Entryway
+/// ```
+///
+/// Returns (map id, key/value pairs) with lowercased keys.
+pub fn parse_emapinfo(src: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    for line in src.lines() {
+        let trimmed = line.trim();
+        if let Some(id) = trimmed.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+            sections.push((id.trim().to_uppercase(), Vec::new()));
+            continue;
+        }
+        if let Some((key, value)) = trimmed.split_once('=')
+            && let Some((_, entries)) = sections.last_mut()
+        {
+            entries.push((key.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    sections
+}
+
+/// Level name of a `map` block: the header name (or "$KEY" for a
+/// `lookup` into LANGUAGE), else UMAPINFO's `levelname` property.
+fn map_block_name(block: &Block) -> Option<String> {
+    match block.args.get(1) {
+        Some(arg) if arg.eq_ignore_ascii_case("lookup") => {
+            block.args.get(2).map(|key| format!("${}", key))
+        }
+        Some(arg) => Some(arg.clone()),
+        None => block.get("levelname").map(str::to_string),
+    }
+}
+
+/// Where a level's exit leads.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LevelExit {
+    /// Another map, by id as it appears in LevelInfo::id.
+    Map { id: String },
+    /// The game ends here. `ending` is the sentinel as written — "EndGame1",
+    /// "EndPic CREDIT", "EndBunny", … — for the UI to label.
+    End { ending: String },
+}
+
+/// Per-map data gathered from MAPINFO-family lumps.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelInfo {
+    /// Uppercase map id ("MAP01", "E1M1"; Hexen's numeric "1" stays as-is).
+    pub id: String,
+    pub name: Option<String>,
+    pub par_seconds: Option<u32>,
+    pub next: Option<LevelExit>,
+    /// Set only for maps that have a secret exit.
+    pub secret_next: Option<LevelExit>,
+    /// Cluster (Hexen hub) the map belongs to.
+    pub cluster: Option<u32>,
+    /// Music as written: a lump name ("D_RUNNIN"), a LANGUAGE lookup
+    /// ("$MUSIC_RUNNIN") or an external file inside the archive
+    /// ("music/e1m1.ogg"). None when the map doesn't set one and plays the
+    /// game's default.
+    pub music: Option<String>,
+    /// UMAPINFO `author`, for crediting the mapper.
+    pub author: Option<String>,
+    /// Skips the tally screen after the map.
+    pub no_intermission: bool,
+    /// Music for the tally screen after the map, as written.
+    pub inter_music: Option<String>,
+    /// Whether finishing the map ends the game (`next` is an ending).
+    pub ends_game: bool,
+}
+
+impl LevelInfo {
+    fn new(id: &str) -> Self {
+        LevelInfo { id: id.to_uppercase(), ..Default::default() }
+    }
+
+    /// Fill fields this definition leaves unset from a later one.
+    fn fill_from(&mut self, other: LevelInfo) {
+        if self.name.is_none() {
+            self.name = other.name;
+        }
+        if self.par_seconds.is_none() {
+            self.par_seconds = other.par_seconds;
+        }
+        if self.next.is_none() {
+            self.next = other.next;
+        }
+        if self.secret_next.is_none() {
+            self.secret_next = other.secret_next;
+        }
+        if self.cluster.is_none() {
+            self.cluster = other.cluster;
+        }
+        if self.music.is_none() {
+            self.music = other.music;
+        }
+        if self.author.is_none() {
+            self.author = other.author;
+        }
+        self.no_intermission |= other.no_intermission;
+        if self.inter_music.is_none() {
+            self.inter_music = other.inter_music;
+        }
+        self.ends_game = matches!(self.next, Some(LevelExit::End { .. }));
+    }
+}
+
+fn parse_number(value: Option<&str>) -> Option<u32> {
+    value.and_then(|v| v.trim().parse().ok())
+}
+
+/// Whether a `next` target is an ending rather than a map.
+fn is_end_sentinel(target: &str) -> bool {
+    let lower = target.to_lowercase();
+    lower.starts_with("endgame")
+        || matches!(
+            lower.as_str(),
+            "endpic" | "endbunny" | "endcast" | "enddemon" | "endchess" | "endunderwater"
+                | "endbuystrife" | "endtitle" | "endsequence"
+        )
+}
+
+/// Map ids and Hexen warp numbers of one lump's maps, for resolving
+/// numeric `next` targets.
+struct MapIndex {
+    ids: Vec<String>,
+    warptrans: Vec<(u32, String)>,
+}
+
+impl MapIndex {
+    /// A numeric target is a warptrans number in Hexen MAPINFO, else a map
+    /// number: "2" is the map declared as `map 2`, or MAP02.
+    fn resolve(&self, target: &str) -> String {
+        let Ok(number) = target.parse::<u32>() else {
+            return target.to_uppercase();
+        };
+        if let Some((_, id)) = self.warptrans.iter().find(|(n, _)| *n == number) {
+            return id.clone();
+        }
+        if self.ids.iter().any(|id| *id == target) {
+            return target.to_string();
+        }
+        format!("MAP{:02}", number)
+    }
+}
+
+fn exit_from_values(values: &[String], index: &MapIndex) -> Option<LevelExit> {
+    let target = values.first()?;
+    if is_end_sentinel(target) {
+        Some(LevelExit::End { ending: values.join(" ") })
+    } else {
+        Some(LevelExit::Map { id: index.resolve(target) })
+    }
+}
+
+/// A flag property: bare in ZMAPINFO (`nointermission`), `= true` in
+/// UMAPINFO.
+fn flag(block: &Block, key: &str) -> bool {
+    block.values(key).is_some_and(|v| v.first().is_none_or(|v| v.eq_ignore_ascii_case("true")))
+}
+
+/// UMAPINFO ends the game with flags instead of a sentinel `next`.
+fn umapinfo_ending(block: &Block) -> Option<LevelExit> {
+    let flag = |key: &str| flag(block, key);
+    let ending = if let Some(pic) = block.get("endpic") {
+        format!("EndPic {}", pic)
+    } else if flag("endbunny") {
+        "EndBunny".to_string()
+    } else if flag("endcast") {
+        "EndCast".to_string()
+    } else if flag("endgame") {
+        "EndGame".to_string()
+    } else {
+        return None;
+    };
+    Some(LevelExit::End { ending })
+}
+
+fn level_from_block(block: &Block, index: &MapIndex) -> Option<LevelInfo> {
+    let mut level = LevelInfo::new(block.args.first()?);
+    level.name = map_block_name(block);
+    // ZMAPINFO/MAPINFO say "par", UMAPINFO "partime".
+    level.par_seconds = parse_number(block.get("par").or(block.get("partime")));
+    level.next = umapinfo_ending(block)
+        .or_else(|| exit_from_values(block.values("next")?, index));
+    // ZMAPINFO says "secretnext", UMAPINFO "nextsecret".
+    level.secret_next = block
+        .values("secretnext")
+        .or(block.values("nextsecret"))
+        .and_then(|v| exit_from_values(v, index));
+    level.cluster = parse_number(block.get("cluster"));
+    // `music = "D_RUNNIN", 2` picks a subsong; only the track matters here.
+    level.music = block.get("music").filter(|m| !m.is_empty()).map(str::to_string);
+    level.author = block.get("author").filter(|a| !a.is_empty()).map(str::to_string);
+    level.no_intermission = flag(block, "nointermission");
+    level.inter_music = block.get("intermusic").filter(|m| !m.is_empty()).map(str::to_string);
+    level.ends_game = matches!(level.next, Some(LevelExit::End { .. }));
+    Some(level)
+}
+
+fn level_from_emapinfo(id: &str, entries: &[(String, String)]) -> LevelInfo {
+    let get = |key: &str| entries.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    let mut level = LevelInfo::new(id);
+    level.name = get("levelname").map(|name| {
+        // "MAP01: Entryway" -> "Entryway"
+        let prefix = format!("{}:", level.id);
+        match name.get(..prefix.len()) {
+            Some(head) if head.eq_ignore_ascii_case(&prefix) => name[prefix.len()..].trim().to_string(),
+            _ => name.to_string(),
+        }
+    });
+    level.par_seconds = parse_number(get("partime"));
+    let exit = |key: &str| get(key).map(|v| LevelExit::Map { id: v.to_uppercase() });
+    level.next = if get("endofgame").is_some_and(|v| v.eq_ignore_ascii_case("true")) {
+        Some(LevelExit::End { ending: "EndGame".to_string() })
+    } else {
+        exit("nextlevel")
+    };
+    level.secret_next = exit("nextsecret");
+    level.music = get("music").filter(|m| !m.is_empty()).map(str::to_string);
+    level.inter_music = get("intermusic").filter(|m| !m.is_empty()).map(str::to_string);
+    level.ends_game = matches!(level.next, Some(LevelExit::End { .. }));
+    level
+}
+
+/// Levels defined by one MAPINFO-family lump, in definition order.
+/// `lump_name` picks the dialect (EMAPINFO is ini-style; everything else
+/// goes through the block parser).
+pub fn parse_levels(lump_name: &str, src: &str) -> Vec<LevelInfo> {
+    if lump_name.eq_ignore_ascii_case("EMAPINFO") {
+        return parse_emapinfo(src)
+            .iter()
+            .map(|(id, entries)| level_from_emapinfo(id, entries))
+            .collect();
+    }
+    let blocks = parse_blocks(src);
+    let maps: Vec<&Block> = blocks.iter().filter(|b| b.kind == "map" && !b.args.is_empty()).collect();
+    let index = MapIndex {
+        ids: maps.iter().map(|b| b.args[0].to_uppercase()).collect(),
+        warptrans: maps
+            .iter()
+            .filter_map(|b| Some((b.get("warptrans")?.parse().ok()?, b.args[0].to_uppercase())))
+            .collect(),
+    };
+    maps.iter().filter_map(|b| level_from_block(b, &index)).collect()
+}
+
+/// Level names defined by one lump, keyed by uppercase map id.
+pub fn parse_level_names(lump_name: &str, src: &str) -> Vec<(String, String)> {
+    parse_levels(lump_name, src)
+        .into_iter()
+        .filter_map(|l| Some((l.id, l.name?)))
+        .collect()
+}
+
+/// Merge per-lump results into `into`. The first definition of each field
+/// wins, in lump directory order — the order the parser has always used for
+/// names — so a MAPINFO name survives a later UMAPINFO that only adds a par.
+pub fn merge_levels(into: &mut Vec<LevelInfo>, levels: Vec<LevelInfo>) {
+    for level in levels {
+        match into.iter_mut().find(|l| l.id == level.id) {
+            Some(existing) => existing.fill_from(level),
+            None => into.push(level),
+        }
+    }
+}
+
+/// An entry of the new-game episode menu.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Episode {
+    /// Uppercase id of the map the episode starts on.
+    pub start_map: String,
+    /// Menu text, or "$KEY" for a LANGUAGE lookup. None when only `pic_name` is given.
+    pub name: Option<String>,
+    /// Graphic lump drawn instead of the text, e.g. "M_EPI1".
+    pub pic_name: Option<String>,
+    /// Menu hotkey.
+    pub key: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EpisodeList {
+    pub episodes: Vec<Episode>,
+    /// Whether `clearepisodes` (UMAPINFO: `episode = clear`) removed the
+    /// game's built-in episodes; otherwise `episodes` adds to them.
+    pub clears_defaults: bool,
+}
+
+impl EpisodeList {
+    fn clear(&mut self) {
+        self.episodes.clear();
+        self.clears_defaults = true;
+    }
+
+    /// Add an episode; one starting on the same map is replaced in place.
+    fn define(&mut self, episode: Episode) {
+        match self.episodes.iter_mut().find(|e| e.start_map == episode.start_map) {
+            Some(existing) => *existing = episode,
+            None => self.episodes.push(episode),
+        }
+    }
+}
+
+/// Apply one lump's episode definitions to `list`, in order: ZMAPINFO and
+/// old-style `episode` blocks, `clearepisodes`, and UMAPINFO's per-map
+/// `episode` property. EMAPINFO has no episodes.
+pub fn apply_episodes(list: &mut EpisodeList, lump_name: &str, src: &str) {
+    if lump_name.eq_ignore_ascii_case("EMAPINFO") {
+        return;
+    }
+    let text = |value: Option<&str>| value.map(str::to_string).filter(|v| !v.is_empty());
+    for block in parse_blocks(src) {
+        match block.kind.as_str() {
+            "clearepisodes" => list.clear(),
+            "episode" => {
+                let Some(start_map) = block.args.first() else { continue };
+                let start_map = start_map.to_uppercase();
+                if block.has("remove") {
+                    list.episodes.retain(|e| e.start_map != start_map);
+                    continue;
+                }
+                // Old style writes `name lookup KEY`, ZMAPINFO `lookup = "KEY"`.
+                let name = match block.values("name") {
+                    Some([lookup, key, ..]) if lookup.eq_ignore_ascii_case("lookup") => Some(format!("${}", key)),
+                    _ => text(block.get("lookup")).map(|key| format!("${}", key)).or_else(|| text(block.get("name"))),
+                };
+                list.define(Episode { start_map, name, pic_name: text(block.get("picname")), key: text(block.get("key")) });
+            }
+            "map" => match (block.args.first(), block.values("episode")) {
+                (_, Some([clear])) if clear.eq_ignore_ascii_case("clear") => list.clear(),
+                (Some(map), Some(values)) => list.define(Episode {
+                    start_map: map.to_uppercase(),
+                    pic_name: text(values.first().map(String::as_str)),
+                    name: text(values.get(1).map(String::as_str)),
+                    key: text(values.get(2).map(String::as_str)),
+                }),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Player classes the stock games define. A `playerclasses` list naming
+/// anything else replaces the player, which is what total conversions do.
+const STOCK_PLAYER_CLASSES: &[&str] = &[
+    "doomplayer", "hereticplayer", "fighterplayer", "clericplayer", "mageplayer", "strifeplayer", "chexplayer",
+];
+
+/// The `gameinfo` block of MAPINFO/ZMAPINFO, plus the `IWad` block of an
+/// IPK3's IWADINFO.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GameInfo {
+    pub player_classes: Vec<String>,
+    pub border_flat: Option<String>,
+    pub title_music: Option<String>,
+    pub title_page: Option<String>,
+    pub credit_pages: Vec<String>,
+    /// IWADINFO `Name`, e.g. "Freedoom: Phase 2".
+    pub iwad_name: Option<String>,
+    /// Base game: gameinfo `game`, else IWADINFO `Game` ("Doom", "Heretic", …).
+    pub game: Option<String>,
+    /// A standalone game or one that replaces the player classes: not
+    /// something to stack with other mods.
+    pub total_conversion: bool,
+}
+
+/// Apply one lump's `gameinfo` and `IWad` blocks to `info`; later values
+/// replace earlier ones. Returns whether the lump had either block.
+pub fn apply_gameinfo(info: &mut GameInfo, src: &str) -> bool {
+    let text = |value: Option<&str>| value.map(str::to_string).filter(|v| !v.is_empty());
+    let mut found = false;
+    for block in parse_blocks(src) {
+        match block.kind.as_str() {
+            "gameinfo" => {
+                found = true;
+                if let Some(classes) = block.values("playerclasses") {
+                    info.player_classes = classes.to_vec();
+                }
+                if let Some(pages) = block.values("creditpage") {
+                    info.credit_pages = pages.to_vec();
+                }
+                info.border_flat = text(block.get("borderflat")).or(info.border_flat.take());
+                info.title_music = text(block.get("titlemusic")).or(info.title_music.take());
+                info.title_page = text(block.get("titlepage")).or(info.title_page.take());
+                info.game = text(block.get("game")).or(info.game.take());
+            }
+            "iwad" => {
+                found = true;
+                info.iwad_name = text(block.get("name")).or(info.iwad_name.take());
+                info.game = text(block.get("game")).or(info.game.take());
+            }
+            _ => {}
+        }
+    }
+    info.total_conversion = info.iwad_name.is_some()
+        || info
+            .player_classes
+            .iter()
+            .any(|class| !STOCK_PLAYER_CLASSES.contains(&class.to_lowercase().as_str()));
+    found
+}
+
+/// Story text as a cluster writes it, before LANGUAGE and lumps are read.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoryText {
+    /// The text itself; it may still be a "$KEY" reference.
+    Literal(String),
+    /// `exittext lookup KEY`.
+    Lookup(String),
+    /// `exittextislump`: the text is the named lump's contents.
+    Lump(String),
+}
+
+/// A MAPINFO cluster: the story screens shown entering and leaving it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterDef {
+    pub id: u32,
+    pub enter_text: Option<StoryText>,
+    pub exit_text: Option<StoryText>,
+    /// Flat (or, with ZMAPINFO's `pic`, graphic) behind the text.
+    pub backdrop: Option<String>,
+    pub music: Option<String>,
+    /// Hexen hub: maps keep their state while the player is in the cluster.
+    pub hub: bool,
+}
+
+fn story_text(block: &Block, key: &str) -> Option<StoryText> {
+    let values = block.values(key)?;
+    Some(match values {
+        [lookup, text_key, ..] if lookup.eq_ignore_ascii_case("lookup") => StoryText::Lookup(text_key.clone()),
+        [] => return None,
+        // ZMAPINFO continues long texts over several comma-separated strings.
+        _ if block.has(&format!("{}islump", key)) => StoryText::Lump(values[0].to_uppercase()),
+        _ => StoryText::Literal(values.join("\n")),
+    })
+}
+
+/// The clusters one MAPINFO/ZMAPINFO lump defines, in order: ZMAPINFO
+/// `cluster N { }` and old-style `clusterdef N` blocks.
+pub fn parse_clusters(src: &str) -> Vec<ClusterDef> {
+    parse_blocks(src)
+        .iter()
+        .filter(|b| b.kind == "cluster" || b.kind == "clusterdef")
+        .filter_map(|block| {
+            Some(ClusterDef {
+                id: block.args.first()?.parse().ok()?,
+                enter_text: story_text(block, "entertext"),
+                exit_text: story_text(block, "exittext"),
+                backdrop: block.get("pic").or(block.get("flat")).filter(|f| !f.is_empty()).map(str::to_string),
+                music: block.get("music").filter(|m| !m.is_empty()).map(str::to_string),
+                hub: block.has("hub"),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(lump: &str, src: &str) -> Vec<(String, String)> {
+        parse_level_names(lump, src)
+    }
+
+    fn pair(id: &str, name: &str) -> (String, String) {
+        (id.to_string(), name.to_string())
+    }
+
+    #[test]
+    fn parses_zmapinfo_blocks() {
+        let src = r#"
+            // comment
+            map MAP01 "Entryway" { next = "MAP02" par = 30 }
+            map map02 "Underhalls"
+            {
+                music = "D_STALKS"
+            }
+            /* map MAP99 "Hidden" */
+            map MAP03 lookup "HUSTR_3" { }
+        "#;
+        assert_eq!(
+            names("ZMAPINFO", src),
+            vec![pair("MAP01", "Entryway"), pair("MAP02", "Underhalls"), pair("MAP03", "$HUSTR_3")]
+        );
+        let blocks = parse_blocks(src);
+        assert_eq!(blocks[0].get("next"), Some("MAP02"));
+        assert_eq!(blocks[0].get("par"), Some("30"));
+        assert_eq!(blocks[1].get("music"), Some("D_STALKS"));
+    }
+
+    #[test]
+    fn parses_old_style_hexen_mapinfo() {
+        let src = "; Hexen\nmap 1 \"WINNOWING HALL\"\nwarptrans 1\nnext 2\ncluster 1\n\nmap 2 \"SEVEN PORTALS\"\nnext 3\n";
+        assert_eq!(
+            names("MAPINFO", src),
+            vec![pair("1", "WINNOWING HALL"), pair("2", "SEVEN PORTALS")]
+        );
+        let blocks = parse_blocks(src);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].get("next"), Some("2"));
+        assert_eq!(blocks[0].get("cluster"), Some("1"));
+
+        // In brace style, cluster opens its own block.
+        let blocks = parse_blocks("map MAP01 \"A\" { }\ncluster 5\n{\n exittext = \"Bye\"\n}\n");
+        assert_eq!(blocks[1].kind, "cluster");
+        assert_eq!(blocks[1].get("exittext"), Some("Bye"));
+    }
+
+    #[test]
+    fn parses_umapinfo_levelname_and_multiline_values() {
+        let src = r#"
+            MAP MAP01
+            {
+                levelname = "Hangar"
+                intertext = "line one",
+                            "line two"
+                next = "MAP02"
+            }
+        "#;
+        assert_eq!(names("UMAPINFO", src), vec![pair("MAP01", "Hangar")]);
+        let block = &parse_blocks(src)[0];
+        let intertext = block.props.iter().find(|p| p.key == "intertext").unwrap();
+        assert_eq!(intertext.values, vec!["line one", "line two"]);
+        assert_eq!(block.get("next"), Some("MAP02"));
+    }
+
+    #[test]
+    fn parses_emapinfo_sections() {
+        let src = "[MAP01]\nlevelname = MAP01: Entryway\n[map02]\nlevelname = Underhalls\n";
+        assert_eq!(
+            names("EMAPINFO", src),
+            vec![pair("MAP01", "Entryway"), pair("MAP02", "Underhalls")]
+        );
+    }
+
+    #[test]
+    fn parses_par_times_per_dialect() {
+        let par = |lump: &str, src: &str| -> Vec<(String, Option<u32>)> {
+            parse_levels(lump, src).into_iter().map(|l| (l.id, l.par_seconds)).collect()
+        };
+        assert_eq!(
+            par("ZMAPINFO", "map MAP01 \"A\" { par = 30 }\nmap MAP02 \"B\" { }"),
+            vec![("MAP01".into(), Some(30)), ("MAP02".into(), None)]
+        );
+        assert_eq!(par("MAPINFO", "map E1M1 \"Hangar\"\npar 30\n"), vec![("E1M1".into(), Some(30))]);
+        assert_eq!(par("UMAPINFO", "MAP MAP07 { partime = 180 }"), vec![("MAP07".into(), Some(180))]);
+        assert_eq!(par("EMAPINFO", "[MAP01]\npartime = 45\n"), vec![("MAP01".into(), Some(45))]);
+    }
+
+    #[test]
+    fn merge_keeps_first_definition_per_field() {
+        let mut merged = Vec::new();
+        merge_levels(&mut merged, parse_levels("MAPINFO", "map MAP01 \"From MAPINFO\""));
+        merge_levels(
+            &mut merged,
+            parse_levels("UMAPINFO", "MAP MAP01 { levelname = \"Other\" partime = 60 }\nMAP MAP02 { levelname = \"Second\" }"),
+        );
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].name.as_deref(), Some("From MAPINFO"));
+        assert_eq!(merged[0].par_seconds, Some(60));
+        assert_eq!(merged[1].name.as_deref(), Some("Second"));
+    }
+
+    #[test]
+    fn parses_next_and_secret_exits() {
+        let map = |id: &str| Some(LevelExit::Map { id: id.into() });
+        let end = |e: &str| Some(LevelExit::End { ending: e.into() });
+
+        let levels = parse_levels(
+            "ZMAPINFO",
+            "map MAP15 \"A\" { next = \"MAP16\" secretnext = \"MAP31\" }\n\
+             map MAP30 \"B\" { next = EndPic, \"CREDIT\" }\n\
+             map MAP31 \"C\" { next = \"map16\" }",
+        );
+        assert_eq!(levels[0].next, map("MAP16"));
+        assert_eq!(levels[0].secret_next, map("MAP31"));
+        assert_eq!(levels[1].next, end("EndPic CREDIT"));
+        assert_eq!(levels[1].secret_next, None);
+        assert_eq!(levels[2].next, map("MAP16"));
+
+        let levels = parse_levels(
+            "UMAPINFO",
+            "MAP E1M3 { next = \"E1M4\" nextsecret = \"E1M9\" }\nMAP E1M8 { endgame = true }",
+        );
+        assert_eq!(levels[0].secret_next, map("E1M9"));
+        assert_eq!(levels[1].next, end("EndGame"));
+
+        let levels = parse_levels("EMAPINFO", "[MAP07]\nnextlevel = MAP08\nnextsecret = MAP31\n");
+        assert_eq!(levels[0].next, map("MAP08"));
+        assert_eq!(levels[0].secret_next, map("MAP31"));
+    }
+
+    #[test]
+    fn resolves_numeric_targets_and_hexen_clusters() {
+        // Hexen's `next` is a warptrans number, not the map number.
+        let src = "map 1 \"WINNOWING HALL\"\nwarptrans 1\nnext 2\ncluster 1\n\n\
+                   map 13 \"DARKMERE\"\nwarptrans 2\nnext EndGame1\ncluster 2\n\n\
+                   map MAP02 \"X\"\nnext 5\n";
+        let levels = parse_levels("MAPINFO", src);
+        assert_eq!(levels[0].next, Some(LevelExit::Map { id: "13".into() }));
+        assert_eq!(levels[0].cluster, Some(1));
+        assert_eq!(levels[1].next, Some(LevelExit::End { ending: "EndGame1".into() }));
+        assert_eq!(levels[1].cluster, Some(2));
+        assert_eq!(levels[2].next, Some(LevelExit::Map { id: "MAP05".into() }));
+    }
+
+    #[test]
+    fn parses_music_tracks() {
+        let music = |lump: &str, src: &str| -> Vec<Option<String>> {
+            parse_levels(lump, src).into_iter().map(|l| l.music).collect()
+        };
+        let some = |m: &str| Some(m.to_string());
+        assert_eq!(
+            music(
+                "ZMAPINFO",
+                "map MAP01 \"A\" { music = \"D_RUNNIN\", 2 }\n\
+                 map MAP02 \"B\" { music = \"music/map02.ogg\" }\n\
+                 map MAP03 \"C\" { }"
+            ),
+            vec![some("D_RUNNIN"), some("music/map02.ogg"), None]
+        );
+        assert_eq!(music("MAPINFO", "map E1M1 \"Hangar\"\nmusic $MUSIC_E1M1\n"), vec![some("$MUSIC_E1M1")]);
+        assert_eq!(music("UMAPINFO", "MAP MAP01 { music = \"D_DM2TTL\" }"), vec![some("D_DM2TTL")]);
+        assert_eq!(music("EMAPINFO", "[MAP01]\nmusic = RUNNIN\n"), vec![some("RUNNIN")]);
+    }
+
+    #[test]
+    fn parses_umapinfo_author_and_flags() {
+        let levels = parse_levels(
+            "UMAPINFO",
+            "MAP MAP01 { levelname = \"A\" author = \"Someone\" nointermission = true intermusic = \"D_DM2INT\" bossaction = clear }\n\
+             MAP MAP02 { endgame = true somefuturekey = 1, 2 }",
+        );
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[0].author.as_deref(), Some("Someone"));
+        assert!(levels[0].no_intermission && !levels[0].ends_game);
+        assert_eq!(levels[0].inter_music.as_deref(), Some("D_DM2INT"));
+        assert!(!levels[1].no_intermission && levels[1].ends_game);
+
+        let levels = parse_levels("ZMAPINFO", "map MAP07 \"B\"\n{\n  nointermission\n  next = EndGame\n}");
+        assert!(levels[0].no_intermission && levels[0].ends_game);
+    }
+
+    #[test]
+    fn parses_cluster_story_texts() {
+        let zmapinfo = r#"
+            cluster 5
+            {
+                flat = "SLIME16"
+                music = "$MUSIC_READ_M"
+                exittext = "Once you beat the big badasses",
+                           "and clean out the moon base"
+            }
+            cluster 6
+            {
+                exittext = lookup, "C2TEXT"
+                entertext = "STORY1"
+                entertextislump
+                pic = "INTERPIC"
+            }
+        "#;
+        let clusters = parse_clusters(zmapinfo);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(
+            clusters[0].exit_text,
+            Some(StoryText::Literal("Once you beat the big badasses\nand clean out the moon base".into()))
+        );
+        assert_eq!((clusters[0].backdrop.as_deref(), clusters[0].music.as_deref()), (Some("SLIME16"), Some("$MUSIC_READ_M")));
+        assert_eq!(clusters[1].exit_text, Some(StoryText::Lookup("C2TEXT".into())));
+        assert_eq!(clusters[1].enter_text, Some(StoryText::Lump("STORY1".into())));
+        assert_eq!(clusters[1].backdrop.as_deref(), Some("INTERPIC"));
+
+        let hexen = "map 1 \"WINNOWING HALL\"\ncluster 1\n\nclusterdef 1\nhub\nexittext lookup HEXEN_C1\n";
+        let clusters = parse_clusters(hexen);
+        assert_eq!(clusters.len(), 1);
+        assert!(clusters[0].hub);
+        assert_eq!(clusters[0].exit_text, Some(StoryText::Lookup("HEXEN_C1".into())));
+    }
+
+    #[test]
+    fn parses_episode_menus() {
+        let zmapinfo = r#"
+            clearepisodes
+            episode MAP01 { name = "Hell Revealed" picname = "M_EPI1" key = "h" }
+            episode map12 { lookup = "EPI_2" }
+            episode MAP20 { name = "Cut" }
+            episode MAP20 { remove }
+        "#;
+        let mut list = EpisodeList::default();
+        apply_episodes(&mut list, "ZMAPINFO", zmapinfo);
+        assert!(list.clears_defaults);
+        assert_eq!(
+            list.episodes,
+            vec![
+                Episode {
+                    start_map: "MAP01".into(),
+                    name: Some("Hell Revealed".into()),
+                    pic_name: Some("M_EPI1".into()),
+                    key: Some("h".into()),
+                },
+                Episode { start_map: "MAP12".into(), name: Some("$EPI_2".into()), pic_name: None, key: None },
+            ]
+        );
+
+        let mut list = EpisodeList::default();
+        apply_episodes(&mut list, "MAPINFO", "episode e1m1\nname lookup HUSTR_E1\npicname M_EPI1\n");
+        apply_episodes(&mut list, "UMAPINFO", "MAP E2M1 { episode = \"M_EPI2\", \"The Shores\", \"s\" }");
+        assert!(!list.clears_defaults);
+        assert_eq!(list.episodes[0].name.as_deref(), Some("$HUSTR_E1"));
+        assert_eq!((list.episodes[1].start_map.as_str(), list.episodes[1].name.as_deref()), ("E2M1", Some("The Shores")));
+        apply_episodes(&mut list, "UMAPINFO", "MAP E1M1 { episode = clear }");
+        assert_eq!((list.episodes.len(), list.clears_defaults), (0, true));
+    }
+
+    #[test]
+    fn parses_gameinfo_and_iwadinfo() {
+        let mapinfo = r#"
+            gameinfo
+            {
+                playerclasses = "DoomPlayer"
+                titlemusic = "$MUSIC_INTRO"
+                creditpage = "CREDIT", "HELP2"
+                borderflat = "FLOOR7_2"
+            }
+            map MAP01 "Entryway" { }
+        "#;
+        let mut info = GameInfo::default();
+        assert!(apply_gameinfo(&mut info, mapinfo));
+        assert_eq!(info.credit_pages, vec!["CREDIT", "HELP2"]);
+        assert_eq!((info.title_music.as_deref(), info.border_flat.as_deref()), (Some("$MUSIC_INTRO"), Some("FLOOR7_2")));
+        assert!(!info.total_conversion);
+
+        assert!(apply_gameinfo(&mut info, "gameinfo { playerclasses = \"HacxPlayer\" }"));
+        assert!(info.total_conversion);
+        assert_eq!(info.title_page, None);
+
+        let mut info = GameInfo::default();
+        assert!(apply_gameinfo(&mut info, "IWad\n{\n  Name = \"Freedoom: Phase 2\"\n  Game = \"Doom\"\n  MustContain = \"MAP01\"\n}\n"));
+        assert_eq!((info.iwad_name.as_deref(), info.game.as_deref()), (Some("Freedoom: Phase 2"), Some("Doom")));
+        assert!(info.total_conversion);
+        assert!(!apply_gameinfo(&mut GameInfo::default(), "map MAP01 \"Entryway\" { }"));
+    }
+}
@@ -0,0 +1,117 @@
+// MUSINFO: music that changes mid-map. A music changer thing placed in a
+// map carries a numeric id, and when the player touches it the engine
+// switches to the lump MUSINFO lists for that map and id. ZDoom, Eternity
+// and the Boom descendants (DSDA-Doom, Woof) read it. A map name starts a
+// section; each line after it is an id and a lump:
+//
+//     MAP01
+//     1 D_RUNNIN
+//     2 D_STALKS     // e.g. when the player reaches the outdoor area
+//     E1M1
+//     1 D_E1M5
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::mapinfo::{self, Token};
+use crate::wad_parser;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MusicChange {
+    /// Map lump name, uppercase.
+    pub map: String,
+    /// The id a music changer thing in the map selects the track by.
+    pub id: u32,
+    /// Music lump name, uppercase.
+    pub lump: String,
+    pub defined_in: String,
+    /// The earlier file whose track for this map and id this one replaces.
+    pub replaces: Option<String>,
+}
+
+/// (map, id, lump) in the order one MUSINFO lump lists them. Lines that
+/// are neither a map name nor "id lump" are skipped.
+fn parse_musinfo(src: &str) -> Vec<(String, u32, String)> {
+    let tokens = mapinfo::tokenize(src);
+    let mut out = Vec::new();
+    let mut map: Option<String> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let line_end = tokens[i..].iter().position(|t| t.line != tokens[i].line).map_or(tokens.len(), |n| i + n);
+        let line: Vec<&Token> = tokens[i..line_end].iter().collect();
+        match (line[0].text.parse::<u32>(), line.get(1)) {
+            (Ok(id), Some(lump)) => match &map {
+                Some(map) => out.push((map.clone(), id, lump.text.to_uppercase())),
+                None => eprintln!("MUSINFO: track {} before any map name, skipped", id),
+            },
+            (Err(_), _) => map = Some(line[0].text.to_uppercase()),
+            _ => {}
+        }
+        i = line_end;
+    }
+    out
+}
+
+/// The music changes of a load order (IWAD first, when given). A later
+/// file's track for the same map and id replaces the earlier one, as the
+/// engine reads every MUSINFO in order; the result keeps the first one's
+/// position.
+pub fn list_music_changes(files: &[String]) -> Result<Vec<MusicChange>, String> {
+    let mut out: Vec<MusicChange> = Vec::new();
+    let mut index: HashMap<(String, u32), usize> = HashMap::new();
+    for file in files {
+        for lump in wad_parser::collect_lumps(file, &|name| name == "MUSINFO")? {
+            for (map, id, track) in parse_musinfo(&String::from_utf8_lossy(&lump.data)) {
+                match index.get(&(map.clone(), id)) {
+                    Some(&at) => {
+                        let previous = &mut out[at];
+                        if previous.defined_in != *file {
+                            previous.replaces = Some(std::mem::replace(&mut previous.defined_in, file.clone()));
+                        }
+                        previous.lump = track;
+                    }
+                    None => {
+                        index.insert((map.clone(), id), out.len());
+                        out.push(MusicChange { map, id, lump: track, defined_in: file.clone(), replaces: None });
+                    }
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, build_zip, write_temp};
+
+    #[test]
+    fn parses_tracks_per_map() {
+        let src = "3 D_NOMAP\nmap01\n1 d_runnin\n2 D_STALKS // outside\n\nE1M1\n1 D_E1M5\n";
+        assert_eq!(
+            parse_musinfo(src),
+            vec![
+                ("MAP01".to_string(), 1, "D_RUNNIN".to_string()),
+                ("MAP01".to_string(), 2, "D_STALKS".to_string()),
+                ("E1M1".to_string(), 1, "D_E1M5".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn later_files_replace_tracks() {
+        let base = write_temp("mus_base.wad", &build_wad(b"IWAD", &[("MUSINFO", b"MAP01\n1 D_RUNNIN\n2 D_STALKS\n")]));
+        let pack = write_temp("mus_pack.pk3", &build_zip(&[("musinfo.txt", b"MAP01\n2 NEWSONG\nMAP02\n1 D_COUNTD\n")]));
+        let changes = list_music_changes(&[base.clone(), pack.clone()]).unwrap();
+        let summary: Vec<(&str, u32, &str, &str)> =
+            changes.iter().map(|c| (c.map.as_str(), c.id, c.lump.as_str(), c.defined_in.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![("MAP01", 1, "D_RUNNIN", base.as_str()), ("MAP01", 2, "NEWSONG", pack.as_str()), ("MAP02", 1, "D_COUNTD", pack.as_str())]
+        );
+        assert_eq!(changes[1].replaces, Some(base));
+        assert_eq!(changes[2].replaces, None);
+    }
+}
@@ -0,0 +1,112 @@
+// Multiplayer launch options. GZDoom and ZDoom start peer-to-peer games
+// with `-host N` / `-join <address>`; the Chocolate Doom family has a
+// server player (`-server`) that others `-connect` to. The game mode and
+// port flags are shared. Other ports need a separate server program and
+// aren't offered.
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::EngineKind;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Netplay {
+    /// Host a game for this many players, this one included.
+    pub host: Option<u8>,
+    /// Join the game hosted at this address ("192.168.1.5" or "host:5029").
+    pub join: Option<String>,
+    /// UDP port, when not the engine's default.
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub deathmatch: bool,
+    /// Deathmatch 2.0: items respawn.
+    #[serde(default)]
+    pub altdeath: bool,
+}
+
+/// Most players a vanilla-style netgame can have.
+const MAX_PLAYERS: u8 = 8;
+
+/// Validate `netplay` and return the engine arguments for `kind`.
+pub fn netplay_args(netplay: &Netplay, kind: EngineKind) -> Result<Vec<String>, String> {
+    let join = netplay.join.as_deref().map(str::trim);
+    if netplay.host.is_some() && join.is_some() {
+        return Err("Can't both host and join a multiplayer game".to_string());
+    }
+    if netplay.deathmatch && netplay.altdeath {
+        return Err("Deathmatch and altdeath can't both be set; pick one mode".to_string());
+    }
+    if netplay.port.is_some() && netplay.host.is_none() && join.is_none() {
+        return Err("A port needs a game to host or join".to_string());
+    }
+
+    let chocolate = matches!(kind, EngineKind::Chocolate | EngineKind::Crispy | EngineKind::Woof);
+    let mut args = Vec::new();
+    if let Some(players) = netplay.host {
+        if players == 0 || players > MAX_PLAYERS {
+            return Err(format!("Player count {} is out of range (1-{})", players, MAX_PLAYERS));
+        }
+        match kind {
+            EngineKind::GZDoom | EngineKind::ZDoom => args.extend(["-host".to_string(), players.to_string()]),
+            // The server starts the game once everyone is in; no count.
+            _ if chocolate => args.push("-server".to_string()),
+            _ => return Err(format!("{} can't host a multiplayer game from the launcher", kind.display_name())),
+        }
+    }
+    if let Some(address) = join {
+        if address.is_empty() {
+            return Err("Join address is empty".to_string());
+        }
+        match kind {
+            EngineKind::GZDoom | EngineKind::ZDoom => args.extend(["-join".to_string(), address.to_string()]),
+            _ if chocolate || kind == EngineKind::Zandronum => {
+                args.extend(["-connect".to_string(), address.to_string()])
+            }
+            _ => return Err(format!("{} can't join a multiplayer game from the launcher", kind.display_name())),
+        }
+    }
+    if let Some(port) = netplay.port {
+        if port == 0 {
+            return Err("Port 0 is not a valid port".to_string());
+        }
+        args.extend(["-port".to_string(), port.to_string()]);
+    }
+    if netplay.deathmatch {
+        args.push("-deathmatch".to_string());
+    }
+    if netplay.altdeath {
+        args.push("-altdeath".to_string());
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(netplay: Netplay, kind: EngineKind) -> Result<String, String> {
+        netplay_args(&netplay, kind).map(|a| a.join(" "))
+    }
+
+    #[test]
+    fn builds_host_and_join_arguments() {
+        let host = Netplay { host: Some(4), port: Some(5030), deathmatch: true, ..Default::default() };
+        assert_eq!(args(host.clone(), EngineKind::GZDoom).unwrap(), "-host 4 -port 5030 -deathmatch");
+        assert_eq!(args(host, EngineKind::Chocolate).unwrap(), "-server -port 5030 -deathmatch");
+
+        let join = Netplay { join: Some(" 192.168.1.5 ".into()), altdeath: true, ..Default::default() };
+        assert_eq!(args(join.clone(), EngineKind::GZDoom).unwrap(), "-join 192.168.1.5 -altdeath");
+        assert_eq!(args(join.clone(), EngineKind::Crispy).unwrap(), "-connect 192.168.1.5 -altdeath");
+        assert!(args(join, EngineKind::PrBoom).unwrap_err().contains("can't join"));
+    }
+
+    #[test]
+    fn rejects_conflicting_options() {
+        let err = |netplay: Netplay| args(netplay, EngineKind::GZDoom).unwrap_err();
+        assert!(err(Netplay { host: Some(2), join: Some("10.0.0.1".into()), ..Default::default() }).contains("both host and join"));
+        assert!(err(Netplay { host: Some(2), deathmatch: true, altdeath: true, ..Default::default() }).contains("pick one"));
+        assert!(err(Netplay { port: Some(5029), ..Default::default() }).contains("host or join"));
+        assert!(err(Netplay { host: Some(9), ..Default::default() }).contains("1-8"));
+        assert!(err(Netplay { join: Some(" ".into()), ..Default::default() }).contains("empty"));
+    }
+}
@@ -0,0 +1,174 @@
+// Doom graphics decoding for previews. Title screens come as Doom
+// "pictures" (column-based patches, the usual TITLEPIC), raw 320x200
+// screens (Heretic's and Hexen's TITLE), or PNGs in modern PK3s. Indexed
+// formats are coloured with the first palette of PLAYPAL.
+//
+// Picture layout: width, height: u16 LE; left/top offset: i16 LE; then
+// `width` u32 LE column offsets. A column is a run of posts — topdelta u8
+// (0xFF ends the column), length u8, a pad byte, `length` palette indices,
+// another pad byte.
+
+use base64::Engine;
+
+/// One 256-colour palette as RGB triples.
+pub type Palette = [[u8; 3]; 256];
+
+pub const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Raw fullscreen images are exactly 320x200 indices.
+const RAW_SCREEN_LEN: usize = 320 * 200;
+
+/// The first palette of a PLAYPAL lump (14 palettes of 768 bytes).
+pub fn palette_from_playpal(playpal: &[u8]) -> Result<Palette, String> {
+    if playpal.len() < 768 {
+        return Err(format!("PLAYPAL is too short ({} bytes)", playpal.len()));
+    }
+    let mut palette = [[0u8; 3]; 256];
+    for (i, rgb) in palette.iter_mut().enumerate() {
+        rgb.copy_from_slice(&playpal[i * 3..i * 3 + 3]);
+    }
+    Ok(palette)
+}
+
+/// Decoded image: RGBA8, row-major.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rgba {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Decode a Doom picture; None if `data` isn't one. Pixels not covered by
+/// a post stay transparent.
+pub fn decode_picture(data: &[u8], palette: &Palette) -> Option<Rgba> {
+    let width = u16_at(data, 0)? as usize;
+    let height = u16_at(data, 2)? as usize;
+    if width == 0 || height == 0 || width > 4096 || height > 4096 || data.len() < 8 + width * 4 {
+        return None;
+    }
+    let mut pixels = vec![0u8; width * height * 4];
+    for x in 0..width {
+        let mut at = u32_at(data, 8 + x * 4)? as usize;
+        let mut top = -1i32;
+        loop {
+            let delta = *data.get(at)?;
+            if delta == 0xFF {
+                break;
+            }
+            // Tall patches: a delta not past the previous one is relative.
+            top = if i32::from(delta) <= top { top + i32::from(delta) } else { i32::from(delta) };
+            let len = *data.get(at + 1)? as usize;
+            let run = data.get(at + 3..at + 3 + len)?;
+            for (dy, &index) in run.iter().enumerate() {
+                let y = top as usize + dy;
+                if y >= height {
+                    break;
+                }
+                let p = (y * width + x) * 4;
+                pixels[p..p + 3].copy_from_slice(&palette[index as usize]);
+                pixels[p + 3] = 255;
+            }
+            at += len + 4;
+        }
+    }
+    Some(Rgba { width: width as u32, height: height as u32, pixels })
+}
+
+/// Decode a raw 320x200 screen; None if `data` isn't one.
+pub fn decode_raw_screen(data: &[u8], palette: &Palette) -> Option<Rgba> {
+    if data.len() != RAW_SCREEN_LEN {
+        return None;
+    }
+    let mut pixels = Vec::with_capacity(RAW_SCREEN_LEN * 4);
+    for &index in data {
+        pixels.extend_from_slice(&palette[index as usize]);
+        pixels.push(255);
+    }
+    Some(Rgba { width: 320, height: 200, pixels })
+}
+
+/// Width and height from a PNG's IHDR chunk.
+pub fn png_size(data: &[u8]) -> Option<(u32, u32)> {
+    if !data.starts_with(PNG_MAGIC) || data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let be = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    Some((be(16)?, be(20)?))
+}
+
+pub fn encode_png(image: &Rgba) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    writer
+        .write_image_data(&image.pixels)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    writer.finish().map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    Ok(out)
+}
+
+/// `data:image/png;base64,…` for an <img src>.
+pub fn png_data_url(png: &[u8]) -> String {
+    format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png))
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    /// A PLAYPAL whose colour i is (i, 255 - i, 0).
+    pub fn playpal() -> Vec<u8> {
+        (0..256).flat_map(|i| [i as u8, 255 - i as u8, 0]).collect()
+    }
+
+    /// A 2x3 picture: column 0 has indices 1,2 from the top, column 1 a
+    /// single index 7 at y = 2.
+    pub fn picture() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&3u16.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(&23u32.to_le_bytes());
+        data.extend_from_slice(&[0, 2, 0, 1, 2, 0, 0xFF]);
+        data.extend_from_slice(&[2, 1, 0, 7, 0, 0xFF]);
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::*;
+    use super::*;
+
+    #[test]
+    fn decodes_picture_columns_with_transparency() {
+        let palette = palette_from_playpal(&playpal()).unwrap();
+        let image = decode_picture(&picture(), &palette).unwrap();
+        assert_eq!((image.width, image.height), (2, 3));
+        let px = |x: usize, y: usize| image.pixels[(y * 2 + x) * 4..(y * 2 + x) * 4 + 4].to_vec();
+        assert_eq!(px(0, 0), vec![1, 254, 0, 255]);
+        assert_eq!(px(0, 1), vec![2, 253, 0, 255]);
+        assert_eq!(px(0, 2), vec![0, 0, 0, 0]);
+        assert_eq!(px(1, 0), vec![0, 0, 0, 0]);
+        assert_eq!(px(1, 2), vec![7, 248, 0, 255]);
+        assert_eq!(decode_picture(b"\x00\x00", &palette), None);
+    }
+
+    #[test]
+    fn encodes_png_and_reads_its_size() {
+        let palette = palette_from_playpal(&playpal()).unwrap();
+        let screen = decode_raw_screen(&vec![3u8; 320 * 200], &palette).unwrap();
+        let png = encode_png(&screen).unwrap();
+        assert_eq!(png_size(&png), Some((320, 200)));
+        assert!(png_data_url(&png).starts_with("data:image/png;base64,iVBORw0KGgo"));
+    }
+}
@@ -0,0 +1,121 @@
+// Play time per WAD, for "you've played Sunlust for 12 hours". Each
+// finished session adds its runtime to the file it was launched with: the
+// first -file, or the IWAD when there is none. Files are keyed by
+// wad_parser::content_key, so a renamed or moved WAD keeps its history.
+// Stored as play-stats.json in the app config dir, a json_store file like
+// profiles.json.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::json_store;
+use crate::wad_parser;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayStat {
+    /// Content hash of the file.
+    pub key: String,
+    /// Where the file was last launched from.
+    pub path: String,
+    pub total_ms: u64,
+    pub sessions: u32,
+    /// Milliseconds since the Unix epoch.
+    pub last_played_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayStats {
+    pub version: u8,
+    pub stats: Vec<PlayStat>,
+}
+
+impl PlayStats {
+    pub fn empty() -> Self {
+        Self {
+            version: 1,
+            stats: Vec::new(),
+        }
+    }
+}
+
+/// Serializes read-modify-write cycles: two engines can exit at once.
+static STATS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Build the full path to play-stats.json in the app config directory.
+pub fn play_stats_path(config_dir: impl AsRef<Path>) -> PathBuf {
+    config_dir.as_ref().join("play-stats.json")
+}
+
+fn read_stats_or_empty(path: &Path) -> Result<PlayStats, String> {
+    json_store::read_or_empty(path, PlayStats::empty)
+}
+
+fn write_stats(path: &Path, state: &PlayStats) -> Result<(), String> {
+    json_store::write(path, state, "play stats")
+}
+
+/// The file a launch's play time counts for: the first -file, else the
+/// -iwad.
+pub fn counted_file(args: &[String]) -> Option<String> {
+    let after = |flag: &str| args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).cloned();
+    after("-file").or_else(|| after("-iwad"))
+}
+
+/// Add one finished session of `runtime_ms` to `file`'s total.
+pub fn record_session(path: impl AsRef<Path>, file: &str, runtime_ms: u64) -> Result<(), String> {
+    let key = wad_parser::content_key(file)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("System clock error: {}", e))?
+        .as_millis() as u64;
+    let _guard = STATS_LOCK.lock().unwrap();
+    let mut state = read_stats_or_empty(path.as_ref())?;
+    match state.stats.iter_mut().find(|s| s.key == key) {
+        Some(stat) => {
+            stat.path = file.to_string();
+            stat.total_ms += runtime_ms;
+            stat.sessions += 1;
+            stat.last_played_ms = now;
+        }
+        None => state.stats.push(PlayStat { key, path: file.to_string(), total_ms: runtime_ms, sessions: 1, last_played_ms: now }),
+    }
+    write_stats(path.as_ref(), &state)
+}
+
+/// Play time per file, most played first.
+pub fn get_play_stats(path: impl AsRef<Path>) -> Result<Vec<PlayStat>, String> {
+    let _guard = STATS_LOCK.lock().unwrap();
+    let mut stats = read_stats_or_empty(path.as_ref())?.stats;
+    stats.sort_by_key(|stat| std::cmp::Reverse(stat.total_ms));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, write_temp};
+
+    #[test]
+    fn totals_play_time_across_renames() {
+        let store = play_stats_path(std::env::temp_dir().join(format!("play_stats_test_{}", std::process::id())));
+        let sunlust = build_wad(b"PWAD", &[("MAP01", b""), ("THINGS", b"1234")]);
+        let old_name = write_temp("sunlust.wad", &sunlust);
+        let new_name = write_temp("sunlust_v2.wad", &sunlust);
+        let other = write_temp("scythe.wad", &build_wad(b"PWAD", &[("MAP02", b"")]));
+
+        record_session(&store, &old_name, 60_000).unwrap();
+        record_session(&store, &other, 90_000).unwrap();
+        record_session(&store, &new_name, 45_000).unwrap();
+
+        let stats = get_play_stats(&store).unwrap();
+        let summary: Vec<(&str, u64, u32)> = stats.iter().map(|s| (s.path.as_str(), s.total_ms, s.sessions)).collect();
+        assert_eq!(summary, vec![(new_name.as_str(), 105_000, 2), (other.as_str(), 90_000, 1)]);
+
+        let args: Vec<String> = ["-iwad", "doom2.wad", "-file", "a.wad", "-file", "b.pk3"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(counted_file(&args).as_deref(), Some("a.wad"));
+        assert_eq!(counted_file(&args[..2]).as_deref(), Some("doom2.wad"));
+    }
+}
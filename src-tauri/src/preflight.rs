@@ -0,0 +1,230 @@
+// Pre-flight check of a launch: every check a launch would make, collected
+// into one list instead of stopping at the first failure, plus warnings a
+// launch doesn't stop for, like a mod that needs a different port. The UI
+// shows the list as a checklist and disables Launch while it has errors.
+// Nothing is started, and nothing is written but the temp copies of WADs
+// nested in archives that reading them takes; a missing save directory is
+// reported as one that will be created.
+
+use serde::Serialize;
+use std::path::Path;
+
+use crate::compat::{self, PortFeatures};
+use crate::engine::{self, EngineKind};
+use crate::launch_config::{self, LaunchConfig};
+use crate::wad_parser::{self, WadType};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The launch would fail or the game wouldn't work.
+    Error,
+    /// Worth a look, but the launch can go ahead.
+    Warning,
+}
+
+/// Which part of the launch an issue is about.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Check {
+    Engine,
+    Iwad,
+    File,
+    Dehacked,
+    Compatibility,
+    Warp,
+    Savedir,
+    /// The remaining options: skill, complevel, demo, netplay.
+    Options,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub check: Check,
+    /// The file the issue is about, when there is one.
+    pub path: Option<String>,
+    pub message: String,
+}
+
+fn issue(severity: Severity, check: Check, path: Option<&str>, message: impl Into<String>) -> ValidationIssue {
+    ValidationIssue { severity, check, path: path.map(str::to_string), message: message.into() }
+}
+
+/// Check `engine_path` (with an explicit `engine_kind` for renamed
+/// binaries) and `config` as launch_gzdoom_with_config would, and more.
+/// An empty list means the launch is expected to work.
+pub fn validate_profile(engine_path: &str, engine_kind: Option<EngineKind>, config: &LaunchConfig) -> Vec<ValidationIssue> {
+    use Severity::*;
+    let mut issues = Vec::new();
+    let kind = engine::bundle_executable(engine_path).and_then(|path| {
+        let kind = engine::resolve_engine(&path, engine_kind)?;
+        engine::check_executable(&path, false)?;
+        Ok(kind)
+    });
+    let kind = match kind {
+        Ok(kind) => Some(kind),
+        Err(e) => {
+            issues.push(issue(Error, Check::Engine, Some(engine_path), e.to_string()));
+            engine_kind
+        }
+    };
+
+    let iwad_ok = match launch_config::check_iwad(&config.iwad) {
+        Ok(()) => true,
+        Err(e) => {
+            issues.push(issue(Error, Check::Iwad, Some(&config.iwad), e.to_string()));
+            false
+        }
+    };
+
+    let files = match launch_config::load_order_files(config) {
+        Ok(files) => files,
+        Err(e) => {
+            issues.push(issue(Error, Check::File, config.autoload_dir.as_deref(), e));
+            Vec::new()
+        }
+    };
+    let mut readable = Vec::new();
+    for file in &files {
+        if file.trim().is_empty() {
+            issues.push(issue(Error, Check::File, None, "Empty file path in the load order"));
+            continue;
+        }
+        // Folders load like archives; -file also takes DEHACKED patches.
+        if Path::new(file).is_dir() {
+            readable.push(file.clone());
+            continue;
+        }
+        if !Path::new(file).is_file() {
+            issues.push(issue(Error, Check::File, Some(file), format!("File not found: {}", file)));
+            continue;
+        }
+        if launch_config::is_patch_file(file) {
+            if let Err(e) = std::fs::File::open(file) {
+                issues.push(issue(Error, Check::Dehacked, Some(file), format!("Cannot read DEHACKED patch {}: {}", file, e)));
+            }
+            continue;
+        }
+        match wad_parser::check_file(file) {
+            Ok(WadType::Iwad | WadType::Ipk3) => {
+                issues.push(issue(Warning, Check::File, Some(file), format!("{} is an IWAD loaded as a mod", file)))
+            }
+            Ok(_) => {}
+            Err(e) => {
+                issues.push(issue(Error, Check::File, Some(file), e.to_string()));
+                continue;
+            }
+        }
+        if let Err(e) = launch_config::check_plain_zip(file) {
+            issues.push(issue(Error, Check::File, Some(file), e.to_string()));
+            continue;
+        }
+        readable.push(file.clone());
+    }
+
+    for patch in config.dehacked.iter().chain(config.bundles.iter().flat_map(|bundle| &bundle.dehacked)) {
+        if let Err(e) = std::fs::File::open(patch) {
+            issues.push(issue(Error, Check::Dehacked, Some(patch), format!("Cannot read DEHACKED patch {}: {}", patch, e)));
+        }
+    }
+
+    if let Some(kind) = kind {
+        for file in readable.iter().filter(|file| !Path::new(file).is_dir()) {
+            match compat::detect_compatibility(file) {
+                Ok(found) if !found.minimum.supported_by(kind) => {
+                    // Map specials are inferred and can be stray; needing a
+                    // ZDoom port (UDMF, PK3, ZSCRIPT) is certain.
+                    let severity = if found.minimum >= PortFeatures::Zdoom { Error } else { Warning };
+                    let reasons: Vec<&str> = found.evidence.iter().filter(|e| e.needs == found.minimum).map(|e| e.reason.as_str()).collect();
+                    let message = format!(
+                        "{} needs {}-level features {} lacks: {}",
+                        file,
+                        found.minimum.display_name(),
+                        kind.display_name(),
+                        reasons.join(", ")
+                    );
+                    issues.push(issue(severity, Check::Compatibility, Some(file), message));
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to check {} for port compatibility: {}", file, e),
+            }
+        }
+    }
+
+    if let Some(warp) = &config.warp
+        && iwad_ok
+        && let Some(kind) = kind
+        && let Err(e) = launch_config::checked_warp_args(config, &readable, warp.trim(), kind)
+    {
+        issues.push(issue(Error, Check::Warp, None, e));
+    }
+
+    if let Some(dir) = &config.savedir {
+        if !Path::new(dir).exists() {
+            issues.push(issue(Warning, Check::Savedir, Some(dir), format!("Save directory {} will be created", dir)));
+        } else if let Err(e) = crate::demo::check_writable_dir(Path::new(dir), "Save directory") {
+            issues.push(issue(Error, Check::Savedir, Some(dir), e));
+        }
+    }
+
+    // Whatever build_args still objects to once the checks above pass.
+    if let Some(kind) = kind
+        && !issues.iter().any(|i| i.severity == Error)
+        && let Err(e) = launch_config::build_args(config, kind)
+    {
+        issues.push(issue(Error, Check::Options, None, e.to_string()));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, build_zip, write_temp};
+
+    #[test]
+    fn collects_every_issue() {
+        let iwad = write_temp("pf_doom2.wad", &build_wad(b"IWAD", &[("MAP01", b"")]));
+        let udmf = write_temp("pf_udmf.wad", &build_wad(b"PWAD", &[("MAP02", b""), ("TEXTMAP", b"namespace=\"zdoom\";"), ("ENDMAP", b"")]));
+        let wrapped = write_temp("pf_wrapped.zip", &build_zip(&[("mod/mod.wad", b"PWAD")]));
+        let config = LaunchConfig {
+            iwad: iwad.clone(),
+            pwads: vec![udmf.clone(), "/nonexistent/pf.wad".to_string(), wrapped.clone()],
+            dehacked: vec!["/nonexistent/pf.deh".to_string()],
+            warp: Some("MAP07".to_string()),
+            ..Default::default()
+        };
+        let issues = validate_profile("/nonexistent/dsda-doom", Some(EngineKind::Dsda), &config);
+        let summary: Vec<(Severity, Check, Option<&str>)> = issues.iter().map(|i| (i.severity, i.check, i.path.as_deref())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Error, Check::Engine, Some("/nonexistent/dsda-doom")),
+                (Severity::Error, Check::File, Some("/nonexistent/pf.wad")),
+                (Severity::Error, Check::File, Some(wrapped.as_str())),
+                (Severity::Error, Check::Dehacked, Some("/nonexistent/pf.deh")),
+                (Severity::Error, Check::Compatibility, Some(udmf.as_str())),
+                (Severity::Error, Check::Warp, None),
+            ]
+        );
+        assert!(issues[4].message.contains("needs ZDoom-level features DSDA-Doom lacks"), "{}", issues[4].message);
+        assert!(issues[5].message.contains("MAP07"), "{}", issues[5].message);
+
+        let engine = std::env::current_exe().unwrap().to_string_lossy().to_string();
+        // A folder and a patch loaded with -file are fine too.
+        let patch = write_temp("pf_patch.deh", b"Patch File for DeHackEd v3.0\n");
+        let folder = std::env::temp_dir().to_string_lossy().to_string();
+        let good = LaunchConfig {
+            iwad,
+            pwads: vec![folder, patch],
+            warp: Some("MAP01".to_string()),
+            skill: Some(9),
+            ..Default::default()
+        };
+        let issues = validate_profile(&engine, Some(EngineKind::Dsda), &good);
+        assert_eq!(issues.iter().map(|i| i.check).collect::<Vec<_>>(), vec![Check::Options]);
+        assert!(issues[0].message.contains("Skill 9"), "{}", issues[0].message);
+    }
+}
@@ -0,0 +1,355 @@
+// Portable "is this engine running?" check. pgrep doesn't exist on Windows
+// and differs between macOS and Linux, so each platform lists process
+// executable names its own way: /proc on Linux, ps on macOS, tasklist on
+// Windows. Matching is by executable basename, like `pgrep -x`, unless
+// a looser MatchMode is asked for, so renamed and versioned builds
+// ("gzdoom-g4.11") still count.
+//
+// Minimal containers may restrict /proc or lack ps, so each platform has a
+// list of strategies tried in order; the first that works answers.
+
+use serde::Deserialize;
+use std::sync::Mutex;
+
+use crate::launch_error::LaunchError;
+
+/// Whether `candidate` (a process's executable name or path) names the
+/// wanted executable. Only the basename is compared. On Windows the
+/// comparison is case-insensitive and ignores a trailing ".exe" on either
+/// side, so "gzdoom" matches "GZDoom.exe".
+pub fn name_matches(candidate: &str, wanted: &str, windows_rules: bool) -> bool {
+    let base = candidate.rsplit(['/', '\\']).next().unwrap_or(candidate);
+    if windows_rules {
+        let strip = |s: &str| {
+            let lower = s.to_lowercase();
+            lower.strip_suffix(".exe").map(str::to_string).unwrap_or(lower)
+        };
+        strip(base) == strip(wanted)
+    } else {
+        base == wanted
+    }
+}
+
+/// How a process name is compared with a wanted one. The basename and the
+/// case and .exe rules are those of name_matches in every mode.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// The whole name, like `pgrep -x`.
+    #[default]
+    Exact,
+    /// Names starting with the wanted one: "gzdoom" finds "gzdoom-g4.11".
+    Prefix,
+    /// Names containing it anywhere: "doom" finds "dsda-doom" and "gzdoom".
+    Substring,
+}
+
+/// name_matches under `mode`.
+pub fn name_matches_mode(candidate: &str, wanted: &str, mode: MatchMode, windows_rules: bool) -> bool {
+    if mode == MatchMode::Exact {
+        return name_matches(candidate, wanted, windows_rules);
+    }
+    let base = candidate.rsplit(['/', '\\']).next().unwrap_or(candidate);
+    let (base, wanted) = if windows_rules {
+        let strip = |s: &str| {
+            let lower = s.to_lowercase();
+            lower.strip_suffix(".exe").map(str::to_string).unwrap_or(lower)
+        };
+        (strip(base), strip(wanted))
+    } else {
+        (base.to_string(), wanted.to_string())
+    };
+    match mode {
+        MatchMode::Prefix => base.starts_with(&wanted),
+        _ => base.contains(&wanted),
+    }
+}
+
+/// Start of the error returned when no way of listing processes works here.
+pub const DETECTION_UNSUPPORTED: &str = "Process detection unsupported on this platform";
+
+type Strategy = (&'static str, fn() -> Result<Vec<String>, String>);
+
+/// The strategy that answered last, so the log says which one is in use
+/// once rather than on every poll.
+static LAST_STRATEGY: Mutex<Option<&'static str>> = Mutex::new(None);
+
+/// Process names from the first strategy that works, or
+/// DETECTION_UNSUPPORTED with every strategy's failure.
+fn names_from(strategies: &[Strategy]) -> Result<Vec<String>, String> {
+    let mut failures = Vec::new();
+    for (label, list) in strategies {
+        match list() {
+            Ok(names) => {
+                let mut last = LAST_STRATEGY.lock().unwrap();
+                if *last != Some(*label) {
+                    eprintln!("Process detection: using {}", label);
+                    *last = Some(*label);
+                }
+                return Ok(names);
+            }
+            Err(e) => failures.push(e),
+        }
+    }
+    Err(format!("{} ({})", DETECTION_UNSUPPORTED, failures.join("; ")))
+}
+
+/// True if any running process's executable is named `name`.
+pub fn is_process_running(name: &str) -> Result<bool, String> {
+    is_any_process_running(&[name.to_string()], MatchMode::Exact)
+}
+
+/// True if any running process's executable matches one of `wanted`
+/// under `mode`, for a fork that may run under several names.
+pub fn is_any_process_running(wanted: &[String], mode: MatchMode) -> Result<bool, String> {
+    let names = names_from(STRATEGIES)?;
+    Ok(names.iter().any(|n| wanted.iter().any(|w| name_matches_mode(n, w, mode, cfg!(windows)))))
+}
+
+/// Start of the message of the error a launch returns when asked to check
+/// for a running copy of the engine and finding one.
+pub const ALREADY_RUNNING: &str = "Already running";
+
+/// Fail with LaunchError::AlreadyRunning if a process with `engine_path`'s
+/// executable name is running, launched by this app or not. The UI offers
+/// "launch anyway" for it instead of showing a failure.
+pub fn check_not_running(engine_path: &str) -> Result<(), LaunchError> {
+    let name = engine_path.rsplit(['/', '\\']).next().unwrap_or(engine_path);
+    if is_process_running(name)? {
+        return Err(LaunchError::AlreadyRunning {
+            path: engine_path.to_string(),
+            message: format!("{}: {} is already open", ALREADY_RUNNING, name),
+        });
+    }
+    Ok(())
+}
+
+/// Ask a process to exit: SIGTERM on Unix, so the engine can save its
+/// config. Windows has no polite equivalent for console-less GUI apps, so
+/// there this is the same as force_kill.
+#[cfg(unix)]
+pub fn terminate(pid: u32) -> Result<(), String> {
+    send_signal(pid, libc::SIGTERM)
+}
+
+#[cfg(windows)]
+pub fn terminate(pid: u32) -> Result<(), String> {
+    force_kill(pid)
+}
+
+/// Kill a process outright: SIGKILL on Unix, TerminateProcess on Windows.
+#[cfg(unix)]
+pub fn force_kill(pid: u32) -> Result<(), String> {
+    send_signal(pid, libc::SIGKILL)
+}
+
+#[cfg(windows)]
+pub fn force_kill(pid: u32) -> Result<(), String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_TERMINATE, TerminateProcess};
+    unsafe {
+        let handle = OpenProcess(PROCESS_TERMINATE, false, pid)
+            .map_err(|e| format!("Failed to open process {}: {}", pid, e))?;
+        let result = TerminateProcess(handle, 1)
+            .map_err(|e| format!("Failed to terminate process {}: {}", pid, e));
+        let _ = CloseHandle(handle);
+        result
+    }
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int) -> Result<(), String> {
+    let pid = libc::pid_t::try_from(pid).map_err(|_| format!("Invalid process id {}", pid))?;
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to signal process {}: {}",
+            pid,
+            std::io::Error::last_os_error()
+        ))
+    }
+}
+
+#[cfg(target_os = "linux")]
+const STRATEGIES: &[Strategy] = &[("/proc", proc_process_names), ("ps", ps_process_names)];
+
+#[cfg(target_os = "windows")]
+const STRATEGIES: &[Strategy] = &[("tasklist", tasklist_process_names), ("PowerShell", powershell_process_names)];
+
+#[cfg(all(unix, not(target_os = "linux")))]
+const STRATEGIES: &[Strategy] = &[("ps", ps_process_names)];
+
+#[cfg(not(any(unix, windows)))]
+const STRATEGIES: &[Strategy] = &[];
+
+#[cfg(target_os = "linux")]
+fn proc_process_names() -> Result<Vec<String>, String> {
+    let entries =
+        std::fs::read_dir("/proc").map_err(|e| format!("Failed to read /proc: {}", e))?;
+    let mut names = Vec::new();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        if !file_name.to_string_lossy().bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        let dir = entry.path();
+        // comm is truncated to 15 bytes, so also take argv[0]'s basename for
+        // longer names (prboom-plus-2.6.66). Processes can exit mid-scan, so
+        // unreadable entries are skipped rather than failing the whole check.
+        if let Ok(comm) = std::fs::read_to_string(dir.join("comm")) {
+            names.push(comm.trim_end().to_string());
+        }
+        if let Ok(cmdline) = std::fs::read(dir.join("cmdline"))
+            && let Some(argv0) = cmdline.split(|&b| b == 0).next()
+            && !argv0.is_empty()
+        {
+            names.push(String::from_utf8_lossy(argv0).into_owned());
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(unix)]
+fn ps_process_names() -> Result<Vec<String>, String> {
+    // comm= prints the full executable path without a header line.
+    let output = std::process::Command::new("ps")
+        .args(["-axo", "comm="])
+        .output()
+        .map_err(|e| format!("Failed to run ps: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("ps exited with {}", output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+/// Run a console tool without flashing a console window from the GUI app.
+#[cfg(target_os = "windows")]
+fn hidden_output(program: &str, args: &[&str]) -> Result<std::process::Output, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let output = std::process::Command::new(program)
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    Ok(output)
+}
+
+#[cfg(target_os = "windows")]
+fn tasklist_process_names() -> Result<Vec<String>, String> {
+    let output = hidden_output("tasklist", &["/FO", "CSV", "/NH"])?;
+    Ok(parse_tasklist_csv(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Process names without ".exe", which name_matches ignores on Windows.
+#[cfg(target_os = "windows")]
+fn powershell_process_names() -> Result<Vec<String>, String> {
+    let output = hidden_output(
+        "powershell",
+        &["-NoProfile", "-NonInteractive", "-Command", "Get-Process | ForEach-Object { $_.ProcessName }"],
+    )?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect())
+}
+
+/// Image names from `tasklist /FO CSV /NH` output, e.g.
+/// `"gzdoom.exe","1234","Console","1","120,000 K"` -> "gzdoom.exe".
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_tasklist_csv(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix('"')?;
+            let end = rest.find('"')?;
+            Some(rest[..end].to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_basename_exactly_on_unix() {
+        assert!(name_matches("/Applications/GZDoom.app/Contents/MacOS/gzdoom", "gzdoom", false));
+        assert!(name_matches("gzdoom", "gzdoom", false));
+        assert!(!name_matches("gzdoom-helper", "gzdoom", false));
+        assert!(!name_matches("GZDoom", "gzdoom", false));
+    }
+
+    #[test]
+    fn windows_rules_ignore_case_and_exe_suffix() {
+        assert!(name_matches("GZDoom.exe", "gzdoom", true));
+        assert!(name_matches("gzdoom.exe", "gzdoom.EXE", true));
+        assert!(name_matches("C:\\Games\\UZDoom.exe", "uzdoom", true));
+        assert!(!name_matches("gzdoom-helper.exe", "gzdoom", true));
+    }
+
+    #[test]
+    fn looser_modes_find_versioned_builds() {
+        assert!(name_matches_mode("/opt/gzdoom-g4.11/gzdoom-g4.11", "gzdoom", MatchMode::Prefix, false));
+        assert!(!name_matches_mode("gzdoom-g4.11", "gzdoom", MatchMode::Exact, false));
+        assert!(!name_matches_mode("my-gzdoom", "gzdoom", MatchMode::Prefix, false));
+        assert!(name_matches_mode("my-gzdoom", "gzdoom", MatchMode::Substring, false));
+        assert!(name_matches_mode("C:\\Games\\GZDoom-Dev.exe", "gzdoom.exe", MatchMode::Prefix, true));
+        assert!(!name_matches_mode("GZDoom-dev", "gzdoom", MatchMode::Substring, false));
+    }
+
+    #[test]
+    fn parses_tasklist_csv_rows() {
+        let out = "\"System Idle Process\",\"0\",\"Services\",\"0\",\"8 K\"\r\n\
+                   \"gzdoom.exe\",\"4312\",\"Console\",\"1\",\"210,444 K\"\r\n";
+        assert_eq!(parse_tasklist_csv(out), vec!["System Idle Process", "gzdoom.exe"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn terminate_then_force_kill_child() {
+        let mut child = std::process::Command::new("sleep").arg("30").spawn().unwrap();
+        terminate(child.id()).unwrap();
+        let status = child.wait().unwrap();
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+        // u32::MAX must not wrap to pid -1, which would signal every process.
+        assert!(force_kill(u32::MAX).is_err());
+    }
+
+    #[test]
+    fn falls_back_through_strategies() {
+        fn broken() -> Result<Vec<String>, String> {
+            Err("Failed to read /proc: permission denied".to_string())
+        }
+        fn working() -> Result<Vec<String>, String> {
+            Ok(vec!["gzdoom".to_string()])
+        }
+        assert_eq!(names_from(&[("broken", broken), ("working", working)]).unwrap(), vec!["gzdoom"]);
+        let err = names_from(&[("broken", broken)]).unwrap_err();
+        assert!(err.starts_with(DETECTION_UNSUPPORTED) && err.contains("permission denied"), "{}", err);
+        assert!(names_from(&[]).unwrap_err().starts_with(DETECTION_UNSUPPORTED));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_own_test_process() {
+        let exe = std::env::current_exe().unwrap();
+        let name = exe.file_name().unwrap().to_string_lossy().to_string();
+        assert!(is_process_running(&name).unwrap());
+        assert!(!is_process_running("definitely-not-a-running-engine").unwrap());
+        let err = check_not_running(&exe.to_string_lossy()).unwrap_err();
+        assert!(matches!(err, LaunchError::AlreadyRunning { .. }) && err.to_string().starts_with(ALREADY_RUNNING), "{}", err);
+        assert!(check_not_running("/usr/games/definitely-not-a-running-engine").is_ok());
+    }
+}
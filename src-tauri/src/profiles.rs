@@ -0,0 +1,245 @@
+// Named launch profiles: an engine plus a LaunchConfig, saved so a favourite
+// IWAD + mod + flags combination is one click away. Stored as
+// profiles.json in the app config dir, a json_store file like
+// launcher-downloads.json.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::demo;
+use crate::engine::EngineKind;
+use crate::json_store;
+use crate::launch_config::LaunchConfig;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub name: String,
+    pub engine_path: String,
+    /// Explicit engine type for renamed binaries; inferred from the path
+    /// when absent, as in launch_gzdoom.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub engine_kind: Option<EngineKind>,
+    pub config: LaunchConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profiles {
+    pub version: u8,
+    pub profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    pub fn empty() -> Self {
+        Self {
+            version: 1,
+            profiles: Vec::new(),
+        }
+    }
+}
+
+/// Serializes read-modify-write cycles, so two saves in flight can't drop
+/// each other's profile.
+static PROFILES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Build the full path to profiles.json in the app config directory.
+pub fn profiles_path(config_dir: impl AsRef<Path>) -> PathBuf {
+    config_dir.as_ref().join("profiles.json")
+}
+
+/// Read profiles.json, returning an empty state if the file is missing.
+pub fn read_profiles_or_empty(path: impl AsRef<Path>) -> Result<Profiles, String> {
+    json_store::read_or_empty(path.as_ref(), Profiles::empty)
+}
+
+/// Write profiles.json to disk, creating parent directories if needed.
+pub fn write_profiles(path: impl AsRef<Path>, state: &Profiles) -> Result<(), String> {
+    json_store::write(path.as_ref(), state, "profiles")
+}
+
+/// Save `profile`, replacing any existing profile with the same name.
+pub fn save_profile(path: impl AsRef<Path>, profile: Profile) -> Result<(), String> {
+    if profile.name.trim().is_empty() {
+        return Err("Profile name is empty".to_string());
+    }
+    let _guard = PROFILES_LOCK.lock().unwrap();
+    let mut state = read_profiles_or_empty(&path)?;
+    match state.profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => state.profiles.push(profile),
+    }
+    write_profiles(&path, &state)
+}
+
+/// All saved profiles, in the order they were first saved.
+pub fn list_profiles(path: impl AsRef<Path>) -> Result<Vec<Profile>, String> {
+    let _guard = PROFILES_LOCK.lock().unwrap();
+    Ok(read_profiles_or_empty(path)?.profiles)
+}
+
+pub fn load_profile(path: impl AsRef<Path>, name: &str) -> Result<Profile, String> {
+    list_profiles(path)?
+        .into_iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("No profile named '{}'", name))
+}
+
+/// A profile name as a file name, with characters unsafe there replaced.
+fn file_stem(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
+        .collect()
+}
+
+/// Default engine config for a profile: configs/<name>.ini in the app
+/// config dir.
+pub fn profile_config_path(config_dir: impl AsRef<Path>, name: &str) -> PathBuf {
+    config_dir.as_ref().join("configs").join(format!("{}.ini", file_stem(name)))
+}
+
+/// Default save directory for a profile: saves/<name> in the app data dir.
+pub fn profile_savedir_path(data_dir: impl AsRef<Path>, name: &str) -> PathBuf {
+    data_dir.as_ref().join("saves").join(file_stem(name))
+}
+
+/// Give a profile its own save directory, so runs of different megawads
+/// don't overwrite each other's slots, and record it as the profile's
+/// savedir. Keeps a directory the profile already has; otherwise uses
+/// profile_savedir_path. The directory is created and checked writable.
+/// Returns its path.
+pub fn prepare_profile_savedir(
+    path: impl AsRef<Path>,
+    data_dir: impl AsRef<Path>,
+    name: &str,
+) -> Result<String, String> {
+    let _guard = PROFILES_LOCK.lock().unwrap();
+    let mut state = read_profiles_or_empty(&path)?;
+    let profile = state
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("No profile named '{}'", name))?;
+    let savedir = match &profile.config.savedir {
+        Some(existing) => PathBuf::from(existing),
+        None => profile_savedir_path(&data_dir, name),
+    };
+    fs::create_dir_all(&savedir).map_err(|e| format!("Failed to create {}: {}", savedir.display(), e))?;
+    demo::check_writable_dir(&savedir, "Save directory")?;
+    let resolved = savedir.to_string_lossy().to_string();
+    profile.config.savedir = Some(resolved.clone());
+    write_profiles(&path, &state)?;
+    Ok(resolved)
+}
+
+/// Give a profile its own engine config, so one mod's keybinds don't
+/// overwrite another's, and record it as the profile's `-config`. Keeps a
+/// path the profile already has; otherwise uses profile_config_path. A
+/// missing file is copied from `base` (e.g. the engine's default config)
+/// when given, else left for the engine to create on first run. Returns the
+/// config path.
+pub fn prepare_profile_config(
+    path: impl AsRef<Path>,
+    config_dir: impl AsRef<Path>,
+    name: &str,
+    base: Option<&str>,
+) -> Result<String, String> {
+    let _guard = PROFILES_LOCK.lock().unwrap();
+    let mut state = read_profiles_or_empty(&path)?;
+    let profile = state
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == name)
+        .ok_or_else(|| format!("No profile named '{}'", name))?;
+    let config_path = match &profile.config.config_path {
+        Some(existing) => PathBuf::from(existing),
+        None => profile_config_path(&config_dir, name),
+    };
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    if let Some(base) = base
+        && !config_path.exists()
+    {
+        fs::copy(base, &config_path)
+            .map_err(|e| format!("Failed to copy {} to {}: {}", base, config_path.display(), e))?;
+    }
+    let resolved = config_path.to_string_lossy().to_string();
+    profile.config.config_path = Some(resolved.clone());
+    write_profiles(&path, &state)?;
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, iwad: &str) -> Profile {
+        Profile {
+            name: name.into(),
+            engine_path: "/usr/bin/gzdoom".into(),
+            engine_kind: None,
+            config: LaunchConfig {
+                iwad: iwad.into(),
+                pwads: vec!["/wads/sunlust.wad".into()],
+                skill: Some(4),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn saves_replaces_and_loads_profiles() {
+        let dir = std::env::temp_dir().join(format!("profiles_test_{}", std::process::id()));
+        let path = profiles_path(&dir);
+        assert!(list_profiles(&path).unwrap().is_empty());
+
+        save_profile(&path, profile("Sunlust UV", "/iwads/DOOM2.WAD")).unwrap();
+        save_profile(&path, profile("Other", "/iwads/DOOM.WAD")).unwrap();
+        save_profile(&path, profile("Sunlust UV", "/iwads/PLUTONIA.WAD")).unwrap();
+
+        let names: Vec<String> = list_profiles(&path).unwrap().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["Sunlust UV", "Other"]);
+        assert_eq!(load_profile(&path, "Sunlust UV").unwrap().config.iwad, "/iwads/PLUTONIA.WAD");
+        assert!(load_profile(&path, "Missing").unwrap_err().contains("No profile"));
+        assert!(save_profile(&path, profile(" ", "/iwads/DOOM.WAD")).is_err());
+    }
+
+    #[test]
+    fn gives_each_profile_its_own_config() {
+        let dir = std::env::temp_dir().join(format!("profiles_cfg_test_{}", std::process::id()));
+        let path = profiles_path(&dir);
+        save_profile(&path, profile("Brutal/UV", "/iwads/DOOM2.WAD")).unwrap();
+        save_profile(&path, profile("Vanilla", "/iwads/DOOM2.WAD")).unwrap();
+        let base = dir.join("gzdoom.ini");
+        fs::write(&base, "[Doom.Bindings]\nw=+forward\n").unwrap();
+
+        let brutal = prepare_profile_config(&path, &dir, "Brutal/UV", base.to_str()).unwrap();
+        assert_eq!(PathBuf::from(&brutal), dir.join("configs").join("Brutal_UV.ini"));
+        assert_eq!(fs::read_to_string(&brutal).unwrap(), "[Doom.Bindings]\nw=+forward\n");
+        assert_eq!(load_profile(&path, "Brutal/UV").unwrap().config.config_path, Some(brutal.clone()));
+
+        // An existing config is kept, not re-cloned over the user's changes.
+        fs::write(&brutal, "changed").unwrap();
+        assert_eq!(prepare_profile_config(&path, &dir, "Brutal/UV", base.to_str()).unwrap(), brutal);
+        assert_eq!(fs::read_to_string(&brutal).unwrap(), "changed");
+
+        let vanilla = prepare_profile_config(&path, &dir, "Vanilla", None).unwrap();
+        assert!(!PathBuf::from(&vanilla).exists());
+        assert!(prepare_profile_config(&path, &dir, "Missing", None).is_err());
+    }
+
+    #[test]
+    fn gives_each_profile_its_own_savedir() {
+        let dir = std::env::temp_dir().join(format!("profiles_save_test_{}", std::process::id()));
+        let path = profiles_path(&dir);
+        save_profile(&path, profile("Sunlust UV", "/iwads/DOOM2.WAD")).unwrap();
+        let savedir = prepare_profile_savedir(&path, &dir, "Sunlust UV").unwrap();
+        assert_eq!(PathBuf::from(&savedir), dir.join("saves").join("Sunlust UV"));
+        assert!(PathBuf::from(&savedir).is_dir());
+        assert_eq!(load_profile(&path, "Sunlust UV").unwrap().config.savedir, Some(savedir.clone()));
+        assert_eq!(prepare_profile_savedir(&path, &dir, "Sunlust UV").unwrap(), savedir);
+    }
+}
@@ -0,0 +1,117 @@
+// Quick test for mappers: start the engine warped to one map and report
+// whether it loaded, so edit, save and check is a single click.
+// ZDoom-family ports announce each level they start with a banner line,
+//
+//     MAP07 - Dead Simple
+//
+// and seeing it counts as loaded, while an error line ("Script error, …")
+// or the engine exiting first counts as failed. Other ports print nothing
+// when a level starts; for them the map counts as loaded if the engine is
+// still running without errors when the timeout is up.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::log_analysis::{self, Severity};
+use crate::session::{GZDoomSession, SessionId};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickTestResult {
+    pub session_id: SessionId,
+    pub map: String,
+    pub loaded: bool,
+    /// From spawn until the banner, the error or the exit; the whole wait
+    /// when none of them came.
+    pub elapsed_ms: u64,
+    /// Why the map didn't load: the error line, the exit or the timeout.
+    pub failure: Option<String>,
+    /// Whether the engine is still running.
+    pub running: bool,
+}
+
+pub const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Watch a just-started session's log until `map`'s banner, an error line,
+/// the exit or `timeout`. `expect_banner` is whether the port prints level
+/// banners; without one, outlasting the timeout is success.
+pub fn wait_for_map(session: &Mutex<GZDoomSession>, map: &str, timeout: Duration, expect_banner: bool) -> QuickTestResult {
+    let start = Instant::now();
+    let mut next = 0u64;
+    let result = |guard: &GZDoomSession, elapsed_ms: u64, failure: Option<String>| QuickTestResult {
+        session_id: guard.id,
+        map: map.to_string(),
+        loaded: failure.is_none(),
+        elapsed_ms,
+        failure,
+        running: !guard.finished,
+    };
+    loop {
+        {
+            let guard = session.lock().unwrap();
+            let skip = next.saturating_sub(guard.dropped_lines) as usize;
+            for (ms, line, _) in guard.lines.iter().skip(skip) {
+                if log_analysis::banner_map(line).as_deref() == Some(map) {
+                    return result(&guard, *ms, None);
+                }
+                if log_analysis::classify(line) == Severity::Error {
+                    return result(&guard, *ms, Some(line.trim().to_string()));
+                }
+            }
+            next = guard.dropped_lines + guard.lines.len() as u64;
+            // Lines are drained before a session is marked finished, so all
+            // of them have been seen by now.
+            if guard.finished {
+                let exit = match (guard.exit_code, guard.signal) {
+                    (Some(code), _) => format!("The engine exited with code {} before {} loaded", code, map),
+                    (None, Some(signal)) => format!("The engine was killed by signal {} before {} loaded", signal, map),
+                    (None, None) => format!("The engine exited before {} loaded", map),
+                };
+                return result(&guard, start.elapsed().as_millis() as u64, Some(exit));
+            }
+            if start.elapsed() >= timeout {
+                let failure = expect_banner.then(|| format!("{} didn't start within {} ms", map, timeout.as_millis()));
+                return result(&guard, start.elapsed().as_millis() as u64, failure);
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::session::Sessions;
+    use std::sync::Arc;
+
+    fn run(sessions: &Sessions, script: &str, expect_banner: bool) -> QuickTestResult {
+        let id = sessions.start("/bin/sh", &["-c".to_string(), script.to_string()], Arc::new(|_| {})).unwrap();
+        wait_for_map(&sessions.get(id).unwrap(), "MAP07", Duration::from_millis(500), expect_banner)
+    }
+
+    #[test]
+    fn reports_whether_the_map_loaded() {
+        let sessions = Sessions::new();
+        let loaded = run(&sessions, "echo W_Init; echo 'MAP07 - Dead Simple'; exec sleep 5", true);
+        assert_eq!((loaded.loaded, loaded.running, loaded.failure.as_deref()), (true, true, None));
+        sessions.kill(loaded.session_id).unwrap();
+
+        let error = run(&sessions, "echo 'Script error, \"map.wad:SCRIPTS\" line 3:'; exec sleep 5", true);
+        assert_eq!(error.failure.as_deref(), Some("Script error, \"map.wad:SCRIPTS\" line 3:"));
+        sessions.kill(error.session_id).unwrap();
+
+        let exited = run(&sessions, "echo 'MAP01 - Entryway'; exit 3", true);
+        assert_eq!(exited.failure.as_deref(), Some("The engine exited with code 3 before MAP07 loaded"));
+        assert!(!exited.running);
+
+        for expect_banner in [true, false] {
+            let silent = run(&sessions, "exec sleep 5", expect_banner);
+            assert_eq!(silent.loaded, !expect_banner);
+            assert_eq!(silent.failure.is_some_and(|f| f.contains("didn't start within 500 ms")), expect_banner);
+            sessions.kill(silent.session_id).unwrap();
+        }
+    }
+}
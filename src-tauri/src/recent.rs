@@ -0,0 +1,124 @@
+// Recently played files for the home screen, newest first. Stored as
+// recent.json in the app config dir, a json_store file like profiles.json.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::json_store;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentEntry {
+    /// Canonical path, so one file reached through different paths is one
+    /// entry.
+    pub path: String,
+    /// Milliseconds since the Unix epoch.
+    pub last_played_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recent {
+    pub version: u8,
+    pub entries: Vec<RecentEntry>,
+}
+
+impl Recent {
+    pub fn empty() -> Self {
+        Self {
+            version: 1,
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// Entries kept; adding one more drops the least recently played.
+pub const MAX_RECENT: usize = 20;
+
+/// Serializes read-modify-write cycles, as for profiles.
+static RECENT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Build the full path to recent.json in the app config directory.
+pub fn recent_path(config_dir: impl AsRef<Path>) -> PathBuf {
+    config_dir.as_ref().join("recent.json")
+}
+
+/// Read recent.json, returning an empty list if the file is missing.
+fn read_recent_or_empty(path: &Path) -> Result<Recent, String> {
+    json_store::read_or_empty(path, Recent::empty)
+}
+
+fn write_recent(path: &Path, state: &Recent) -> Result<(), String> {
+    json_store::write(path, state, "recent files")
+}
+
+/// Record `file` as just played: it moves to the front (or is added there)
+/// with the current time, and the list is cut to MAX_RECENT. Errors if the
+/// file doesn't exist.
+pub fn add_recent(path: impl AsRef<Path>, file: &str) -> Result<(), String> {
+    let canonical = fs::canonicalize(file)
+        .map_err(|e| format!("Failed to resolve {}: {}", file, e))?
+        .to_string_lossy()
+        .to_string();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("System clock error: {}", e))?
+        .as_millis() as u64;
+    let _guard = RECENT_LOCK.lock().unwrap();
+    let mut state = read_recent_or_empty(path.as_ref())?;
+    state.entries.retain(|e| e.path != canonical);
+    state.entries.insert(0, RecentEntry { path: canonical, last_played_ms: now });
+    state.entries.truncate(MAX_RECENT);
+    write_recent(path.as_ref(), &state)
+}
+
+/// Recently played files, most recent first.
+pub fn get_recent(path: impl AsRef<Path>) -> Result<Vec<RecentEntry>, String> {
+    let _guard = RECENT_LOCK.lock().unwrap();
+    Ok(read_recent_or_empty(path.as_ref())?.entries)
+}
+
+pub fn clear_recent(path: impl AsRef<Path>) -> Result<(), String> {
+    let _guard = RECENT_LOCK.lock().unwrap();
+    write_recent(path.as_ref(), &Recent::empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_newest_first_deduped_and_capped() {
+        let dir = std::env::temp_dir().join(format!("recent_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = recent_path(dir.join("config"));
+        let wads: Vec<String> = (0..MAX_RECENT + 1)
+            .map(|i| {
+                let wad = dir.join(format!("{}.wad", i));
+                fs::write(&wad, b"PWAD").unwrap();
+                wad.to_string_lossy().to_string()
+            })
+            .collect();
+        for wad in &wads {
+            add_recent(&store, wad).unwrap();
+        }
+        // The same file by another path moves to the front instead of
+        // being listed twice.
+        let second = dir.join("config").join("..").join("1.wad");
+        add_recent(&store, &second.to_string_lossy()).unwrap();
+
+        let entries = get_recent(&store).unwrap();
+        assert_eq!(entries.len(), MAX_RECENT);
+        let canonical = |wad: &str| fs::canonicalize(wad).unwrap().to_string_lossy().to_string();
+        assert_eq!(entries[0].path, canonical(&wads[1]));
+        assert_eq!(entries[1].path, canonical(&wads[MAX_RECENT]));
+        assert!(!entries.iter().any(|e| e.path == canonical(&wads[0])));
+        assert!(entries[0].last_played_ms >= entries[1].last_played_ms);
+
+        assert!(add_recent(&store, "/definitely/not/here.wad").is_err());
+        clear_recent(&store).unwrap();
+        assert_eq!(get_recent(&store).unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,126 @@
+// Showing a file in the OS file manager: Finder and Explorer select it in
+// its folder; on Linux there's no common "select" call, so xdg-open opens
+// the folder that holds it. Text files (saved logs) can also be opened in
+// the OS text editor.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The folder xdg-open shows for `path`: the path itself for a directory,
+/// else its parent.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn containing_folder(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        return path.to_path_buf();
+    }
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn reveal_command(path: &Path) -> Command {
+    let mut command = Command::new("open");
+    command.arg("-R").arg(path);
+    command
+}
+
+#[cfg(target_os = "windows")]
+fn reveal_command(path: &Path) -> Command {
+    use std::os::windows::process::CommandExt;
+    // Explorer parses its own command line: the path must be quoted inside
+    // the /select, argument, which std's quoting would wrap whole.
+    let mut command = Command::new("explorer");
+    command.raw_arg(format!("/select,\"{}\"", path.display()));
+    command
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn reveal_command(path: &Path) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(containing_folder(path));
+    command
+}
+
+/// `open -t` picks the user's default text editor rather than the app
+/// registered for the extension.
+#[cfg(target_os = "macos")]
+fn editor_command(path: &Path) -> Command {
+    let mut command = Command::new("open");
+    command.arg("-t").arg(path);
+    command
+}
+
+/// Windows has no "default text editor" to ask for; Notepad is always there.
+#[cfg(target_os = "windows")]
+fn editor_command(path: &Path) -> Command {
+    let mut command = Command::new("notepad");
+    command.arg(path);
+    command
+}
+
+/// xdg-open picks the app for the file's type, a text editor for .log.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn editor_command(path: &Path) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(path);
+    command
+}
+
+/// Start `command` for `path` without waiting for it. `what` names the
+/// program in errors ("file manager").
+fn spawn_detached(mut command: Command, path: &Path, what: &'static str) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("Path not found: {}", path.display()));
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to open {} for {}: {}", what, path.display(), e))?;
+    // Reap it so it doesn't linger as a zombie. Explorer exits with 1 even
+    // when it worked, so the status is only logged.
+    let shown = path.display().to_string();
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() && !cfg!(windows) => {
+            eprintln!("{} exited with {} opening {}", what, status, shown)
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to wait for {} opening {}: {}", what, shown, e),
+    });
+    Ok(())
+}
+
+/// Show `path` in the file manager. Errors if the path doesn't exist or the
+/// file manager can't be started; the file manager is not waited for.
+pub fn reveal_in_file_manager(path: &str) -> Result<(), String> {
+    let path = Path::new(path);
+    spawn_detached(reveal_command(path), path, "file manager")
+}
+
+/// Open a text file in the OS text editor, not waiting for it. Errors as
+/// reveal_in_file_manager does.
+pub fn open_in_text_editor(path: &str) -> Result<(), String> {
+    let path = Path::new(path);
+    spawn_detached(editor_command(path), path, "text editor")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveals_only_existing_paths() {
+        let err = reveal_in_file_manager("/definitely/not/here.wad").unwrap_err();
+        assert!(err.starts_with("Path not found"), "{}", err);
+        let err = open_in_text_editor("/definitely/not/here.log").unwrap_err();
+        assert!(err.starts_with("Path not found"), "{}", err);
+    }
+
+    #[test]
+    fn opens_the_folder_holding_a_file() {
+        let dir = std::env::temp_dir();
+        assert_eq!(containing_folder(&dir), dir);
+        assert_eq!(containing_folder(&dir.join("missing.wad")), dir);
+        assert_eq!(containing_folder(Path::new("doom2.wad")), PathBuf::from("."));
+    }
+}
@@ -0,0 +1,915 @@
+// Engine sessions: one spawned engine process plus its captured output.
+//
+// Every launch gets a fresh Arc<Mutex<GZDoomSession>> that its reader and
+// wait threads capture by value, so output from one run can only ever land
+// in that run's session. Sessions are keyed by a SessionId returned from
+// the launch and carried by every event, so several engines (a dedicated
+// server and a client, two ports side by side) can run at once.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+
+use crate::launch_error::LaunchError;
+use crate::process;
+use crate::startup_banner;
+use crate::timestamp;
+
+/// Identifies one launch. Ids increase, so a larger id is a later launch.
+pub type SessionId = u64;
+
+/// The output stream a log line came from. GZDoom prints some diagnostics
+/// only to stderr.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// A captured line: ms since spawn, text, stream.
+pub type LogLine = (u64, String, Stream);
+
+pub struct GZDoomSession {
+    pub id: SessionId,
+    pub engine_path: String,
+    start_time: std::time::Instant,
+    /// Wall-clock time of the spawn; line times are relative to it.
+    started_at: std::time::SystemTime,
+    /// (time_ms, line, stream), at most max_lines of the most recent, in
+    /// the order the readers got them.
+    pub lines: VecDeque<LogLine>,
+    max_lines: usize,
+    /// Lines dropped from the front once the cap was reached.
+    pub dropped_lines: u64,
+    /// Time of the latest output line, for the startup watchdog; None until
+    /// the engine prints anything.
+    last_output_ms: Option<u64>,
+    pub finished: bool,
+    // Set from child.wait() once the process is gone. exit_code is None when
+    // the process was killed by a signal (Unix), in which case signal is set.
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub pid: Option<u32>, // set while the process is alive; used by kill
+    /// Stopped by Sessions::kill (the user, or a finished quick test)
+    /// rather than exiting on its own.
+    pub killed: bool,
+    /// Working directory the engine was started in, when one was set.
+    pub cwd: Option<String>,
+}
+
+impl GZDoomSession {
+    fn new(id: SessionId, engine_path: &str, max_lines: usize) -> Self {
+        Self {
+            id,
+            engine_path: engine_path.to_string(),
+            start_time: std::time::Instant::now(),
+            started_at: std::time::SystemTime::now(),
+            lines: VecDeque::new(),
+            max_lines,
+            dropped_lines: 0,
+            last_output_ms: None,
+            finished: false,
+            exit_code: None,
+            signal: None,
+            pid: None,
+            killed: false,
+            cwd: None,
+        }
+    }
+
+    /// Wall-clock time of the spawn, for finding files the engine wrote.
+    pub fn started_at(&self) -> std::time::SystemTime {
+        self.started_at
+    }
+
+    fn log_result(&self) -> GZDoomLogResult {
+        GZDoomLogResult {
+            session_id: self.id,
+            started_at: timestamp::iso8601(self.started_at),
+            lines: self.lines.iter().cloned().collect(),
+            dropped_lines: self.dropped_lines,
+            exit_code: self.exit_code,
+            signal: self.signal,
+        }
+    }
+
+    fn exit_summary(&self) -> ExitSummary {
+        ExitSummary {
+            session_id: self.id,
+            exit_code: self.exit_code,
+            signal: self.signal,
+            runtime_ms: self.start_time.elapsed().as_millis() as u64,
+            line_count: self.lines.len() as u64 + self.dropped_lines,
+        }
+    }
+
+    fn push_line(&mut self, elapsed: u64, line: String, stream: Stream) {
+        self.last_output_ms = Some(elapsed);
+        if self.max_lines == 0 {
+            self.dropped_lines += 1;
+            return;
+        }
+        if self.lines.len() >= self.max_lines {
+            self.lines.pop_front();
+            self.dropped_lines += 1;
+        }
+        self.lines.push_back((elapsed, line, stream));
+    }
+}
+
+/// Finished-session log handed to the frontend by get_gzdoom_log.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GZDoomLogResult {
+    pub session_id: SessionId,
+    /// Wall-clock time of the spawn as ISO-8601 UTC
+    /// ("2026-10-14T17:46:40.123Z"); add a line's ms to get its time.
+    pub started_at: String,
+    pub lines: Vec<LogLine>,
+    /// How many earlier lines were dropped to stay under the cap.
+    pub dropped_lines: u64,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+}
+
+/// Payload of the exit event, enough for the UI to show a crash without
+/// fetching the whole log.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitSummary {
+    pub session_id: SessionId,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    /// From spawn until the output was drained.
+    pub runtime_ms: u64,
+    /// All lines the engine printed, including ones dropped by the cap.
+    pub line_count: u64,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// "[mm:ss.mmm] line" per line, for pasting into bug reports.
+    Text,
+    /// The GZDoomLogResult as get_gzdoom_log returns it.
+    Json,
+}
+
+impl GZDoomLogResult {
+    pub fn to_text(&self) -> String {
+        let mut out = format!("Started {}\n", self.started_at);
+        if self.dropped_lines > 0 {
+            out.push_str(&format!("... {} earlier lines omitted\n", self.dropped_lines));
+        }
+        for (ms, line, _) in &self.lines {
+            out.push_str(&format!("[{:02}:{:02}.{:03}] {}\n", ms / 60_000, ms / 1000 % 60, ms % 1000, line));
+        }
+        match (self.exit_code, self.signal) {
+            (Some(code), _) => out.push_str(&format!("Exited with code {}\n", code)),
+            (None, Some(signal)) => out.push_str(&format!("Killed by signal {}\n", signal)),
+            (None, None) => {}
+        }
+        out
+    }
+
+    /// Write the log to `path` and return the path written: the log's
+    /// extension (.log or .json) is added when the path has none.
+    pub fn save(&self, path: &str, format: LogFormat) -> Result<String, String> {
+        let mut path = std::path::PathBuf::from(path);
+        if path.extension().is_none() {
+            path.set_extension(match format {
+                LogFormat::Text => "log",
+                LogFormat::Json => "json",
+            });
+        }
+        let content = match format {
+            LogFormat::Text => self.to_text(),
+            LogFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|e| format!("Failed to serialize log: {}", e))?,
+        };
+        std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    /// Write the log as text to the temp log folder and return the path.
+    /// Opening the same session again overwrites its file with the log as
+    /// it is now.
+    pub fn save_temp(&self) -> Result<String, String> {
+        let dir = temp_log_dir();
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let path = dir.join(format!("gzdoom-session-{}.log", self.session_id));
+        self.save(&path.to_string_lossy(), LogFormat::Text)
+    }
+}
+
+/// Where open_log_in_editor writes logs: one folder under the system temp
+/// dir, emptied by cleanup_temp_logs when the app starts.
+pub fn temp_log_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join("rusted-doom-launcher-logs")
+}
+
+/// Delete the logs earlier runs left in the temp log folder. Only .log
+/// files are touched; failures are logged, since a stale log is harmless.
+pub fn cleanup_temp_logs() {
+    let dir = temp_log_dir();
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.is_file()
+            && path.extension().is_some_and(|ext| ext == "log")
+            && let Err(e) = std::fs::remove_file(&path)
+        {
+            eprintln!("Failed to remove old log {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Where a session is in its life, as session_status reports it.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum SessionStatus {
+    /// Never started, or finished and since pruned or cleared.
+    None,
+    Running,
+    /// Running, but it hasn't printed a line in the NO_OUTPUT_AFTER since
+    /// it started. GUI-only builds (macOS app bundles in particular) write
+    /// nothing to the pipes, so there is no console to show.
+    RunningNoOutput,
+    #[serde(rename_all = "camelCase")]
+    Finished { exit_code: Option<i32>, signal: Option<i32> },
+}
+
+/// Process environment for a launch, on top of the launcher's own.
+#[derive(serde::Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpawnOptions {
+    /// Extra environment variables, e.g. DOOMWADDIR.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Working directory; engines resolve relative paths against it.
+    pub cwd: Option<String>,
+    /// Report the engine as possibly hung when it prints nothing for this
+    /// long before finishing startup. Each output line restarts the wait.
+    pub startup_timeout_ms: Option<u64>,
+    /// Also kill it then, as kill_gzdoom does.
+    #[serde(default)]
+    pub kill_if_hung: bool,
+    /// Directories the launch's -iwad/-file/-deh/-bex paths must lie in;
+    /// empty allows any. See launch_args::check_args.
+    #[serde(default)]
+    pub allowed_dirs: Vec<String>,
+}
+
+impl SpawnOptions {
+    pub fn check_cwd(&self) -> Result<(), LaunchError> {
+        match &self.cwd {
+            Some(cwd) if !std::path::Path::new(cwd).is_dir() => Err(LaunchError::NotFound {
+                path: cwd.clone(),
+                message: format!("Working directory not found: {}", cwd),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Payload of the possibly-hung event.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HangWarning {
+    pub session_id: SessionId,
+    /// How long the engine had been silent.
+    pub silent_ms: u64,
+    /// Whether the engine is being killed because of it.
+    pub killed: bool,
+}
+
+/// What the session threads report while an engine runs.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    Line { session: SessionId, elapsed: u64, line: String, stream: Stream },
+    /// Sent at most once, while the engine is still starting up.
+    PossiblyHung(HangWarning),
+    /// Sent once if the engine has printed nothing NO_OUTPUT_AFTER after
+    /// starting and is still running.
+    NoOutput(SessionId),
+    Exited(ExitSummary),
+}
+
+/// Where events go: the Tauri event bus in the app, a channel in tests.
+pub type EventSink = Arc<dyn Fn(SessionEvent) + Send + Sync>;
+
+/// Terminating signal of a process killed by one, e.g. 11 for a segfault.
+#[cfg(unix)]
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+/// How long the wait thread lets the readers finish after the process exits.
+const DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How often the startup watchdog looks at a session.
+const WATCHDOG_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// How long an engine may stay silent after starting before it counts as
+/// having no console output.
+const NO_OUTPUT_AFTER: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How long kill waits after SIGTERM before escalating to SIGKILL.
+const KILL_GRACE: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Finished sessions kept for their logs; older ones are dropped on launch.
+const MAX_FINISHED_SESSIONS: usize = 16;
+
+/// Lines kept per session by default. An engine spewing script warnings for
+/// hours would otherwise grow the log without bound.
+pub const DEFAULT_MAX_LOG_LINES: usize = 50_000;
+
+/// All engine sessions of this app run, held in Tauri managed state.
+pub struct Sessions {
+    sessions: Mutex<HashMap<SessionId, Arc<Mutex<GZDoomSession>>>>,
+    last_id: AtomicU64,
+    max_lines: AtomicUsize,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sessions {
+    pub fn new() -> Self {
+        Self::with_max_lines(DEFAULT_MAX_LOG_LINES)
+    }
+
+    /// Keep at most `max_lines` of each session's output, dropping the
+    /// oldest lines first.
+    pub fn with_max_lines(max_lines: usize) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            last_id: AtomicU64::new(0),
+            max_lines: AtomicUsize::new(max_lines),
+        }
+    }
+
+    /// Change the cap for sessions started from now on; running ones keep
+    /// the one they started with.
+    pub fn set_max_lines(&self, max_lines: usize) {
+        self.max_lines.store(max_lines, Ordering::Relaxed);
+    }
+
+    pub fn get(&self, id: SessionId) -> Result<Arc<Mutex<GZDoomSession>>, String> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| format!("No engine session {}", id))
+    }
+
+    /// Spawn the engine as a new session and return its id.
+    pub fn start(&self, engine_path: &str, args: &[String], sink: EventSink) -> Result<SessionId, LaunchError> {
+        self.start_with(engine_path, args, &SpawnOptions::default(), sink)
+    }
+
+    /// Like start, with extra environment variables and a working directory.
+    pub fn start_with(
+        &self,
+        engine_path: &str,
+        args: &[String],
+        options: &SpawnOptions,
+        sink: EventSink,
+    ) -> Result<SessionId, LaunchError> {
+        options.check_cwd()?;
+        let mut command = Command::new(engine_path);
+        command.args(args).envs(&options.env);
+        if let Some(cwd) = &options.cwd {
+            command.current_dir(cwd);
+        }
+
+        let id = self.last_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let session = Arc::new(Mutex::new(GZDoomSession::new(id, engine_path, self.max_lines.load(Ordering::Relaxed))));
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| LaunchError::SpawnFailed {
+                path: engine_path.to_string(),
+                message: format!("Failed to launch engine at '{}': {}", engine_path, e),
+            })?;
+        {
+            let mut guard = session.lock().unwrap();
+            guard.pid = Some(child.id());
+            guard.cwd = options.cwd.clone();
+        }
+        {
+            let mut sessions = self.sessions.lock().unwrap();
+            prune_finished(&mut sessions);
+            sessions.insert(id, session.clone());
+        }
+
+        // Both streams feed the same session.
+        let (done_tx, done_rx) = mpsc::channel();
+        let mut readers = 0;
+        if let Some(stdout) = child.stdout.take() {
+            spawn_log_reader(stdout, Stream::Stdout, session.clone(), sink.clone(), done_tx.clone());
+            readers += 1;
+        }
+        if let Some(stderr) = child.stderr.take() {
+            spawn_log_reader(stderr, Stream::Stderr, session.clone(), sink.clone(), done_tx);
+            readers += 1;
+        }
+
+        if let Some(timeout) = options.startup_timeout_ms {
+            spawn_startup_watchdog(session.clone(), timeout, options.kill_if_hung, sink.clone());
+        }
+        spawn_no_output_check(session.clone(), sink.clone());
+
+        // Wait for process exit, give the readers a moment to drain the
+        // pipes, then mark the session finished and tell the frontend. The
+        // drain is bounded: a grandchild that inherited the pipes can keep
+        // them open indefinitely.
+        thread::spawn(move || {
+            let status = child.wait();
+            {
+                // Clear the pid the moment the child is reaped: from here on
+                // the OS may hand it to an unrelated process.
+                let mut guard = session.lock().unwrap();
+                guard.pid = None;
+                match status {
+                    Ok(status) => {
+                        guard.exit_code = status.code();
+                        guard.signal = exit_signal(&status);
+                    }
+                    Err(e) => eprintln!("Failed to wait for engine process: {}", e),
+                }
+            }
+            let deadline = std::time::Instant::now() + DRAIN_TIMEOUT;
+            for _ in 0..readers {
+                let left = deadline.saturating_duration_since(std::time::Instant::now());
+                if done_rx.recv_timeout(left).is_err() {
+                    break;
+                }
+            }
+            let summary = {
+                let mut guard = session.lock().unwrap();
+                guard.finished = true;
+                guard.exit_summary()
+            };
+            sink(SessionEvent::Exited(summary));
+        });
+        Ok(id)
+    }
+
+    /// A session's log once it has finished; None while it is still running.
+    pub fn finished_log(&self, id: SessionId) -> Result<Option<GZDoomLogResult>, String> {
+        let session = self.get(id)?;
+        let guard = session.lock().unwrap();
+        Ok(guard.finished.then(|| guard.log_result()))
+    }
+
+    /// The first `n` output lines of the latest session run with
+    /// `engine_path` — where ports print their startup banner.
+    pub fn first_lines_for(&self, engine_path: &str, n: usize) -> Vec<String> {
+        let sessions = self.sessions.lock().unwrap();
+        let latest = sessions
+            .iter()
+            .filter(|(_, s)| s.lock().unwrap().engine_path == engine_path)
+            .max_by_key(|(id, _)| **id);
+        match latest {
+            Some((_, session)) => {
+                let guard = session.lock().unwrap();
+                guard.lines.iter().take(n).map(|(_, l, _)| l.clone()).collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Whether a session is running, finished or unknown. Unlike the log
+    /// commands this never fails, so "no such session" is a state.
+    pub fn status(&self, id: SessionId) -> SessionStatus {
+        let Ok(session) = self.get(id) else {
+            return SessionStatus::None;
+        };
+        let guard = session.lock().unwrap();
+        if guard.finished {
+            SessionStatus::Finished { exit_code: guard.exit_code, signal: guard.signal }
+        } else if guard.last_output_ms.is_none() && guard.start_time.elapsed() >= NO_OUTPUT_AFTER {
+            SessionStatus::RunningNoOutput
+        } else {
+            SessionStatus::Running
+        }
+    }
+
+    /// A session's log so far, finished or not.
+    pub fn snapshot(&self, id: SessionId) -> Result<GZDoomLogResult, String> {
+        Ok(self.get(id)?.lock().unwrap().log_result())
+    }
+
+    /// The lines captured since `since_index`, finished or not, and the
+    /// index to ask for next time. Indices count every line the session
+    /// printed, so lines dropped by the cap are skipped rather than shifting
+    /// later ones; an index past the end (the log was cleared since) starts
+    /// over from the first line still held. Taken under the session lock,
+    /// so a line is either in this batch or the next.
+    pub fn log_since(&self, id: SessionId, since_index: u64) -> Result<(Vec<LogLine>, u64), String> {
+        let session = self.get(id)?;
+        let guard = session.lock().unwrap();
+        let end = guard.dropped_lines + guard.lines.len() as u64;
+        let start = match since_index > end {
+            true => guard.dropped_lines,
+            false => since_index.max(guard.dropped_lines),
+        };
+        let lines = guard.lines.iter().skip((start - guard.dropped_lines) as usize).cloned().collect();
+        Ok((lines, end))
+    }
+
+    /// Clear a session's console. A running session keeps going with an
+    /// empty log; a finished one keeps its exit status, so status and
+    /// finished_log still answer for it. Unknown ids (already pruned) are
+    /// not an error. Takes the session lock the reader threads take, so no
+    /// line is half-cleared.
+    pub fn clear(&self, id: SessionId) {
+        let Some(session) = self.sessions.lock().unwrap().get(&id).cloned() else {
+            return;
+        };
+        let mut guard = session.lock().unwrap();
+        guard.lines.clear();
+        guard.dropped_lines = 0;
+    }
+
+    /// Stop one engine: a polite terminate first, then a hard kill if it is
+    /// still alive after KILL_GRACE. The wait thread marks the session
+    /// finished (and sends Exited) once it dies. The session is marked
+    /// killed first, so its exit isn't reported as a crash.
+    pub fn kill(&self, id: SessionId) -> Result<(), String> {
+        let session = self.get(id)?;
+        session.lock().unwrap().killed = true;
+        kill_session(session.clone()).inspect_err(|_| session.lock().unwrap().killed = false)
+    }
+}
+
+fn kill_session(session: Arc<Mutex<GZDoomSession>>) -> Result<(), String> {
+    let pid = session.lock().unwrap().pid.ok_or("The engine has already exited")?;
+    process::terminate(pid)?;
+    thread::spawn(move || {
+        thread::sleep(KILL_GRACE);
+        // Re-checked under the session lock, which the wait thread takes
+        // to clear the pid right after reaping the child.
+        let guard = session.lock().unwrap();
+        if guard.pid == Some(pid)
+            && let Err(e) = process::force_kill(pid)
+        {
+            eprintln!("Failed to force-kill engine process {}: {}", pid, e);
+        }
+    });
+    Ok(())
+}
+
+/// Watch a starting engine: if it goes `timeout_ms` without printing
+/// anything before its startup banner is complete, send PossiblyHung (and
+/// kill it when asked). Stops once startup is done or the engine exits.
+/// An engine stuck on an off-screen prompt otherwise just looks like
+/// "nothing happens".
+fn spawn_startup_watchdog(session: Arc<Mutex<GZDoomSession>>, timeout_ms: u64, kill: bool, sink: EventSink) {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(WATCHDOG_INTERVAL);
+            let (id, silent_ms) = {
+                let guard = session.lock().unwrap();
+                if guard.finished || guard.pid.is_none() {
+                    return;
+                }
+                let lines = guard.lines.iter().map(|(_, line, _)| line.as_str());
+                if startup_banner::parse_banner(lines, false).is_some() {
+                    return;
+                }
+                let now = guard.start_time.elapsed().as_millis() as u64;
+                (guard.id, now.saturating_sub(guard.last_output_ms.unwrap_or(0)))
+            };
+            if silent_ms < timeout_ms {
+                continue;
+            }
+            let killed = kill && kill_session(session.clone()).is_ok();
+            sink(SessionEvent::PossiblyHung(HangWarning { session_id: id, silent_ms, killed }));
+            return;
+        }
+    });
+}
+
+/// Send NoOutput once NO_OUTPUT_AFTER has passed if the engine is still
+/// running and hasn't printed anything, so the UI can say there is no
+/// console instead of looking dead.
+fn spawn_no_output_check(session: Arc<Mutex<GZDoomSession>>, sink: EventSink) {
+    thread::spawn(move || {
+        thread::sleep(NO_OUTPUT_AFTER);
+        let silent = {
+            let guard = session.lock().unwrap();
+            (!guard.finished && guard.last_output_ms.is_none()).then_some(guard.id)
+        };
+        if let Some(id) = silent {
+            sink(SessionEvent::NoOutput(id));
+        }
+    });
+}
+
+/// Drop the oldest finished sessions beyond MAX_FINISHED_SESSIONS. Running
+/// sessions are always kept.
+fn prune_finished(sessions: &mut HashMap<SessionId, Arc<Mutex<GZDoomSession>>>) {
+    let mut finished: Vec<SessionId> = sessions
+        .iter()
+        .filter(|(_, s)| s.lock().unwrap().finished)
+        .map(|(id, _)| *id)
+        .collect();
+    if finished.len() <= MAX_FINISHED_SESSIONS {
+        return;
+    }
+    finished.sort_unstable();
+    for id in &finished[..finished.len() - MAX_FINISHED_SESSIONS] {
+        sessions.remove(id);
+    }
+}
+
+/// Read one engine output stream line by line into the session, tagged
+/// with `tag`, sending a Line event with the same fields for live consoles. The
+/// event goes out after the session lock is released so a slow webview
+/// never stalls the other stream's reader.
+fn spawn_log_reader<R: std::io::Read + Send + 'static>(
+    stream: R,
+    tag: Stream,
+    session: Arc<Mutex<GZDoomSession>>,
+    sink: EventSink,
+    done: mpsc::Sender<()>,
+) {
+    thread::spawn(move || {
+        let reader = BufReader::new(stream);
+        for line in reader.lines().map_while(Result::ok) {
+            let (id, elapsed) = {
+                let mut guard = session.lock().unwrap();
+                let elapsed = guard.start_time.elapsed().as_millis() as u64;
+                guard.push_line(elapsed, line.clone(), tag);
+                (guard.id, elapsed)
+            };
+            sink(SessionEvent::Line { session: id, elapsed, line, stream: tag });
+        }
+        // The wait thread may have stopped listening after its timeout.
+        let _ = done.send(());
+    });
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn channel_sink() -> (EventSink, mpsc::Receiver<SessionEvent>) {
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        (Arc::new(move |event| tx.lock().unwrap().send(event).unwrap()), rx)
+    }
+
+    fn wait_exited(rx: &mpsc::Receiver<SessionEvent>, id: SessionId) -> ExitSummary {
+        loop {
+            match rx.recv_timeout(Duration::from_secs(10)).unwrap() {
+                SessionEvent::Exited(summary) if summary.session_id == id => return summary,
+                _ => {}
+            }
+        }
+    }
+
+    fn sh(script: &str) -> Vec<String> {
+        vec!["-c".to_string(), script.to_string()]
+    }
+
+    fn log_lines(sessions: &Sessions, id: SessionId) -> Vec<String> {
+        let result = sessions.finished_log(id).unwrap().unwrap();
+        assert_eq!(result.session_id, id);
+        result.lines.into_iter().map(|(_, l, _)| l).collect()
+    }
+
+    #[test]
+    fn relaunch_keeps_logs_separate() {
+        let sessions = Sessions::new();
+        let (sink, rx) = channel_sink();
+
+        // The first run keeps printing after the relaunch starts.
+        let first = sessions
+            .start("/bin/sh", &sh("echo first-1; sleep 0.3; echo first-2"), sink.clone())
+            .unwrap();
+        let second = sessions.start("/bin/sh", &sh("echo second-1; echo second-2"), sink).unwrap();
+        assert!(second > first);
+        assert_eq!(sessions.finished_log(first).unwrap(), None);
+
+        wait_exited(&rx, second);
+        assert_eq!(log_lines(&sessions, second), vec!["second-1", "second-2"]);
+        wait_exited(&rx, first);
+        assert_eq!(log_lines(&sessions, first), vec!["first-1", "first-2"]);
+        assert_eq!(log_lines(&sessions, second), vec!["second-1", "second-2"]);
+    }
+
+    #[test]
+    fn kills_one_of_two_concurrent_sessions() {
+        let sessions = Sessions::new();
+        let (sink, rx) = channel_sink();
+        let server = sessions.start("/bin/sh", &sh("echo server; exec sleep 30"), sink.clone()).unwrap();
+        let client = sessions.start("/bin/sh", &sh("echo client"), sink.clone()).unwrap();
+
+        wait_exited(&rx, client);
+        assert_eq!(sessions.finished_log(server).unwrap(), None);
+        assert_eq!(sessions.status(server), SessionStatus::Running);
+        assert_eq!(sessions.status(client), SessionStatus::Finished { exit_code: Some(0), signal: None });
+        assert_eq!(sessions.status(999), SessionStatus::None);
+        sessions.kill(server).unwrap();
+        wait_exited(&rx, server);
+        assert_eq!(sessions.finished_log(server).unwrap().unwrap().signal, Some(libc::SIGTERM));
+        assert_eq!(log_lines(&sessions, client), vec!["client"]);
+
+        assert!(sessions.kill(client).unwrap_err().contains("already exited"));
+        assert!(sessions.finished_log(999).unwrap_err().contains("No engine session"));
+        let err = sessions.start("/nonexistent/engine", &[], sink).unwrap_err();
+        assert!(matches!(err, LaunchError::SpawnFailed { .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn tags_lines_with_their_stream() {
+        let sessions = Sessions::new();
+        let (sink, rx) = channel_sink();
+        let id = sessions.start("/bin/sh", &sh("echo out; sleep 0.1; echo err >&2"), sink).unwrap();
+        wait_exited(&rx, id);
+        let lines: Vec<(String, Stream)> =
+            sessions.finished_log(id).unwrap().unwrap().lines.into_iter().map(|(_, l, s)| (l, s)).collect();
+        assert_eq!(lines, vec![("out".to_string(), Stream::Stdout), ("err".to_string(), Stream::Stderr)]);
+    }
+
+    #[test]
+    fn reports_engines_without_console_output() {
+        let sessions = Sessions::new();
+        let (sink, rx) = channel_sink();
+        let silent = sessions.start("/bin/sh", &sh("exec sleep 2.5"), sink.clone()).unwrap();
+        let chatty = sessions.start("/bin/sh", &sh("echo W_Init; exec sleep 2.5"), sink).unwrap();
+        assert_eq!(sessions.status(silent), SessionStatus::Running);
+        let mut events = Vec::new();
+        while !events.contains(&SessionEvent::NoOutput(silent)) {
+            events.push(rx.recv_timeout(Duration::from_secs(10)).unwrap());
+        }
+        assert_eq!(sessions.status(silent), SessionStatus::RunningNoOutput);
+        assert_eq!(sessions.status(chatty), SessionStatus::Running);
+        events.extend(events_until_exit(&rx, silent));
+        assert!(!events.contains(&SessionEvent::NoOutput(chatty)));
+    }
+
+    #[test]
+    fn caps_log_and_counts_dropped_lines() {
+        let sessions = Sessions::with_max_lines(3);
+        let (sink, rx) = channel_sink();
+        let id = sessions.start("/bin/sh", &sh("for i in 1 2 3 4 5 6 7; do echo $i; done"), sink).unwrap();
+        let summary = wait_exited(&rx, id);
+        assert_eq!((summary.exit_code, summary.line_count), (Some(0), 7));
+        let result = sessions.finished_log(id).unwrap().unwrap();
+        assert_eq!(result.dropped_lines, 4);
+        assert_eq!(log_lines(&sessions, id), vec!["5", "6", "7"]);
+
+        sessions.set_max_lines(5);
+        let (sink, rx) = channel_sink();
+        let id = sessions.start("/bin/sh", &sh("for i in 1 2 3 4 5 6 7; do echo $i; done"), sink).unwrap();
+        wait_exited(&rx, id);
+        assert_eq!(sessions.finished_log(id).unwrap().unwrap().dropped_lines, 2);
+    }
+
+    #[test]
+    fn returns_lines_since_an_index() {
+        let sessions = Sessions::with_max_lines(3);
+        let (sink, rx) = channel_sink();
+        let id = sessions.start("/bin/sh", &sh("echo 1; echo 2; sleep 0.3; for i in 3 4 5 6; do echo $i; done"), sink).unwrap();
+        let mut lines = Vec::new();
+        while lines.len() < 2 {
+            lines = sessions.log_since(id, 0).unwrap().0;
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert_eq!(sessions.log_since(id, 0).unwrap().1, 2);
+        wait_exited(&rx, id);
+        let text = |(lines, next): (Vec<LogLine>, u64)| (lines.into_iter().map(|(_, l, _)| l).collect::<Vec<_>>(), next);
+        assert_eq!(text(sessions.log_since(id, 2).unwrap()), (vec!["4".to_string(), "5".into(), "6".into()], 6));
+        assert_eq!(text(sessions.log_since(id, 5).unwrap()), (vec!["6".to_string()], 6));
+        assert_eq!(text(sessions.log_since(id, 6).unwrap()), (vec![], 6));
+        assert_eq!(text(sessions.log_since(id, 9).unwrap()).0.len(), 3);
+    }
+
+    #[test]
+    fn applies_env_and_cwd() {
+        let sessions = Sessions::new();
+        let (sink, rx) = channel_sink();
+        let dir = std::env::temp_dir().canonicalize().unwrap();
+        let options = SpawnOptions {
+            env: HashMap::from([("DOOMWADDIR".to_string(), "/wads".to_string())]),
+            cwd: Some(dir.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let id = sessions.start_with("/bin/sh", &sh("echo $DOOMWADDIR; pwd -P"), &options, sink.clone()).unwrap();
+        wait_exited(&rx, id);
+        assert_eq!(log_lines(&sessions, id), vec!["/wads".to_string(), dir.to_string_lossy().to_string()]);
+
+        let missing = SpawnOptions { cwd: Some("/nonexistent/dir".to_string()), ..Default::default() };
+        let err = sessions.start_with("/bin/sh", &[], &missing, sink).unwrap_err();
+        assert!(matches!(err, LaunchError::NotFound { .. }) && err.to_string().contains("Working directory not found"), "{}", err);
+    }
+
+    /// Events of session `id` up to and including its exit.
+    fn events_until_exit(rx: &mpsc::Receiver<SessionEvent>, id: SessionId) -> Vec<SessionEvent> {
+        let mut events = Vec::new();
+        loop {
+            let event = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+            let done = matches!(&event, SessionEvent::Exited(summary) if summary.session_id == id);
+            events.push(event);
+            if done {
+                return events;
+            }
+        }
+    }
+
+    #[test]
+    fn startup_watchdog_reports_and_kills_silent_engines() {
+        let sessions = Sessions::new();
+        let (sink, rx) = channel_sink();
+        let options = SpawnOptions { startup_timeout_ms: Some(300), kill_if_hung: true, ..Default::default() };
+        let id = sessions.start_with("/bin/sh", &sh("echo W_Init; exec sleep 30"), &options, sink.clone()).unwrap();
+        let events = events_until_exit(&rx, id);
+        let hung: Vec<&HangWarning> = events
+            .iter()
+            .filter_map(|e| match e {
+                SessionEvent::PossiblyHung(w) => Some(w),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(hung.len(), 1);
+        assert!(hung[0].killed && hung[0].silent_ms >= 300);
+        assert_eq!(sessions.finished_log(id).unwrap().unwrap().signal, Some(libc::SIGTERM));
+
+        // Done starting up: a quiet engine is fine.
+        let id = sessions.start_with("/bin/sh", &sh("echo 'P_Init: Init Playloop state.'; sleep 0.6"), &options, sink).unwrap();
+        assert!(!events_until_exit(&rx, id).iter().any(|e| matches!(e, SessionEvent::PossiblyHung(_))));
+    }
+
+    #[test]
+    fn clears_running_and_finished_sessions() {
+        let sessions = Sessions::new();
+        let (sink, rx) = channel_sink();
+        let running = sessions.start("/bin/sh", &sh("echo before; sleep 0.3; echo after"), sink.clone()).unwrap();
+        let done = sessions.start("/bin/sh", &sh("echo done"), sink).unwrap();
+        wait_exited(&rx, done);
+        while sessions.snapshot(running).unwrap().lines.is_empty() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        sessions.clear(running);
+        sessions.clear(done);
+        sessions.clear(999);
+        let cleared = sessions.finished_log(done).unwrap().unwrap();
+        assert!(cleared.lines.is_empty());
+        assert_eq!((cleared.dropped_lines, cleared.exit_code), (0, Some(0)));
+        wait_exited(&rx, running);
+        assert_eq!(log_lines(&sessions, running), vec!["after"]);
+    }
+
+    #[test]
+    fn saves_log_as_text_and_json() {
+        let log = GZDoomLogResult {
+            session_id: 3,
+            started_at: "2026-10-14T17:46:40.123Z".into(),
+            lines: vec![(1234, "W_Init".into(), Stream::Stdout), (61_005, "MAP01 - Entryway".into(), Stream::Stdout)],
+            dropped_lines: 2,
+            exit_code: Some(0),
+            signal: None,
+        };
+        assert_eq!(
+            log.to_text(),
+            "Started 2026-10-14T17:46:40.123Z\n... 2 earlier lines omitted\n[00:01.234] W_Init\n[01:01.005] MAP01 - Entryway\nExited with code 0\n"
+        );
+        let dir = std::env::temp_dir().join(format!("session_log_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let written = log.save(dir.join("console").to_str().unwrap(), LogFormat::Json).unwrap();
+        assert!(written.ends_with("console.json"));
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&written).unwrap()).unwrap();
+        assert_eq!(json["sessionId"], 3);
+        assert_eq!(json["startedAt"], "2026-10-14T17:46:40.123Z");
+        assert_eq!(json["lines"][1][1], "MAP01 - Entryway");
+
+        let temp = GZDoomLogResult { session_id: 987_654, ..log }.save_temp().unwrap();
+        assert!(temp.ends_with("gzdoom-session-987654.log"));
+        assert!(std::fs::read_to_string(&temp).unwrap().contains("[00:01.234] W_Init"));
+        cleanup_temp_logs();
+        assert!(!std::path::Path::new(&temp).exists());
+    }
+}
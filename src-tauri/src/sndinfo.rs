@@ -0,0 +1,173 @@
+// SNDINFO: the ZDoom-family (and Hexen) table from logical sound names to
+// lumps, plus music aliases. Listing it shows whether a mod brings its own
+// sound pack or soundtrack. Definitions are one per line:
+//
+//     misc/secret     DSSECRET         // sound
+//     $random grunt { grunt1 grunt2 }
+//     $musicalias D_RUNNIN D_DM2TTL    // music
+//     $map 1 WINNOWR                   // Hexen map music
+//
+// Other $ directives ($limit, $pitchshift, $ambient, …) tune sounds rather
+// than define them and are skipped.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::mapinfo::{self, Token};
+use crate::wad_parser;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum SoundKind {
+    Sound,
+    /// One of several lumps, picked at random each time.
+    Random,
+    /// Another name for a logical sound.
+    Alias,
+    /// Per player class and gender ("player/male/*pain100").
+    PlayerSound,
+    /// $musicalias: replaces a music lump by name.
+    Music,
+    /// Hexen's $map: the music for a map number ("map 1").
+    MapMusic,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundDefinition {
+    pub kind: SoundKind,
+    /// Logical name, lowercased; engines look sounds up case-insensitively.
+    pub name: String,
+    /// Lump names (uppercase), or logical names for Random and Alias.
+    pub lumps: Vec<String>,
+    pub defined_in: String,
+    /// The earlier file whose definition of this name this one replaces.
+    pub replaces: Option<String>,
+}
+
+type Definition = (SoundKind, String, Vec<String>);
+
+fn definition(kind: SoundKind, name: &str, lumps: &[&Token]) -> Definition {
+    let lumps = lumps
+        .iter()
+        .map(|t| match kind {
+            SoundKind::Random | SoundKind::Alias => t.text.to_lowercase(),
+            _ => t.text.to_uppercase(),
+        })
+        .collect();
+    (kind, name.to_lowercase(), lumps)
+}
+
+/// The definitions in one SNDINFO lump, in order.
+fn parse_sndinfo(src: &str) -> Vec<Definition> {
+    let tokens = mapinfo::tokenize(src);
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let head = &tokens[i];
+        if head.text.eq_ignore_ascii_case("$random") {
+            // $random name { a b c }, which may span lines.
+            let open = i + 2;
+            if tokens.get(open).is_some_and(|t| t.is("{")) {
+                let close = tokens[open..].iter().position(|t| t.is("}")).map_or(tokens.len(), |n| open + n);
+                let members: Vec<&Token> = tokens[open + 1..close].iter().collect();
+                out.push(definition(SoundKind::Random, &tokens[i + 1].text, &members));
+                i = close + 1;
+                continue;
+            }
+        }
+        let line_end = tokens[i..].iter().position(|t| t.line != head.line).map_or(tokens.len(), |n| i + n);
+        let args: Vec<&Token> = tokens[i + 1..line_end].iter().collect();
+        let directive = head.text.to_lowercase();
+        match directive.as_str() {
+            "$musicalias" if args.len() >= 2 => out.push(definition(SoundKind::Music, &args[0].text, &args[1..2])),
+            "$alias" if args.len() >= 2 => out.push(definition(SoundKind::Alias, &args[0].text, &args[1..2])),
+            "$playersound" if args.len() >= 4 => {
+                let name = format!("{}/{}/{}", args[0].text, args[1].text, args[2].text);
+                out.push(definition(SoundKind::PlayerSound, &name, &args[3..4]));
+            }
+            "$map" if args.len() >= 2 => {
+                out.push(definition(SoundKind::MapMusic, &format!("map {}", args[0].text), &args[1..2]))
+            }
+            _ if directive.starts_with('$') || head.is("{") || head.is("}") => {}
+            _ if !args.is_empty() => out.push(definition(SoundKind::Sound, &head.text, &args[..1])),
+            _ => {}
+        }
+        i = line_end;
+    }
+    out
+}
+
+/// The sound and music definitions of a load order (IWAD first, when
+/// given). A name defined again later replaces the earlier definition, as
+/// in the engine; the result keeps the first definition's position.
+pub fn list_sound_definitions(files: &[String]) -> Result<Vec<SoundDefinition>, String> {
+    let mut out: Vec<SoundDefinition> = Vec::new();
+    let mut index: HashMap<(SoundKind, String), usize> = HashMap::new();
+    for file in files {
+        for lump in wad_parser::collect_lumps(file, &|name| name == "SNDINFO")? {
+            for (kind, name, lumps) in parse_sndinfo(&String::from_utf8_lossy(&lump.data)) {
+                match index.get(&(kind, name.clone())) {
+                    Some(&at) => {
+                        let previous = &mut out[at];
+                        if previous.defined_in != *file {
+                            previous.replaces = Some(std::mem::replace(&mut previous.defined_in, file.clone()));
+                        }
+                        previous.lumps = lumps;
+                    }
+                    None => {
+                        index.insert((kind, name.clone()), out.len());
+                        out.push(SoundDefinition { kind, name, lumps, defined_in: file.clone(), replaces: None });
+                    }
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wad_parser::test_util::{build_wad, build_zip, write_temp};
+
+    #[test]
+    fn parses_sndinfo_definitions() {
+        let src = r#"
+            ; Hexen-style comment
+            misc/secret     dssecret    // ZDoom-style comment
+            $random grunt
+            {
+                grunt1 Grunt2
+            }
+            $limit misc/secret 1
+            $musicalias D_RUNNIN d_dm2ttl
+            $playersound player male *pain100 dsplpain
+            $map 1 WINNOWR
+        "#;
+        let defs = parse_sndinfo(src);
+        let kinds: Vec<(SoundKind, &str, Vec<&str>)> =
+            defs.iter().map(|(k, n, l)| (*k, n.as_str(), l.iter().map(String::as_str).collect())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (SoundKind::Sound, "misc/secret", vec!["DSSECRET"]),
+                (SoundKind::Random, "grunt", vec!["grunt1", "grunt2"]),
+                (SoundKind::Music, "d_runnin", vec!["D_DM2TTL"]),
+                (SoundKind::PlayerSound, "player/male/*pain100", vec!["DSPLPAIN"]),
+                (SoundKind::MapMusic, "map 1", vec!["WINNOWR"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn later_files_replace_definitions() {
+        let base = write_temp("snd_base.wad", &build_wad(b"IWAD", &[("SNDINFO", b"misc/secret DSSECRET\n$map 1 WINNOWR\n")]));
+        let pack = write_temp("snd_pack.pk3", &build_zip(&[("sndinfo.txt", b"misc/secret NEWSECRT\nmisc/new NEWSND\n")]));
+        let defs = list_sound_definitions(&[base.clone(), pack.clone()]).unwrap();
+        assert_eq!(defs.len(), 3);
+        assert_eq!((defs[0].lumps[0].as_str(), &defs[0].defined_in, &defs[0].replaces), ("NEWSECRT", &pack, &Some(base.clone())));
+        assert_eq!((&defs[1].defined_in, &defs[1].replaces), (&base, &None));
+        assert_eq!((defs[2].name.as_str(), &defs[2].replaces), ("misc/new", &None));
+    }
+}
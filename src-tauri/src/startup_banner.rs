@@ -0,0 +1,134 @@
+// The startup banner at the top of a GZDoom-family log: which engine build
+// is running, which files it actually loaded and which renderer it got.
+// Comparing the loaded files with the launch catches silent fallbacks, like
+// a mod the engine skipped or the IWAD it picked on its own.
+//
+//     GZDoom g4.14.2 - 2025-05-04 22:20:32
+//     W_Init: Init WADfiles.
+//     adding /usr/share/games/doom/gzdoom.pk3, 656 lumps
+//     adding /home/me/wads/doom2.wad, 2956 lumps
+//     GL_RENDERER: AMD Radeon RX 6700 XT
+//     ...
+//     P_Init: Init Playloop state.
+
+use serde::Serialize;
+
+use crate::engine_version::{self, VersionInfo};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadedFile {
+    pub path: String,
+    /// Lump count as printed; None for directories and other formats.
+    pub lumps: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    /// Program name from the banner, e.g. "GZDoom".
+    pub engine: Option<String>,
+    pub version: VersionInfo,
+    pub build_date: Option<String>,
+    /// In load order, the engine's own resource file included.
+    pub loaded_files: Vec<LoadedFile>,
+    /// GPU/driver line, e.g. "GL_RENDERER: …" or "Vulkan device: …".
+    pub renderer: Option<String>,
+}
+
+/// The banner is only looked for this far into the log.
+const BANNER_LINES: usize = 300;
+
+/// Lines printed once file loading and video setup are done.
+const STARTUP_DONE: &[&str] = &["P_Init:", "ST_Init:", "D_CheckNetGame:"];
+
+const RENDERER_PREFIXES: &[&str] = &["GL_RENDERER:", "Vulkan device:", "Vulkan Device:"];
+
+/// "adding /path/doom2.wad, 2956 lumps" (or "… (12 files)" for a directory).
+fn loaded_file(line: &str) -> Option<LoadedFile> {
+    let rest = line.strip_prefix("adding ")?;
+    if let Some((path, count)) = rest.rsplit_once(", ")
+        && let Some(count) = count.strip_suffix(" lumps")
+    {
+        return Some(LoadedFile { path: path.to_string(), lumps: count.trim().parse().ok() });
+    }
+    let path = rest.rsplit_once(" (").map_or(rest, |(path, _)| path);
+    Some(LoadedFile { path: path.to_string(), lumps: None })
+}
+
+/// Parse the startup banner from the first lines of a log. None until the
+/// engine has finished starting (or `finished` says it never will), so a
+/// half-printed file list is never reported as the whole one.
+pub fn parse_banner<'a>(lines: impl IntoIterator<Item = &'a str>, finished: bool) -> Option<SessionInfo> {
+    let mut info = SessionInfo {
+        engine: None,
+        version: VersionInfo::unknown(),
+        build_date: None,
+        loaded_files: Vec::new(),
+        renderer: None,
+    };
+    let mut done = false;
+    for line in lines.into_iter().take(BANNER_LINES).map(str::trim) {
+        if !info.version.is_known() && info.loaded_files.is_empty() {
+            let (head, date) = line.split_once(" - ").map_or((line, None), |(h, d)| (h, Some(d)));
+            let version = engine_version::parse_version(head);
+            if version.is_known() {
+                info.engine = head.split_whitespace().next().map(str::to_string);
+                info.build_date = date.map(|d| d.trim().to_string());
+                info.version = version;
+                continue;
+            }
+        }
+        if let Some(file) = loaded_file(line) {
+            info.loaded_files.push(file);
+        } else if info.renderer.is_none() && RENDERER_PREFIXES.iter().any(|p| line.starts_with(p)) {
+            info.renderer = line.split_once(':').map(|(_, r)| r.trim().to_string());
+        } else if STARTUP_DONE.iter().any(|p| line.starts_with(p)) {
+            done = true;
+            break;
+        }
+    }
+    (done || finished).then_some(info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &[&str] = &[
+        "GZDoom g4.14.2 - 2025-05-04 22:20:32",
+        "OS: Linux 6.8.0",
+        "W_Init: Init WADfiles.",
+        "adding /usr/share/games/doom/gzdoom.pk3, 656 lumps",
+        "adding /home/me/wads/doom2.wad, 2956 lumps",
+        "adding /home/me/mods/brightmaps (12 files)",
+        "GL_RENDERER: AMD Radeon RX 6700 XT",
+        "P_Init: Init Playloop state.",
+        "adding later.wad, 1 lumps",
+    ];
+
+    #[test]
+    fn parses_version_files_and_renderer() {
+        let info = parse_banner(LOG.iter().copied(), false).unwrap();
+        assert_eq!(info.engine.as_deref(), Some("GZDoom"));
+        assert_eq!((info.version.major, info.version.minor), (Some(4), Some(14)));
+        assert_eq!(info.build_date.as_deref(), Some("2025-05-04 22:20:32"));
+        let files: Vec<(&str, Option<u32>)> = info.loaded_files.iter().map(|f| (f.path.as_str(), f.lumps)).collect();
+        assert_eq!(
+            files,
+            vec![
+                ("/usr/share/games/doom/gzdoom.pk3", Some(656)),
+                ("/home/me/wads/doom2.wad", Some(2956)),
+                ("/home/me/mods/brightmaps", None),
+            ]
+        );
+        assert_eq!(info.renderer.as_deref(), Some("AMD Radeon RX 6700 XT"));
+    }
+
+    #[test]
+    fn waits_for_startup_to_finish() {
+        assert_eq!(parse_banner(LOG[..5].iter().copied(), false), None);
+        let info = parse_banner(LOG[..5].iter().copied(), true).unwrap();
+        assert_eq!(info.loaded_files.len(), 2);
+    }
+}
@@ -0,0 +1,1974 @@
+// WAD parsing on the Rust side. Everything here works from the header and
+// lump directory plus the few lumps it actually needs — megawads run to
+// hundreds of MB, so files are never read whole.
+//
+// WAD layout: 12-byte header ("IWAD"/"PWAD", numlumps: u32 LE,
+// infotableofs: u32 LE), then a directory of numlumps 16-byte entries
+// (filepos: u32 LE, size: u32 LE, name: 8 bytes NUL-padded).
+
+use md5::Md5;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::dehacked;
+use crate::game_archives::{self, MAX_INMEMORY_READ};
+use crate::iwad;
+use crate::language;
+use crate::launch_error::LaunchError;
+use crate::map_stats;
+use crate::mapinfo;
+use crate::picture;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lump {
+    pub name: String,
+    pub offset: u32,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WadHeader {
+    pub magic: [u8; 4],
+    pub num_lumps: u32,
+    pub dir_offset: u32,
+}
+
+const HEADER_LEN: u64 = 12;
+const DIR_ENTRY_LEN: u64 = 16;
+
+fn open(path: &str) -> Result<File, String> {
+    File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))
+}
+
+fn file_len(file: &File, path: &str) -> Result<u64, String> {
+    Ok(file
+        .metadata()
+        .map_err(|e| format!("Failed to stat {}: {}", path, e))?
+        .len())
+}
+
+/// Read the 12-byte header. Errors unless the magic is IWAD or PWAD.
+pub fn read_header(file: &mut File, path: &str) -> Result<WadHeader, String> {
+    let mut buf = [0u8; HEADER_LEN as usize];
+    file.seek(SeekFrom::Start(0))
+        .and_then(|_| file.read_exact(&mut buf))
+        .map_err(|e| format!("Failed to read WAD header of {}: {}", path, e))?;
+    let magic = [buf[0], buf[1], buf[2], buf[3]];
+    if &magic != b"IWAD" && &magic != b"PWAD" {
+        return Err(format!(
+            "Invalid WAD file: {} - expected IWAD/PWAD header, got \"{}\"",
+            path,
+            String::from_utf8_lossy(&magic)
+        ));
+    }
+    Ok(WadHeader {
+        magic,
+        num_lumps: u32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]),
+        dir_offset: u32::from_le_bytes([buf[8], buf[9], buf[10], buf[11]]),
+    })
+}
+
+/// Read the raw directory bytes. The directory must fit inside the file —
+/// a bogus numlumps must not turn into a huge allocation. The directory
+/// comes last in a WAD, so an unfinished download fails here.
+fn read_directory_bytes(file: &mut File, path: &str, header: &WadHeader) -> Result<Vec<u8>, String> {
+    let dir_len = header.num_lumps as u64 * DIR_ENTRY_LEN;
+    let len = file_len(file, path)?;
+    if header.dir_offset as u64 + dir_len > len {
+        return Err(format!(
+            "{} appears truncated or corrupt: its WAD directory ({} lumps at offset {}) extends past the end of the file ({} bytes)",
+            path, header.num_lumps, header.dir_offset, len
+        ));
+    }
+    let mut dir = vec![0u8; dir_len as usize];
+    file.seek(SeekFrom::Start(header.dir_offset as u64))
+        .and_then(|_| file.read_exact(&mut dir))
+        .map_err(|e| format!("Failed to read WAD directory of {}: {}", path, e))?;
+    Ok(dir)
+}
+
+/// Parse lump entries out of raw directory bytes. Names are uppercased and
+/// cut at the first NUL, matching how engines look lumps up.
+fn parse_directory(dir: &[u8]) -> Vec<Lump> {
+    dir.chunks_exact(DIR_ENTRY_LEN as usize)
+        .map(|entry| {
+            let name_bytes = &entry[8..16];
+            let end = name_bytes.iter().position(|&b| b == 0).unwrap_or(8);
+            Lump {
+                offset: u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]),
+                size: u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]),
+                name: String::from_utf8_lossy(&name_bytes[..end]).to_uppercase(),
+            }
+        })
+        .collect()
+}
+
+/// Read and parse the lump directory, which must lie inside the file.
+/// Lumps running past the end are logged, as GZDoom warns about them, and
+/// only fail when read (see read_lump), so one bad entry nobody reads
+/// doesn't make the whole WAD unreadable.
+fn read_checked_directory(file: &mut File, path: &str, header: &WadHeader) -> Result<Vec<Lump>, String> {
+    let lumps = parse_directory(&read_directory_bytes(file, path, header)?);
+    let len = file_len(file, path)?;
+    for lump in lumps.iter().filter(|l| l.offset as u64 + l.size as u64 > len) {
+        eprintln!("{}", past_end(path, lump, len));
+    }
+    Ok(lumps)
+}
+
+fn past_end(path: &str, lump: &Lump, len: u64) -> String {
+    format!(
+        "{} appears truncated or corrupt: lump {} ({} bytes at offset {}) extends past the end of the file ({} bytes)",
+        path, lump.name, lump.size, lump.offset, len
+    )
+}
+
+/// Read header and lump directory of a WAD.
+pub fn read_lumps(path: &str) -> Result<Vec<Lump>, String> {
+    let mut file = open(path)?;
+    let header = read_header(&mut file, path)?;
+    read_checked_directory(&mut file, path, &header)
+}
+
+/// Read one lump's bytes. Capped at MAX_INMEMORY_READ like every other
+/// in-memory read; the lumps parsed here are text or small binaries. A
+/// lump past the end of the file is reported with the offsets involved.
+pub fn read_lump(file: &mut File, path: &str, lump: &Lump) -> Result<Vec<u8>, String> {
+    let len = file_len(file, path)?;
+    if lump.offset as u64 + lump.size as u64 > len {
+        return Err(past_end(path, lump, len));
+    }
+    if lump.size as u64 > MAX_INMEMORY_READ {
+        return Err(format!(
+            "Lump {} in {} is {} bytes, over the {} MB in-memory read cap",
+            lump.name,
+            path,
+            lump.size,
+            MAX_INMEMORY_READ / (1024 * 1024)
+        ));
+    }
+    let mut buf = vec![0u8; lump.size as usize];
+    file.seek(SeekFrom::Start(lump.offset as u64))
+        .and_then(|_| file.read_exact(&mut buf))
+        .map_err(|e| format!("Failed to read lump {} from {}: {}", lump.name, path, e))?;
+    Ok(buf)
+}
+
+/// A named resource from a WAD lump or a ZIP entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedLump {
+    /// Uppercase lump name; for ZIP entries, the basename without extension
+    /// ("maps/zmapinfo.txt" -> "ZMAPINFO"), which is how GZDoom names them.
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// The lump name GZDoom derives from a ZIP entry path.
+pub(crate) fn zip_entry_lump_name(entry_path: &str) -> String {
+    let base = entry_path.rsplit(['/', '\\']).next().unwrap_or(entry_path);
+    let stem = base.split('.').next().unwrap_or(base);
+    stem.to_uppercase()
+}
+
+/// Collect every lump whose name satisfies `wanted`, in directory order,
+/// from a WAD or a ZIP/PK3. WADs nested inside an archive (maps/*.wad, or an
+/// idgames zip wrapping the release) are streamed to a temp file and
+/// searched too, after the archive's own entries; one that can't be read
+/// is logged and skipped, as the engine skips it.
+pub fn collect_lumps(path: &str, wanted: &dyn Fn(&str) -> bool) -> Result<Vec<NamedLump>, String> {
+    match detect_wad_type(path)? {
+        WadType::Iwad | WadType::Pwad => {
+            let mut file = open(path)?;
+            let header = read_header(&mut file, path)?;
+            let lumps = read_checked_directory(&mut file, path, &header)?;
+            let mut out = Vec::new();
+            for lump in lumps.iter().filter(|l| wanted(&l.name)) {
+                out.push(NamedLump { name: lump.name.clone(), data: read_lump(&mut file, path, lump)? });
+            }
+            Ok(out)
+        }
+        WadType::Pk3 | WadType::Ipk3 | WadType::Zip => {
+            let mut out = Vec::new();
+            let mut nested = Vec::new();
+            for entry in game_archives::list_zip_entries(path)? {
+                if entry.path.to_lowercase().ends_with(".wad") {
+                    nested.push(entry.path);
+                } else if wanted(&zip_entry_lump_name(&entry.path)) {
+                    out.push(NamedLump {
+                        name: zip_entry_lump_name(&entry.path),
+                        data: game_archives::read_zip_entry(path, &entry.path)?,
+                    });
+                }
+            }
+            for entry_path in nested {
+                match with_nested_wad(path, &entry_path, |wad| collect_lumps(wad, wanted)) {
+                    Ok(lumps) => out.extend(lumps),
+                    Err(e) => eprintln!("Skipping {} in {}: {}", entry_path, path, e),
+                }
+            }
+            Ok(out)
+        }
+        WadType::Unknown => Err(format!("Not a WAD or ZIP/PK3 file: {}", path)),
+    }
+}
+
+/// Whether a file of a load order holds the map `map_id` (uppercase). A
+/// PK3's maps/<id>.wad is found by entry name, without extracting it;
+/// other WADs inside an archive are searched like collect_lumps does.
+/// Directories and files that are neither WADs nor archives (.deh, .bex)
+/// hold no maps.
+pub fn has_map(path: &str, map_id: &str) -> Result<bool, String> {
+    if std::path::Path::new(path).is_dir() {
+        return Ok(false);
+    }
+    match detect_wad_type(path)? {
+        WadType::Iwad | WadType::Pwad => Ok(!collect_lumps(path, &|n| n == map_id)?.is_empty()),
+        WadType::Pk3 | WadType::Ipk3 | WadType::Zip => {
+            let mut nested = Vec::new();
+            for entry in game_archives::list_zip_entries(path)? {
+                let lower = entry.path.to_lowercase();
+                if !lower.ends_with(".wad") {
+                    continue;
+                }
+                if lower.starts_with("maps/") && zip_entry_lump_name(&entry.path) == map_id {
+                    return Ok(true);
+                }
+                if !lower.starts_with("maps/") {
+                    nested.push(entry.path);
+                }
+            }
+            for entry_path in nested {
+                if with_nested_wad(path, &entry_path, |wad| has_map(wad, map_id))? {
+                    return Ok(true);
+                }
+            }
+            Ok(false)
+        }
+        WadType::Unknown => Ok(false),
+    }
+}
+
+/// The lump namespaces engines look graphics up in: between F_START/F_END
+/// (or FF_), S_ (or SS_) and P_ (or PP_) markers in a WAD, under flats/,
+/// sprites/ and patches/ in a PK3.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Namespace {
+    Flats,
+    Sprites,
+    Patches,
+}
+
+/// One entry of a WAD's lump directory, or one file in a ZIP/PK3.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LumpEntry {
+    /// Lump name as engines look it up; for ZIP entries, as in NamedLump.
+    pub name: String,
+    /// Path inside a ZIP/PK3; None in a WAD.
+    pub entry_path: Option<String>,
+    /// Uncompressed size in bytes.
+    pub size: u64,
+    /// Byte offset in a WAD; None in a ZIP/PK3.
+    pub offset: Option<u64>,
+    /// None outside the namespaces, and for the markers themselves.
+    pub namespace: Option<Namespace>,
+}
+
+/// The namespace a start/end marker opens or closes, with true for a start.
+/// The numbered sub-markers inside (F1_START, P2_END) change nothing.
+fn namespace_marker(name: &str) -> Option<(Namespace, bool)> {
+    let (prefix, start) = match (name.strip_suffix("_START"), name.strip_suffix("_END")) {
+        (Some(prefix), _) => (prefix, true),
+        (_, Some(prefix)) => (prefix, false),
+        _ => return None,
+    };
+    let namespace = match prefix {
+        "F" | "FF" => Namespace::Flats,
+        "S" | "SS" => Namespace::Sprites,
+        "P" | "PP" => Namespace::Patches,
+        _ => return None,
+    };
+    Some((namespace, start))
+}
+
+/// Every lump of a WAD in directory order, or every file of a ZIP/PK3 in
+/// archive order, for inspecting a file. Only the directory is read, never
+/// lump data, so this is cheap on any size of file.
+pub fn list_wad_lumps(path: &str) -> Result<Vec<LumpEntry>, String> {
+    match detect_wad_type(path)? {
+        WadType::Iwad | WadType::Pwad => {
+            let mut file = open(path)?;
+            let header = read_header(&mut file, path)?;
+            let mut namespace = None;
+            let lumps = read_checked_directory(&mut file, path, &header)?;
+            Ok(lumps
+                .into_iter()
+                .map(|lump| {
+                    let marker = namespace_marker(&lump.name);
+                    if let Some((opened, start)) = marker {
+                        namespace = start.then_some(opened);
+                    }
+                    LumpEntry {
+                        namespace: if marker.is_some() { None } else { namespace },
+                        size: lump.size as u64,
+                        offset: Some(lump.offset as u64),
+                        entry_path: None,
+                        name: lump.name,
+                    }
+                })
+                .collect())
+        }
+        WadType::Pk3 | WadType::Ipk3 | WadType::Zip => Ok(game_archives::list_zip_entries(path)?
+            .into_iter()
+            .map(|entry| {
+                let top = entry.path.split(['/', '\\']).next().unwrap_or("").to_lowercase();
+                let namespace = match top.as_str() {
+                    "flats" => Some(Namespace::Flats),
+                    "sprites" => Some(Namespace::Sprites),
+                    "patches" => Some(Namespace::Patches),
+                    _ => None,
+                }
+                .filter(|_| entry.path.contains(['/', '\\']));
+                LumpEntry {
+                    name: zip_entry_lump_name(&entry.path),
+                    size: entry.size,
+                    offset: None,
+                    namespace,
+                    entry_path: Some(entry.path),
+                }
+            })
+            .collect()),
+        WadType::Unknown => Err(format!("Not a WAD or ZIP/PK3 file: {}", path)),
+    }
+}
+
+/// The bytes of one lump, by name. In a ZIP/PK3 a name only finds entries
+/// at the root (mapinfo.txt for MAPINFO); one in a folder takes its full
+/// entry path (maps/mapinfo.txt). A name can appear more than once;
+/// without `index` this reads the last one, the lump engines use, and with
+/// it the entry at that position in list_wad_lumps, which must carry the
+/// name. Capped at MAX_INMEMORY_READ.
+pub fn read_wad_lump(path: &str, name: &str, index: Option<usize>) -> Result<Vec<u8>, String> {
+    let name = name.trim();
+    let lumps = list_wad_lumps(path)?;
+    let matches = |lump: &&LumpEntry| match lump.entry_path.as_deref() {
+        Some(entry_path) => {
+            entry_path == name || (!entry_path.contains(['/', '\\']) && lump.name.eq_ignore_ascii_case(name))
+        }
+        None => lump.name.eq_ignore_ascii_case(name),
+    };
+    let chosen = match index {
+        Some(i) => lumps
+            .get(i)
+            .filter(matches)
+            .ok_or_else(|| format!("Lump #{} of {} is not {}", i, path, name))?,
+        None => lumps.iter().rev().find(matches).ok_or_else(|| format!("No lump {} in {}", name, path))?,
+    };
+    if let Some(entry_path) = &chosen.entry_path {
+        return game_archives::read_zip_entry(path, entry_path);
+    }
+    let lump = Lump { name: chosen.name.clone(), offset: chosen.offset.unwrap_or_default() as u32, size: chosen.size as u32 };
+    read_lump(&mut open(path)?, path, &lump)
+}
+
+/// Lumps the level-name extractor reads.
+pub const MAPINFO_LUMPS: &[&str] = &["MAPINFO", "ZMAPINFO", "EMAPINFO", "UMAPINFO"];
+
+/// Per-level MAPINFO data from a WAD, PK3/IPK3 or ZIP, in definition order.
+/// The first definition of each field wins, in lump order. "$KEY" names
+/// are looked up in the file's LANGUAGE lumps, preferring `locale`.
+pub fn extract_levels(path: &str, locale: Option<&str>) -> Result<Vec<mapinfo::LevelInfo>, String> {
+    let mut strings = language::Strings::new(locale);
+    let mut levels = Vec::new();
+    for lump in collect_lumps(path, &|name| name == "LANGUAGE" || MAPINFO_LUMPS.contains(&name))? {
+        let text = String::from_utf8_lossy(&lump.data);
+        if lump.name == "LANGUAGE" {
+            strings.add_lump(&text);
+        } else {
+            mapinfo::merge_levels(&mut levels, mapinfo::parse_levels(&lump.name, &text));
+        }
+    }
+    for level in &mut levels {
+        level.name = level.name.as_deref().map(|name| strings.resolve(name));
+    }
+    Ok(levels)
+}
+
+/// The episode menu a WAD/PK3 defines, applying its MAPINFO-family lumps in
+/// order, with "$KEY" names looked up as in extract_levels.
+pub fn extract_episodes(path: &str, locale: Option<&str>) -> Result<mapinfo::EpisodeList, String> {
+    let mut strings = language::Strings::new(locale);
+    let mut list = mapinfo::EpisodeList::default();
+    for lump in collect_lumps(path, &|name| name == "LANGUAGE" || MAPINFO_LUMPS.contains(&name))? {
+        let text = String::from_utf8_lossy(&lump.data);
+        if lump.name == "LANGUAGE" {
+            strings.add_lump(&text);
+        } else {
+            mapinfo::apply_episodes(&mut list, &lump.name, &text);
+        }
+    }
+    for episode in &mut list.episodes {
+        episode.name = episode.name.as_deref().map(|name| strings.resolve(name));
+    }
+    Ok(list)
+}
+
+/// The gameinfo a WAD/PK3 declares in MAPINFO/ZMAPINFO and, for
+/// standalone IPK3s, IWADINFO. None when it has neither block.
+pub fn extract_gameinfo(path: &str) -> Result<Option<mapinfo::GameInfo>, String> {
+    let mut info = mapinfo::GameInfo::default();
+    let mut found = false;
+    for lump in collect_lumps(path, &|name| matches!(name, "MAPINFO" | "ZMAPINFO" | "IWADINFO"))? {
+        found |= mapinfo::apply_gameinfo(&mut info, &String::from_utf8_lossy(&lump.data));
+    }
+    Ok(found.then_some(info))
+}
+
+/// A cluster's story screens, with the text looked up.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterText {
+    pub id: u32,
+    /// Shown before the cluster's first map.
+    pub enter_text: Option<String>,
+    /// Shown after its last map.
+    pub exit_text: Option<String>,
+    pub backdrop: Option<String>,
+    pub music: Option<String>,
+    pub hub: bool,
+}
+
+/// The clusters a WAD/PK3's MAPINFO/ZMAPINFO define, with `lookup` and
+/// "$KEY" texts resolved from its LANGUAGE lumps (preferring `locale`) and
+/// `*textislump` texts read from their lumps. Keys the file doesn't define
+/// (the engine's own strings) stay "$KEY". A cluster defined again replaces
+/// the earlier one.
+pub fn extract_cluster_texts(path: &str, locale: Option<&str>) -> Result<Vec<ClusterText>, String> {
+    let mut strings = language::Strings::new(locale);
+    let mut clusters: Vec<mapinfo::ClusterDef> = Vec::new();
+    for lump in collect_lumps(path, &|name| matches!(name, "MAPINFO" | "ZMAPINFO" | "LANGUAGE"))? {
+        let text = String::from_utf8_lossy(&lump.data);
+        if lump.name == "LANGUAGE" {
+            strings.add_lump(&text);
+            continue;
+        }
+        for cluster in mapinfo::parse_clusters(&text) {
+            match clusters.iter_mut().find(|c| c.id == cluster.id) {
+                Some(existing) => *existing = cluster,
+                None => clusters.push(cluster),
+            }
+        }
+    }
+
+    let text_lumps: Vec<String> = clusters
+        .iter()
+        .flat_map(|c| [&c.enter_text, &c.exit_text])
+        .filter_map(|t| match t {
+            Some(mapinfo::StoryText::Lump(name)) => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let lumps: HashMap<String, String> = if text_lumps.is_empty() {
+        HashMap::new()
+    } else {
+        collect_lumps(path, &|name| text_lumps.iter().any(|l| l == name))?
+            .into_iter()
+            .map(|l| (l.name, String::from_utf8_lossy(&l.data).into_owned()))
+            .collect()
+    };
+    let resolve = |text: &Option<mapinfo::StoryText>| -> Option<String> {
+        match text.as_ref()? {
+            mapinfo::StoryText::Literal(text) => Some(strings.resolve(text)),
+            mapinfo::StoryText::Lookup(key) => Some(strings.resolve(&format!("${}", key))),
+            mapinfo::StoryText::Lump(name) => {
+                let text = lumps.get(name).cloned();
+                if text.is_none() {
+                    eprintln!("Cluster text lump {} not found in {}", name, path);
+                }
+                text
+            }
+        }
+    };
+    Ok(clusters
+        .iter()
+        .map(|c| ClusterText {
+            id: c.id,
+            enter_text: resolve(&c.enter_text),
+            exit_text: resolve(&c.exit_text),
+            backdrop: c.backdrop.clone(),
+            music: c.music.clone(),
+            hub: c.hub,
+        })
+        .collect())
+}
+
+/// Level names (map id -> name) from MAPINFO and the file's own DEHACKED
+/// lumps; see extract_level_names_with_patches.
+pub fn extract_level_names(path: &str) -> Result<HashMap<String, String>, String> {
+    extract_level_names_with_patches(path, &[])
+}
+
+/// Level names from a file plus external .deh/.bex patches loaded with it.
+/// A name written out in MAPINFO wins; otherwise the last patch to set a
+/// map's title does, external patches after the file's DEHACKED lumps, as
+/// the engine applies them.
+pub fn extract_level_names_with_patches(path: &str, patches: &[&str]) -> Result<HashMap<String, String>, String> {
+    let mut names = HashMap::new();
+    for lump in collect_lumps(path, &|name| name == "DEHACKED")? {
+        names.extend(dehacked::level_names(&dehacked::parse_strings(&String::from_utf8_lossy(&lump.data))));
+    }
+    for patch in patches {
+        let data = std::fs::read(patch).map_err(|e| format!("Failed to read {}: {}", patch, e))?;
+        names.extend(dehacked::level_names(&dehacked::parse_strings(&String::from_utf8_lossy(&data))));
+    }
+    names.extend(extract_levels(path, None)?.into_iter().filter_map(|l| Some((l.id, l.name?))));
+    Ok(names)
+}
+
+/// A level's name in a load order, and where it came from.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedLevelName {
+    pub map: String,
+    pub name: String,
+    pub defined_in: String,
+    /// The earlier file whose name for this map this one replaces.
+    pub replaces: Option<String>,
+}
+
+/// The level names a load order (IWAD first, when given) shows in game,
+/// sorted by map: each file's names as from extract_level_names, a later
+/// file's name for a map replacing an earlier one's.
+pub fn merge_level_names(files: &[String]) -> Result<Vec<MergedLevelName>, String> {
+    let mut merged: HashMap<String, MergedLevelName> = HashMap::new();
+    for file in files {
+        for (map, name) in extract_level_names(file)? {
+            match merged.get_mut(&map) {
+                Some(previous) => {
+                    if previous.defined_in != *file {
+                        previous.replaces = Some(std::mem::replace(&mut previous.defined_in, file.clone()));
+                    }
+                    previous.name = name;
+                }
+                None => {
+                    merged.insert(map.clone(), MergedLevelName { map, name, defined_in: file.clone(), replaces: None });
+                }
+            }
+        }
+    }
+    let mut out: Vec<MergedLevelName> = merged.into_values().collect();
+    out.sort_by(|a, b| a.map.cmp(&b.map));
+    Ok(out)
+}
+
+/// One file's name for a map.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelNameDefinition {
+    pub file: String,
+    pub name: String,
+}
+
+/// A map named by more than one file of a load order.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelNameCollision {
+    pub map: String,
+    /// In load order; the last is the name shown in game.
+    pub definitions: Vec<LevelNameDefinition>,
+}
+
+/// Every map that more than one file of a load order (IWAD first, when
+/// given) names, sorted by map, with each file's name as from
+/// extract_level_names. Files agreeing on a name still count, since the
+/// later one still overrides the earlier.
+pub fn level_name_collisions(files: &[String]) -> Result<Vec<LevelNameCollision>, String> {
+    let mut by_map: BTreeMap<String, Vec<LevelNameDefinition>> = BTreeMap::new();
+    for file in files {
+        for (map, name) in extract_level_names(file)? {
+            by_map.entry(map).or_default().push(LevelNameDefinition { file: file.clone(), name });
+        }
+    }
+    Ok(by_map
+        .into_iter()
+        .filter(|(_, definitions)| definitions.len() > 1)
+        .map(|(map, definitions)| LevelNameCollision { map, definitions })
+        .collect())
+}
+
+/// Lumps that make up a map after its marker, in any of the three formats.
+pub(crate) const MAP_DATA_LUMPS: &[&str] = &[
+    "THINGS", "LINEDEFS", "SIDEDEFS", "VERTEXES", "SEGS", "SSECTORS", "NODES", "SECTORS", "REJECT",
+    "BLOCKMAP", "BEHAVIOR", "SCRIPTS", "TEXTMAP", "ZNODES", "DIALOGUE", "ENDMAP",
+];
+
+/// How a map's geometry is stored, which decides the ports that can run it.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MapFormat {
+    /// Vanilla binary format, playable everywhere.
+    Doom,
+    /// Binary with a BEHAVIOR (ACS) lump; ZDoom family and Hexen only.
+    Hexen,
+    /// Text TEXTMAP; no support in PrBoom+/Chocolate-family ports.
+    Udmf,
+}
+
+/// A map present in a file, whether or not MAPINFO names it.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MapEntry {
+    pub id: String,
+    pub name: Option<String>,
+    /// None for a bare marker with no map data after it.
+    pub format: Option<MapFormat>,
+    /// Monster, item and secret totals at the skill asked for; None when
+    /// the map lumps are missing or too large to read.
+    pub stats: Option<map_stats::MapStats>,
+    /// MAPINFO cluster; for Hexen, the hub the map belongs to.
+    pub cluster: Option<u32>,
+}
+
+/// Format of the map whose data lumps are `data` (the lumps after the marker).
+fn map_format(data: &[String]) -> Option<MapFormat> {
+    if data.iter().any(|l| l == "TEXTMAP") {
+        Some(MapFormat::Udmf)
+    } else if data.iter().any(|l| l == "BEHAVIOR") {
+        Some(MapFormat::Hexen)
+    } else if data.is_empty() {
+        None
+    } else {
+        Some(MapFormat::Doom)
+    }
+}
+
+/// Map markers in a lump directory, as (marker index, number of map data
+/// lumps after it): MAPxx/ExMy names, and any other lump directly followed
+/// by map data (GZDoom allows arbitrary map names).
+fn map_markers(names: &[String]) -> Vec<(usize, usize)> {
+    let is_data = |name: &str| MAP_DATA_LUMPS.contains(&name);
+    let mut out = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        if is_data(name) {
+            continue;
+        }
+        let data_len = names[i + 1..].iter().take_while(|n| is_data(n)).count();
+        if data_len > 0 || is_mapxx_marker(name) || is_exmy_marker(name) {
+            out.push((i, data_len));
+        }
+    }
+    out
+}
+
+/// One map of a file with the data lumps a caller asked for.
+pub(crate) struct MapLumps {
+    pub id: String,
+    pub format: Option<MapFormat>,
+    /// The wanted lumps present in the map, or why they couldn't be read
+    /// (a lump over the in-memory cap).
+    pub lumps: Result<Vec<NamedLump>, String>,
+}
+
+impl MapLumps {
+    /// A wanted lump's data; empty if the map doesn't have it.
+    pub fn data(&self, name: &str) -> Result<&[u8], String> {
+        let lumps = self.lumps.as_ref().map_err(Clone::clone)?;
+        Ok(lumps.iter().find(|l| l.name == name).map_or(&[][..], |l| &l.data[..]))
+    }
+}
+
+/// Maps in one WAD file, in directory order.
+fn wad_maps(path: &str, wanted: &[&str]) -> Result<Vec<MapLumps>, String> {
+    let mut file = open(path)?;
+    let header = read_header(&mut file, path)?;
+    let lumps = read_checked_directory(&mut file, path, &header)?;
+    let names: Vec<String> = lumps.iter().map(|l| l.name.clone()).collect();
+    let mut maps = Vec::new();
+    for (i, data_len) in map_markers(&names) {
+        let data = lumps[i + 1..i + 1 + data_len]
+            .iter()
+            .filter(|l| wanted.contains(&l.name.as_str()))
+            .map(|l| Ok(NamedLump { name: l.name.clone(), data: read_lump(&mut file, path, l)? }))
+            .collect();
+        maps.push(MapLumps {
+            id: names[i].clone(),
+            format: map_format(&names[i + 1..i + 1 + data_len]),
+            lumps: data,
+        });
+    }
+    Ok(maps)
+}
+
+/// Run `f` on a WAD nested in a ZIP, extracted to a temp file for the call.
+fn with_nested_wad<T>(
+    zip_path: &str,
+    entry_path: &str,
+    f: impl FnOnce(&str) -> Result<T, String>,
+) -> Result<T, String> {
+    let temp = game_archives::extract_zip_entry_to_temp(zip_path, entry_path)?;
+    let result = f(&temp);
+    if let Some(dir) = std::path::Path::new(&temp).parent() {
+        game_archives::cleanup_temp_dir(&dir.to_string_lossy())?;
+    }
+    result
+}
+
+/// Maps in a WAD or ZIP/PK3 with their `wanted` data lumps, in directory
+/// order. A PK3 map is its own WAD under maps/ and takes its name from the
+/// file, not the marker inside.
+pub(crate) fn read_maps(path: &str, wanted: &[&str]) -> Result<Vec<MapLumps>, String> {
+    match detect_wad_type(path)? {
+        WadType::Iwad | WadType::Pwad => wad_maps(path, wanted),
+        WadType::Pk3 | WadType::Ipk3 | WadType::Zip => {
+            let mut maps = Vec::new();
+            let mut nested = Vec::new();
+            for entry in game_archives::list_zip_entries(path)? {
+                let lower = entry.path.to_lowercase();
+                if !lower.ends_with(".wad") {
+                    continue;
+                }
+                if lower.starts_with("maps/") && !lower["maps/".len()..].contains('/') {
+                    let id = zip_entry_lump_name(&entry.path);
+                    let inner = with_nested_wad(path, &entry.path, |wad| wad_maps(wad, wanted))?.into_iter().next();
+                    maps.push(match inner {
+                        Some(map) => MapLumps { id, ..map },
+                        None => MapLumps { id, format: None, lumps: Ok(Vec::new()) },
+                    });
+                } else {
+                    nested.push(entry.path);
+                }
+            }
+            for entry_path in nested {
+                maps.extend(with_nested_wad(path, &entry_path, |wad| read_maps(wad, wanted))?);
+            }
+            Ok(maps)
+        }
+        WadType::Unknown => Err(format!("Not a WAD or ZIP/PK3 file: {}", path)),
+    }
+}
+
+/// Totals for one map from its data lumps.
+fn count_map_stats(map: &MapLumps, skill: u8) -> Result<Option<map_stats::MapStats>, String> {
+    Ok(match map.format {
+        Some(MapFormat::Udmf) => Some(map_stats::udmf_stats(&String::from_utf8_lossy(map.data("TEXTMAP")?), skill)),
+        Some(format) => Some(map_stats::binary_stats(map.data("THINGS")?, map.data("SECTORS")?, format, skill)),
+        None => None,
+    })
+}
+
+/// The marker a MAPINFO map id refers to: Hexen numbers its maps
+/// ("map 1"), while the lump is MAP01.
+pub(crate) fn marker_id(id: &str) -> String {
+    match id.parse::<u32>() {
+        Ok(number) => format!("MAP{:02}", number),
+        Err(_) => id.to_string(),
+    }
+}
+
+/// Every map in a file, named from MAPINFO/DEHACKED where it can be, with
+/// its totals at `skill` (1-5). Unlike extract_levels this includes vanilla
+/// maps no MAPINFO mentions.
+pub fn list_maps(path: &str, skill: u8) -> Result<Vec<MapEntry>, String> {
+    let mut names: HashMap<String, String> =
+        extract_level_names(path)?.into_iter().map(|(id, name)| (marker_id(&id), name)).collect();
+    let clusters: HashMap<String, u32> =
+        extract_levels(path, None)?.into_iter().filter_map(|l| Some((marker_id(&l.id), l.cluster?))).collect();
+    let mut seen = std::collections::HashSet::new();
+    let mut out = Vec::new();
+    for map in read_maps(path, &["THINGS", "SECTORS", "TEXTMAP"])? {
+        if !seen.insert(map.id.clone()) {
+            continue;
+        }
+        let stats = count_map_stats(&map, skill).unwrap_or_else(|e| {
+            // One oversized map shouldn't hide the rest of the list.
+            eprintln!("Failed to count things in {} of {}: {}", map.id, path, e);
+            None
+        });
+        let cluster = clusters.get(&map.id).copied();
+        out.push(MapEntry { name: names.remove(&map.id), id: map.id, format: map.format, stats, cluster });
+    }
+    Ok(out)
+}
+
+/// A Hexen hub: maps that share a cluster and keep their state while the
+/// player travels between them.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Hub {
+    pub cluster: u32,
+    /// Map ids, in directory order.
+    pub maps: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LevelListing {
+    /// Frontend IWAD id of the game the file is (an IWAD) or is for (a
+    /// PWAD, as guessed by detect_required_iwad); None when unknown.
+    pub game: Option<String>,
+    pub maps: Vec<MapEntry>,
+    /// Hexen's hubs, in order of their first map; empty for other games,
+    /// whose clusters only pick the intermission text.
+    pub hubs: Vec<Hub>,
+}
+
+/// The game a file is or is meant for: the IWAD's own identity, else the
+/// required-IWAD guess.
+pub fn detect_game(path: &str) -> Result<Option<String>, String> {
+    match detect_wad_type(path)? {
+        WadType::Iwad | WadType::Ipk3 => Ok(iwad::identify_iwad(path)?.game),
+        _ => Ok(detect_required_iwad(path)?.iwad),
+    }
+}
+
+/// The maps of a file together with the game they belong to and, for
+/// Hexen, the hubs they are grouped in.
+pub fn list_levels(path: &str, skill: u8) -> Result<LevelListing, String> {
+    let game = detect_game(path)?;
+    let maps = list_maps(path, skill)?;
+    let mut hubs: Vec<Hub> = Vec::new();
+    if game.as_deref() == Some("hexen") {
+        for map in &maps {
+            let Some(cluster) = map.cluster else { continue };
+            match hubs.iter_mut().find(|hub| hub.cluster == cluster) {
+                Some(hub) => hub.maps.push(map.id.clone()),
+                None => hubs.push(Hub { cluster, maps: vec![map.id.clone()] }),
+            }
+        }
+    }
+    Ok(LevelListing { game, maps, hubs })
+}
+
+/// Lump (or entry stem) names mods use for a plain-text description, most
+/// telling first.
+const DESCRIPTION_LUMPS: &[&str] = &["README", "INFO", "WADINFO", "ABOUT", "DESCRIPT", "CREDITS"];
+
+/// A mod's embedded plain-text description.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModDescription {
+    /// Lump name, or entry path inside a ZIP/PK3.
+    pub source: String,
+    /// Line endings normalized to "\n".
+    pub text: String,
+}
+
+/// Decode a description if it looks like text: no NUL bytes, not blank.
+fn as_description(source: &str, data: &[u8]) -> Option<ModDescription> {
+    if data.contains(&0) {
+        return None;
+    }
+    let text = String::from_utf8_lossy(data).replace("\r\n", "\n");
+    (!text.trim().is_empty()).then(|| ModDescription { source: source.to_string(), text })
+}
+
+/// How good a ZIP entry is as the description: README-style names first,
+/// then the text file idgames uploads ship next to the WAD ("sc2.txt" for
+/// sc2.wad or sc2.zip), then, in plain ZIPs only, any top-level text file.
+/// A PK3's other .txt entries are definition lumps (mapinfo.txt).
+fn description_rank(entry_path: &str, game_stems: &[String], plain_zip: bool) -> Option<usize> {
+    let lower = entry_path.to_lowercase();
+    let is_text = [".txt", ".nfo", ".md"].iter().any(|ext| lower.ends_with(ext));
+    let stem = zip_entry_lump_name(entry_path);
+    if let Some(i) = DESCRIPTION_LUMPS.iter().position(|name| stem.starts_with(name)) {
+        return (is_text || !lower.contains('.')).then_some(i);
+    }
+    if !is_text || lower.contains('/') {
+        return None;
+    }
+    if game_stems.contains(&stem) {
+        Some(DESCRIPTION_LUMPS.len())
+    } else {
+        plain_zip.then_some(DESCRIPTION_LUMPS.len() + 1)
+    }
+}
+
+/// The plain-text description a WAD, PK3 or idgames ZIP carries, if any:
+/// a README/INFO-style lump or entry, or the .txt shipped alongside the
+/// WAD. WADs nested in an archive are searched when the archive itself has
+/// none.
+pub fn extract_description(path: &str) -> Result<Option<ModDescription>, String> {
+    let wad_type = detect_wad_type(path)?;
+    match wad_type {
+        WadType::Iwad | WadType::Pwad => {
+            let lumps = collect_lumps(path, &|name| DESCRIPTION_LUMPS.contains(&name))?;
+            Ok(DESCRIPTION_LUMPS.iter().find_map(|wanted| {
+                lumps.iter().filter(|l| l.name == *wanted).find_map(|l| as_description(&l.name, &l.data))
+            }))
+        }
+        WadType::Pk3 | WadType::Ipk3 | WadType::Zip => {
+            let entries = game_archives::list_zip_entries(path)?;
+            let is_game = |p: &str| [".wad", ".pk3", ".pk7"].iter().any(|ext| p.to_lowercase().ends_with(ext));
+            let mut game_stems: Vec<String> =
+                entries.iter().filter(|e| is_game(&e.path)).map(|e| zip_entry_lump_name(&e.path)).collect();
+            game_stems.push(zip_entry_lump_name(path));
+            let mut ranked: Vec<(usize, &str)> = entries
+                .iter()
+                .filter_map(|e| Some((description_rank(&e.path, &game_stems, wad_type == WadType::Zip)?, e.path.as_str())))
+                .collect();
+            ranked.sort_by_key(|(rank, _)| *rank);
+            for (_, entry_path) in ranked {
+                let data = game_archives::read_zip_entry(path, entry_path)?;
+                if let Some(description) = as_description(entry_path, &data) {
+                    return Ok(Some(description));
+                }
+            }
+            for entry in entries.iter().filter(|e| e.path.to_lowercase().ends_with(".wad")) {
+                if let Some(description) = with_nested_wad(path, &entry.path, extract_description)? {
+                    return Ok(Some(description));
+                }
+            }
+            Ok(None)
+        }
+        WadType::Unknown => Err(format!("Not a WAD or ZIP/PK3 file: {}", path)),
+    }
+}
+
+/// What kind of Doom data file something is, from its magic bytes. ZIP-based
+/// files are told apart by extension, since the container is identical.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WadType {
+    Iwad,
+    Pwad,
+    /// .pk3/.pke: a ZIP laid out as a GZDoom resource archive.
+    Pk3,
+    /// .ipk3: a standalone game (IWAD) packaged as a PK3.
+    Ipk3,
+    /// Any other ZIP, typically an idgames download wrapping the WAD.
+    Zip,
+    Unknown,
+}
+
+/// Classify a file by its first four bytes. Files shorter than that are
+/// Unknown rather than an error.
+pub fn detect_wad_type(path: &str) -> Result<WadType, String> {
+    let mut file = open(path)?;
+    let mut magic = [0u8; 4];
+    let mut read = 0;
+    while read < magic.len() {
+        let n = file
+            .read(&mut magic[read..])
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if n == 0 {
+            return Ok(WadType::Unknown);
+        }
+        read += n;
+    }
+    Ok(match &magic {
+        b"IWAD" => WadType::Iwad,
+        b"PWAD" => WadType::Pwad,
+        // Local file header, or the end-of-central-directory of an empty zip.
+        b"PK\x03\x04" | b"PK\x05\x06" => {
+            let lower = path.to_lowercase();
+            if lower.ends_with(".ipk3") {
+                WadType::Ipk3
+            } else if lower.ends_with(".pk3") || lower.ends_with(".pke") {
+                WadType::Pk3
+            } else {
+                WadType::Zip
+            }
+        }
+        _ => WadType::Unknown,
+    })
+}
+
+/// NotFound unless `path` is a file.
+pub fn check_exists(path: &str) -> Result<(), LaunchError> {
+    if !std::path::Path::new(path).is_file() {
+        return Err(LaunchError::NotFound { path: path.to_string(), message: format!("File not found: {}", path) });
+    }
+    Ok(())
+}
+
+/// The parser commands' up-front check: NotFound for a missing file and
+/// InvalidWad for one that is neither a WAD nor a ZIP/PK3, or a WAD whose
+/// directory runs past its end (an unfinished download), so the UI can
+/// offer to locate or replace it rather than show a read error. Single
+/// lumps past the end only fail when read.
+pub fn check_file(path: &str) -> Result<WadType, LaunchError> {
+    check_exists(path)?;
+    match detect_wad_type(path)? {
+        WadType::Unknown => Err(LaunchError::InvalidWad {
+            path: path.to_string(),
+            message: format!("Not a WAD or PK3 file: {}", path),
+        }),
+        kind @ (WadType::Iwad | WadType::Pwad) => match read_lumps(path) {
+            Ok(_) => Ok(kind),
+            Err(message) => Err(LaunchError::InvalidWad { path: path.to_string(), message }),
+        },
+        kind => Ok(kind),
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WadHashes {
+    pub md5: String,
+    pub sha1: String,
+    /// SHA-1 of the lump directory (WAD) or of the entry listing — names,
+    /// CRC-32s and sizes — of a ZIP/PK3. None for anything else.
+    pub directory_sha1: Option<String>,
+    /// SHA-1 of the lumps (or ZIP entries) sorted by name and content, so
+    /// a WAD rebuilt with its directory in another order hashes the same.
+    /// Order-blind by design: it can't tell a harmless reordering from one
+    /// that moves a lump between markers. None for anything else.
+    pub content_sha1: Option<String>,
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// MD5 and SHA-1 of the whole file, streamed in 64 KB chunks, plus a hash
+/// of just the directory.
+pub fn wad_checksum(path: &str) -> Result<WadHashes, String> {
+    let mut file = open(path)?;
+    let mut md5 = Md5::new();
+    let mut sha1 = Sha1::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if n == 0 {
+            break;
+        }
+        md5.update(&buf[..n]);
+        sha1.update(&buf[..n]);
+    }
+    Ok(WadHashes {
+        md5: to_hex(&md5.finalize()),
+        sha1: to_hex(&sha1.finalize()),
+        directory_sha1: directory_sha1(&mut file, path)?,
+        content_sha1: content_sha1(&mut file, path)?,
+    })
+}
+
+/// An identity for a file that survives renames and rebuilds: its
+/// content_sha1 for a WAD or ZIP/PK3, else the SHA-1 of the whole file.
+pub fn content_key(path: &str) -> Result<String, String> {
+    match content_sha1(&mut open(path)?, path)? {
+        Some(hash) => Ok(hash),
+        None => Ok(wad_checksum(path)?.sha1),
+    }
+}
+
+/// SHA-1 of one lump's bytes, streamed; None for a lump past the end of
+/// the file.
+fn lump_sha1(file: &mut File, path: &str, lump: &Lump, file_len: u64) -> Result<Option<[u8; 20]>, String> {
+    if lump.offset as u64 + lump.size as u64 > file_len {
+        return Ok(None);
+    }
+    file.seek(SeekFrom::Start(lump.offset as u64))
+        .map_err(|e| format!("Failed to seek {}: {}", path, e))?;
+    let mut hasher = Sha1::new();
+    let mut remaining = lump.size as u64;
+    let mut buf = vec![0u8; 64 * 1024];
+    while remaining > 0 {
+        let n = remaining.min(buf.len() as u64) as usize;
+        file.read_exact(&mut buf[..n])
+            .map_err(|e| format!("Failed to read lump {} from {}: {}", lump.name, path, e))?;
+        hasher.update(&buf[..n]);
+        remaining -= n as u64;
+    }
+    Ok(Some(hasher.finalize().into()))
+}
+
+/// The order-independent content hash: (name, size, content hash) per lump
+/// or entry, sorted, then hashed together. ZIP entries use their CRC-32 as
+/// the content hash, so nothing needs decompressing. None for a WAD with
+/// lumps past its end, whose content can't be hashed.
+fn content_sha1(file: &mut File, path: &str) -> Result<Option<String>, String> {
+    let mut magic = [0u8; 4];
+    file.seek(SeekFrom::Start(0))
+        .map_err(|e| format!("Failed to seek {}: {}", path, e))?;
+    if file.read_exact(&mut magic).is_err() {
+        return Ok(None);
+    }
+    let mut entries: Vec<(String, u64, Vec<u8>)> = Vec::new();
+    if &magic == b"IWAD" || &magic == b"PWAD" {
+        let header = read_header(file, path)?;
+        let len = file_len(file, path)?;
+        for lump in parse_directory(&read_directory_bytes(file, path, &header)?) {
+            let Some(digest) = lump_sha1(file, path, &lump, len)? else {
+                eprintln!("No content hash for {}: {}", path, past_end(path, &lump, len));
+                return Ok(None);
+            };
+            entries.push((lump.name, lump.size as u64, digest.to_vec()));
+        }
+    } else if magic[..2] == *b"PK" {
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| format!("Failed to seek {}: {}", path, e))?;
+        let mut archive = zip::ZipArchive::new(&mut *file)
+            .map_err(|e| format!("Failed to read ZIP {}: {}", path, e))?;
+        for i in 0..archive.len() {
+            let entry = archive
+                .by_index_raw(i)
+                .map_err(|e| format!("Failed to read ZIP entry #{}: {}", i, e))?;
+            if !entry.is_dir() {
+                entries.push((entry.name().to_lowercase(), entry.size(), entry.crc32().to_le_bytes().to_vec()));
+            }
+        }
+    } else {
+        return Ok(None);
+    }
+    entries.sort();
+    let mut hasher = Sha1::new();
+    for (name, size, digest) in &entries {
+        hasher.update(name.as_bytes());
+        hasher.update([0]);
+        hasher.update(size.to_le_bytes());
+        hasher.update(digest);
+    }
+    Ok(Some(to_hex(&hasher.finalize())))
+}
+
+/// MD5 of the whole file, streamed like `wad_checksum` but without the
+/// SHA-1 and directory hashes.
+pub fn file_md5(path: &str) -> Result<String, String> {
+    let mut file = open(path)?;
+    let mut md5 = Md5::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if n == 0 {
+            break;
+        }
+        md5.update(&buf[..n]);
+    }
+    Ok(to_hex(&md5.finalize()))
+}
+
+fn directory_sha1(file: &mut File, path: &str) -> Result<Option<String>, String> {
+    let mut magic = [0u8; 4];
+    file.seek(SeekFrom::Start(0))
+        .map_err(|e| format!("Failed to seek {}: {}", path, e))?;
+    if file.read_exact(&mut magic).is_err() {
+        return Ok(None);
+    }
+    if &magic == b"IWAD" || &magic == b"PWAD" {
+        let header = read_header(file, path)?;
+        let dir = read_directory_bytes(file, path, &header)?;
+        return Ok(Some(to_hex(&Sha1::digest(&dir))));
+    }
+    if magic[..2] == *b"PK" {
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| format!("Failed to seek {}: {}", path, e))?;
+        let mut archive = zip::ZipArchive::new(&mut *file)
+            .map_err(|e| format!("Failed to read ZIP {}: {}", path, e))?;
+        let mut hasher = Sha1::new();
+        for i in 0..archive.len() {
+            let entry = archive
+                .by_index_raw(i)
+                .map_err(|e| format!("Failed to read ZIP entry #{}: {}", i, e))?;
+            hasher.update(entry.name().as_bytes());
+            hasher.update(entry.crc32().to_le_bytes());
+            hasher.update(entry.size().to_le_bytes());
+        }
+        return Ok(Some(to_hex(&hasher.finalize())));
+    }
+    Ok(None)
+}
+
+/// The file's own PLAYPAL if it has one, else the one in `palette_wad`.
+fn resolve_palette(own: Option<&NamedLump>, palette_wad: Option<&str>) -> Result<picture::Palette, String> {
+    if let Some(lump) = own {
+        return picture::palette_from_playpal(&lump.data);
+    }
+    let source = palette_wad.ok_or("No PLAYPAL in the file; an IWAD is needed for the palette")?;
+    let lump = collect_lumps(source, &|name| name == "PLAYPAL")?
+        .pop()
+        .ok_or_else(|| format!("No PLAYPAL in {}", source))?;
+    picture::palette_from_playpal(&lump.data)
+}
+
+/// The palette graphics in `path` are drawn with: the first of the 14
+/// PLAYPAL palettes, from the last PLAYPAL in the file (a PWAD may override
+/// the IWAD's), else from `palette_wad`.
+pub fn read_palette(path: &str, palette_wad: Option<&str>) -> Result<picture::Palette, String> {
+    let lumps = collect_lumps(path, &|name| name == "PLAYPAL")?;
+    resolve_palette(lumps.last(), palette_wad)
+}
+
+/// A graphic from a WAD/PK3, converted for display.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Graphic {
+    pub width: u32,
+    pub height: u32,
+    /// PNG as a data URL, ready for an <img src>.
+    pub data_url: String,
+}
+
+/// The last lump named by the first of `names` the file has (later lumps
+/// override earlier ones, as in the engine), as a PNG. Indexed graphics use
+/// the file's own PLAYPAL, else the one in `palette_wad`; PNGs pass through
+/// as-is. None if the file has none of them.
+fn extract_graphic(path: &str, names: &[&str], palette_wad: Option<&str>) -> Result<Option<Graphic>, String> {
+    let lumps = collect_lumps(path, &|name| name == "PLAYPAL" || names.contains(&name))?;
+    let Some(lump) = names.iter().find_map(|name| lumps.iter().rev().find(|l| l.name == *name)) else {
+        return Ok(None);
+    };
+
+    if let Some((width, height)) = picture::png_size(&lump.data) {
+        return Ok(Some(Graphic { width, height, data_url: picture::png_data_url(&lump.data) }));
+    }
+
+    let palette = resolve_palette(lumps.iter().rev().find(|l| l.name == "PLAYPAL"), palette_wad)?;
+    let image = picture::decode_picture(&lump.data, &palette)
+        .or_else(|| picture::decode_raw_screen(&lump.data, &palette))
+        .ok_or_else(|| format!("{} in {} is not a picture, raw screen or PNG", lump.name, path))?;
+    Ok(Some(Graphic {
+        width: image.width,
+        height: image.height,
+        data_url: picture::png_data_url(&picture::encode_png(&image)?),
+    }))
+}
+
+/// The title screen of a WAD/PK3 as a PNG: TITLEPIC, or Heretic/Hexen's raw
+/// TITLE. The palette comes from the file or `palette_wad` (normally the
+/// IWAD). None if the file has no title graphic.
+pub fn extract_titlepic(path: &str, palette_wad: Option<&str>) -> Result<Option<Graphic>, String> {
+    extract_graphic(path, &["TITLEPIC", "TITLE"], palette_wad)
+}
+
+/// The status bar background of a WAD/PK3 as a PNG, for previewing a HUD
+/// replacement: Doom's STBAR (a PK3's graphics/stbar.png counts), else
+/// Heretic's BARBACK or Hexen's H2BAR. None if the file doesn't replace it.
+pub fn extract_status_bar(path: &str, palette_wad: Option<&str>) -> Result<Option<Graphic>, String> {
+    extract_graphic(path, &["STBAR", "BARBACK", "H2BAR"], palette_wad)
+}
+
+/// One sprite frame, converted for display.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpriteFrame {
+    /// Lump name: prefix, frame letter and rotation ("TROOA1"), with a
+    /// second frame and rotation when one lump serves two ("TROOA2A8").
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub data_url: String,
+}
+
+/// The sprite lumps of `path` whose name starts with `prefix`: between
+/// S_START/S_END (or SS_START/SS_END) in a WAD, under sprites/ in a PK3. A
+/// name defined twice keeps the later lump.
+fn sprite_lumps(path: &str, prefix: &str) -> Result<Vec<NamedLump>, String> {
+    let mut out: Vec<NamedLump> = Vec::new();
+    match detect_wad_type(path)? {
+        WadType::Iwad | WadType::Pwad => {
+            let mut file = open(path)?;
+            let header = read_header(&mut file, path)?;
+            let mut in_sprites = false;
+            for lump in read_checked_directory(&mut file, path, &header)? {
+                match lump.name.as_str() {
+                    "S_START" | "SS_START" => in_sprites = true,
+                    "S_END" | "SS_END" => in_sprites = false,
+                    name if in_sprites && name.starts_with(prefix) => {
+                        out.push(NamedLump { name: lump.name.clone(), data: read_lump(&mut file, path, &lump)? })
+                    }
+                    _ => {}
+                }
+            }
+        }
+        WadType::Pk3 | WadType::Ipk3 | WadType::Zip => {
+            for entry in game_archives::list_zip_entries(path)? {
+                let name = zip_entry_lump_name(&entry.path);
+                if entry.path.to_lowercase().starts_with("sprites/") && !entry.path.ends_with('/') && name.starts_with(prefix) {
+                    out.push(NamedLump { name, data: game_archives::read_zip_entry(path, &entry.path)? });
+                }
+            }
+        }
+        WadType::Unknown => return Err(format!("Not a WAD or ZIP/PK3 file: {}", path)),
+    }
+    let mut last: HashMap<String, NamedLump> = HashMap::new();
+    for lump in out {
+        last.insert(lump.name.clone(), lump);
+    }
+    let mut lumps: Vec<NamedLump> = last.into_values().collect();
+    lumps.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(lumps)
+}
+
+/// The frames of the sprite `prefix` ("TROO", "PISG"; four characters as
+/// in DECORATE) in a WAD/PK3, sorted by name, as PNGs for previewing a
+/// gameplay mod's monsters and weapons. Doom-format sprites use the file's
+/// PLAYPAL, else `palette_wad`'s; PNGs pass through. A lump that is
+/// neither is skipped.
+pub fn extract_sprites(path: &str, prefix: &str, palette_wad: Option<&str>) -> Result<Vec<SpriteFrame>, String> {
+    let prefix = prefix.trim().to_uppercase();
+    if prefix.chars().count() != 4 {
+        return Err(format!("Sprite prefix must be four characters, got \"{}\"", prefix));
+    }
+    let lumps = sprite_lumps(path, &prefix)?;
+    let palette = match lumps.iter().any(|l| picture::png_size(&l.data).is_none()) {
+        true => Some(read_palette(path, palette_wad)?),
+        false => None,
+    };
+    let mut frames = Vec::new();
+    for lump in lumps {
+        if let Some((width, height)) = picture::png_size(&lump.data) {
+            frames.push(SpriteFrame { name: lump.name, width, height, data_url: picture::png_data_url(&lump.data) });
+            continue;
+        }
+        match palette.as_ref().and_then(|palette| picture::decode_picture(&lump.data, palette)) {
+            Some(image) => frames.push(SpriteFrame {
+                name: lump.name,
+                width: image.width,
+                height: image.height,
+                data_url: picture::png_data_url(&picture::encode_png(&image)?),
+            }),
+            None => eprintln!("Sprite {} in {} is not a picture or PNG, skipped", lump.name, path),
+        }
+    }
+    Ok(frames)
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// The mod says so itself (GAMEINFO).
+    High,
+    /// Inferred from map markers.
+    Medium,
+    /// A guess from mixed or missing evidence.
+    Low,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IwadGuess {
+    /// Frontend IWAD id ("doom", "doom2", …); None when there's nothing to
+    /// go on.
+    pub iwad: Option<String>,
+    pub confidence: Confidence,
+    /// Short explanation for the UI, e.g. "GAMEINFO names doom2.wad".
+    pub reason: String,
+}
+
+/// IWAD filename -> frontend IWAD id.
+const IWAD_FILES: &[(&str, &str)] = &[
+    ("doom.wad", "doom"),
+    ("doomu.wad", "doom"),
+    ("doom1.wad", "doom"),
+    ("doom2.wad", "doom2"),
+    ("plutonia.wad", "plutonia"),
+    ("tnt.wad", "tnt"),
+    ("heretic.wad", "heretic"),
+    ("heretic1.wad", "heretic"),
+    ("hexen.wad", "hexen"),
+    ("strife1.wad", "strife"),
+    ("strife0.wad", "strife"),
+    ("freedoom1.wad", "freedoom1"),
+    ("freedoom2.wad", "freedoom2"),
+];
+
+/// The `IWAD = "doom2.wad"` value of a ZDoom GAMEINFO lump.
+fn gameinfo_iwad(src: &str) -> Option<String> {
+    src.lines().find_map(|line| {
+        let line = line.split("//").next().unwrap_or(line);
+        let (key, value) = line.split_once('=')?;
+        if !key.trim().eq_ignore_ascii_case("iwad") {
+            return None;
+        }
+        let value = value.trim().trim_matches('"').trim();
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+pub(crate) fn is_mapxx_marker(name: &str) -> bool {
+    let b = name.as_bytes();
+    b.len() == 5 && name.starts_with("MAP") && b[3..].iter().all(u8::is_ascii_digit)
+}
+
+pub(crate) fn is_exmy_marker(name: &str) -> bool {
+    let b = name.as_bytes();
+    b.len() == 4 && b[0] == b'E' && b[1].is_ascii_digit() && b[2] == b'M' && b[3].is_ascii_digit()
+}
+
+/// Guess the IWAD a PWAD/PK3 is meant to be played with. GAMEINFO's `IWAD`
+/// line is authoritative; otherwise map markers decide between Doom II
+/// (MAPxx) and Doom (ExMy). Markers can't tell Doom II from Final DOOM, or
+/// Doom from Heretic — except that Heretic alone has a fifth episode — nor
+/// Doom II from Hexen, unless an old-style MAPINFO uses Hexen's `warptrans`.
+pub fn detect_required_iwad(path: &str) -> Result<IwadGuess, String> {
+    let lumps = collect_lumps(path, &|name| {
+        name == "GAMEINFO" || name == "MAPINFO" || is_mapxx_marker(name) || is_exmy_marker(name)
+    })?;
+
+    let mut unknown_iwad = None;
+    for lump in lumps.iter().filter(|l| l.name == "GAMEINFO") {
+        let Some(file) = gameinfo_iwad(&String::from_utf8_lossy(&lump.data)) else {
+            continue;
+        };
+        let base = file.rsplit(['/', '\\']).next().unwrap_or(&file).to_lowercase();
+        if let Some((_, id)) = IWAD_FILES.iter().find(|(f, _)| *f == base) {
+            return Ok(IwadGuess {
+                iwad: Some(id.to_string()),
+                confidence: Confidence::High,
+                reason: format!("GAMEINFO names {}", file),
+            });
+        }
+        unknown_iwad = Some(file);
+    }
+
+    let mapxx = lumps.iter().filter(|l| is_mapxx_marker(&l.name)).count();
+    let exmy = lumps.iter().filter(|l| is_exmy_marker(&l.name)).count();
+    let heretic = lumps.iter().any(|l| l.name.starts_with("E5M"));
+    let hexen = lumps.iter().filter(|l| l.name == "MAPINFO").any(|l| {
+        mapinfo::parse_blocks(&String::from_utf8_lossy(&l.data))
+            .iter()
+            .any(|b| b.kind == "map" && b.has("warptrans"))
+    });
+    let (iwad, mut confidence, mut reason) = match (mapxx, exmy) {
+        (0, 0) => (None, Confidence::Low, "No map markers found".to_string()),
+        (_, 0) if hexen => (Some("hexen"), Confidence::Medium, "Hexen-style MAPINFO (warptrans)".to_string()),
+        (_, 0) => (Some("doom2"), Confidence::Medium, format!("{} MAPxx map(s)", mapxx)),
+        (0, _) if heretic => (Some("heretic"), Confidence::Medium, "Episode 5 maps (Heretic)".to_string()),
+        (0, _) => (Some("doom"), Confidence::Medium, format!("{} ExMy map(s)", exmy)),
+        _ => (
+            Some(if mapxx >= exmy { "doom2" } else { "doom" }),
+            Confidence::Low,
+            format!("Mixed markers: {} MAPxx, {} ExMy", mapxx, exmy),
+        ),
+    };
+    if let Some(file) = unknown_iwad {
+        // A GAMEINFO naming a game we don't know: markers are only a hint.
+        confidence = Confidence::Low;
+        reason = format!("GAMEINFO names unknown IWAD {}; {}", file, reason);
+    }
+    Ok(IwadGuess { iwad: iwad.map(str::to_string), confidence, reason })
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    /// Build a WAD image from (name, data) lumps, directory at the end.
+    pub fn build_wad(magic: &[u8; 4], lumps: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut dir = Vec::new();
+        let mut offset = 12u32;
+        for (name, bytes) in lumps {
+            data.extend_from_slice(bytes);
+            dir.extend_from_slice(&offset.to_le_bytes());
+            dir.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            let mut padded = [0u8; 8];
+            padded[..name.len()].copy_from_slice(name.as_bytes());
+            dir.extend_from_slice(&padded);
+            offset += bytes.len() as u32;
+        }
+        let mut out = Vec::new();
+        out.extend_from_slice(magic);
+        out.extend_from_slice(&(lumps.len() as u32).to_le_bytes());
+        out.extend_from_slice(&offset.to_le_bytes());
+        out.extend_from_slice(&data);
+        out.extend_from_slice(&dir);
+        out
+    }
+
+    /// Build a ZIP from (entry path, data) pairs.
+    pub fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        use std::io::Write;
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Write `bytes` to a per-process temp file and return its path.
+    pub fn write_temp(name: &str, bytes: &[u8]) -> String {
+        let dir = std::env::temp_dir().join(format!("wad_parser_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path.to_string_lossy().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_util::*;
+    use super::*;
+
+    #[test]
+    fn reads_lump_directory() {
+        let wad = build_wad(b"PWAD", &[("MAP01", b""), ("things", b"1234"), ("MAPINFO", b"map")]);
+        let path = write_temp("dir.wad", &wad);
+        let lumps = read_lumps(&path).unwrap();
+        assert_eq!(
+            lumps,
+            vec![
+                Lump { name: "MAP01".into(), offset: 12, size: 0 },
+                Lump { name: "THINGS".into(), offset: 12, size: 4 },
+                Lump { name: "MAPINFO".into(), offset: 16, size: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_directory_past_end_of_file() {
+        let mut wad = build_wad(b"IWAD", &[("PLAYPAL", b"xx")]);
+        wad[4..8].copy_from_slice(&1000u32.to_le_bytes());
+        let path = write_temp("bogus_dir.wad", &wad);
+        let err = read_lumps(&path).unwrap_err();
+        assert!(err.contains("extends past the end"), "{}", err);
+    }
+
+    #[test]
+    fn reports_truncated_lumps_with_offsets() {
+        let mut wad = build_wad(b"PWAD", &[("MAPINFO", b"map MAP01 \"Entryway\""), ("THINGS", b"1234")]);
+        let dir = u32::from_le_bytes([wad[8], wad[9], wad[10], wad[11]]) as usize;
+        wad[dir + 20..dir + 24].copy_from_slice(&5000u32.to_le_bytes());
+        let path = write_temp("truncated.wad", &wad);
+        let err = collect_lumps(&path, &|n| n == "THINGS").unwrap_err();
+        assert!(err.contains("appears truncated or corrupt: lump THINGS (5000 bytes at offset"), "{}", err);
+        // Lumps that aren't read are fine, as they are to GZDoom.
+        assert_eq!(extract_levels(&path, None).unwrap()[0].name.as_deref(), Some("Entryway"));
+        assert_eq!(check_file(&path), Ok(WadType::Pwad));
+        let hashes = wad_checksum(&path).unwrap();
+        assert_eq!((hashes.md5.len(), hashes.content_sha1), (32, None));
+        assert_eq!(content_key(&path).unwrap(), hashes.sha1);
+    }
+
+    #[test]
+    fn extracts_level_names_from_wad() {
+        let wad = build_wad(
+            b"PWAD",
+            &[
+                ("MAPINFO", b"map MAP01 \"Entryway\"\nmap MAP02 \"Underhalls\"\n"),
+                ("UMAPINFO", b"MAP MAP01 { levelname = \"Ignored\" }\nMAP MAP03 { levelname = \"Third\" }"),
+            ],
+        );
+        let levels = extract_level_names(&write_temp("names.wad", &wad)).unwrap();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels["MAP01"], "Entryway");
+        assert_eq!(levels["MAP03"], "Third");
+    }
+
+    #[test]
+    fn extracts_level_names_from_pk3_and_nested_wads() {
+        let inner = build_wad(b"PWAD", &[("MAPINFO", b"map MAP05 \"Inner\"")]);
+        let pk3 = build_zip(&[
+            ("zmapinfo.txt", b"map MAP01 \"Root\" { }"),
+            ("maps/UMAPINFO.lmp", b"MAP MAP02 { levelname = \"In maps\" }"),
+            ("maps/map05.wad", &inner),
+            ("maps/map06.wad", b"PWAD\x01"),
+            ("readme.txt", b"map MAP09 \"Not mapinfo\""),
+        ]);
+        let levels = extract_level_names(&write_temp("names.pk3", &pk3)).unwrap();
+        assert_eq!(levels.len(), 3);
+        assert_eq!(levels["MAP01"], "Root");
+        assert_eq!(levels["MAP02"], "In maps");
+        assert_eq!(levels["MAP05"], "Inner");
+    }
+
+    #[test]
+    fn detects_wad_type_from_magic() {
+        let iwad = write_temp("t.wad", &build_wad(b"IWAD", &[]));
+        let pwad = write_temp("t2.wad", &build_wad(b"PWAD", &[]));
+        assert_eq!(detect_wad_type(&iwad).unwrap(), WadType::Iwad);
+        assert_eq!(detect_wad_type(&pwad).unwrap(), WadType::Pwad);
+        for (name, expected) in [
+            ("t.pk3", WadType::Pk3),
+            ("t.PKE", WadType::Pk3),
+            ("t.ipk3", WadType::Ipk3),
+            ("t.zip", WadType::Zip),
+        ] {
+            let path = write_temp(name, b"PK\x03\x04rest");
+            assert_eq!(detect_wad_type(&path).unwrap(), expected, "{}", name);
+        }
+        assert_eq!(detect_wad_type(&write_temp("t.txt", b"hello")).unwrap(), WadType::Unknown);
+        assert_eq!(detect_wad_type(&write_temp("short.wad", b"IW")).unwrap(), WadType::Unknown);
+        assert!(detect_wad_type("/nonexistent/file.wad").is_err());
+        assert_eq!(check_file(&iwad), Ok(WadType::Iwad));
+        assert!(matches!(check_file("/nonexistent/file.wad"), Err(LaunchError::NotFound { .. })));
+        assert!(matches!(check_file(&write_temp("t.txt", b"hello")), Err(LaunchError::InvalidWad { .. })));
+    }
+
+    #[test]
+    fn hashes_whole_file_and_directory() {
+        let path = write_temp("abc.bin", b"abc");
+        let hashes = wad_checksum(&path).unwrap();
+        assert_eq!(hashes.md5, "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(hashes.sha1, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(hashes.directory_sha1, None);
+
+        // Same directory, different lump contents: file hashes differ,
+        // directory hash doesn't.
+        let a = write_temp("a.wad", &build_wad(b"PWAD", &[("MAP01", b"aaaa")]));
+        let b = write_temp("b.wad", &build_wad(b"PWAD", &[("MAP01", b"bbbb")]));
+        let (ha, hb) = (wad_checksum(&a).unwrap(), wad_checksum(&b).unwrap());
+        assert_ne!(ha.md5, hb.md5);
+        assert!(ha.directory_sha1.is_some());
+        assert_eq!(ha.directory_sha1, hb.directory_sha1);
+        assert_ne!(ha.content_sha1, hb.content_sha1);
+    }
+
+    #[test]
+    fn content_hash_ignores_lump_order() {
+        let lumps = [("MAP01", &b""[..]), ("THINGS", b"1234"), ("DEHACKED", b"Patch")];
+        let reordered = [lumps[2], lumps[0], lumps[1]];
+        let a = wad_checksum(&write_temp("order_a.wad", &build_wad(b"PWAD", &lumps))).unwrap();
+        let b = wad_checksum(&write_temp("order_b.wad", &build_wad(b"PWAD", &reordered))).unwrap();
+        assert_ne!(a.md5, b.md5);
+        assert!(a.content_sha1.is_some());
+        assert_eq!(a.content_sha1, b.content_sha1);
+
+        let zip_a = wad_checksum(&write_temp("order_a.pk3", &build_zip(&[("a.txt", b"1"), ("b.txt", b"2")]))).unwrap();
+        let zip_b = wad_checksum(&write_temp("order_b.pk3", &build_zip(&[("b.txt", b"2"), ("a.txt", b"1")]))).unwrap();
+        assert_eq!(zip_a.content_sha1, zip_b.content_sha1);
+        assert_eq!(wad_checksum(&write_temp("order.bin", b"abc")).unwrap().content_sha1, None);
+    }
+
+    #[test]
+    fn guesses_required_iwad() {
+        let guess = |name: &str, bytes: &[u8]| detect_required_iwad(&write_temp(name, bytes)).unwrap();
+
+        let g = guess("d2.wad", &build_wad(b"PWAD", &[("MAP01", b""), ("THINGS", b""), ("MAP02", b"")]));
+        assert_eq!((g.iwad.as_deref(), g.confidence), (Some("doom2"), Confidence::Medium));
+
+        let g = guess("d1.wad", &build_wad(b"PWAD", &[("E1M1", b""), ("E4M2", b"")]));
+        assert_eq!((g.iwad.as_deref(), g.confidence), (Some("doom"), Confidence::Medium));
+
+        let g = guess("her.wad", &build_wad(b"PWAD", &[("E5M1", b"")]));
+        assert_eq!(g.iwad.as_deref(), Some("heretic"));
+
+        // GAMEINFO beats the markers of maps nested in the PK3.
+        let inner = build_wad(b"PWAD", &[("MAP01", b"")]);
+        let pk3 = build_zip(&[
+            ("gameinfo.txt", b"// mod\nIWAD = \"Plutonia.wad\"\nLOAD = \"extra.pk3\"\n"),
+            ("maps/map01.wad", &inner),
+        ]);
+        let g = guess("plut.pk3", &pk3);
+        assert_eq!((g.iwad.as_deref(), g.confidence), (Some("plutonia"), Confidence::High));
+
+        let mapinfo = b"map 1 \"Hub\"\nwarptrans 1\nnext 2\ncluster 1\n";
+        let g = guess("hex.wad", &build_wad(b"PWAD", &[("MAPINFO", mapinfo), ("MAP01", b""), ("BEHAVIOR", b"")]));
+        assert_eq!((g.iwad.as_deref(), g.confidence), (Some("hexen"), Confidence::Medium));
+
+        let g = guess("none.wad", &build_wad(b"PWAD", &[("DEHACKED", b"")]));
+        assert_eq!((g.iwad, g.confidence), (None, Confidence::Low));
+    }
+
+    #[test]
+    fn extracts_titlepic_with_own_or_iwad_palette() {
+        use crate::picture::test_util::{picture, playpal};
+        let iwad = write_temp("tp_iwad.wad", &build_wad(b"IWAD", &[("PLAYPAL", &playpal())]));
+        let pwad = write_temp("tp.wad", &build_wad(b"PWAD", &[("TITLEPIC", &picture())]));
+        assert!(extract_titlepic(&pwad, None).unwrap_err().contains("PLAYPAL"));
+        let pic = extract_titlepic(&pwad, Some(&iwad)).unwrap().unwrap();
+        assert_eq!((pic.width, pic.height), (2, 3));
+        assert!(pic.data_url.starts_with("data:image/png;base64,"));
+
+        // PNG titlepics in a PK3 need no palette.
+        let palette = crate::picture::palette_from_playpal(&playpal()).unwrap();
+        let image = crate::picture::decode_picture(&picture(), &palette).unwrap();
+        let png = crate::picture::encode_png(&image).unwrap();
+        let pk3 = write_temp("tp.pk3", &build_zip(&[("graphics/titlepic.png", &png)]));
+        let pic = extract_titlepic(&pk3, None).unwrap().unwrap();
+        assert_eq!(pic.data_url, crate::picture::png_data_url(&png));
+
+        let none = write_temp("tp_none.wad", &build_wad(b"PWAD", &[("MAP01", b"")]));
+        assert_eq!(extract_titlepic(&none, None).unwrap(), None);
+    }
+
+    #[test]
+    fn extracts_status_bar_graphics() {
+        use crate::picture::test_util::{picture, playpal};
+        let hud = write_temp("hud.wad", &build_wad(b"PWAD", &[("PLAYPAL", &playpal()), ("STBAR", &picture())]));
+        let bar = extract_status_bar(&hud, None).unwrap().unwrap();
+        assert_eq!((bar.width, bar.height), (2, 3));
+
+        let heretic = write_temp("hud_heretic.wad", &build_wad(b"PWAD", &[("BARBACK", &picture())]));
+        assert!(extract_status_bar(&heretic, Some(&hud)).unwrap().is_some());
+
+        let none = write_temp("hud_none.pk3", &build_zip(&[("graphics/titlepic.png", b"")]));
+        assert_eq!(extract_status_bar(&none, None).unwrap(), None);
+    }
+
+    #[test]
+    fn lists_lumps_with_namespaces() {
+        let wad = write_temp(
+            "dir.wad",
+            &build_wad(
+                b"PWAD",
+                &[("MAP01", b""), ("FF_START", b""), ("F1_START", b""), ("NUKAGE1", b"4096"), ("FF_END", b""), ("P_START", b""), ("WALL1", b"pic"), ("P_END", b""), ("DEHACKED", b"x")],
+            ),
+        );
+        let lumps = list_wad_lumps(&wad).unwrap();
+        let summary: Vec<(&str, u64, Option<Namespace>)> = lumps.iter().map(|l| (l.name.as_str(), l.size, l.namespace)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("MAP01", 0, None),
+                ("FF_START", 0, None),
+                ("F1_START", 0, Some(Namespace::Flats)),
+                ("NUKAGE1", 4, Some(Namespace::Flats)),
+                ("FF_END", 0, None),
+                ("P_START", 0, None),
+                ("WALL1", 3, Some(Namespace::Patches)),
+                ("P_END", 0, None),
+                ("DEHACKED", 1, None),
+            ]
+        );
+        assert_eq!(lumps[0].offset, Some(HEADER_LEN));
+
+        let pk3 = write_temp("dir.pk3", &build_zip(&[("sprites/trooa1.png", b"png"), ("zscript.txt", b"version")]));
+        let entries = list_wad_lumps(&pk3).unwrap();
+        assert_eq!(entries.iter().map(|e| (e.name.as_str(), e.namespace)).collect::<Vec<_>>(), vec![("TROOA1", Some(Namespace::Sprites)), ("ZSCRIPT", None)]);
+        assert_eq!((entries[1].entry_path.as_deref(), entries[1].size, entries[1].offset), (Some("zscript.txt"), 7, None));
+    }
+
+    #[test]
+    fn later_files_win_merged_level_names() {
+        let base = write_temp("names_base.wad", &build_wad(b"IWAD", &[("MAPINFO", b"map MAP01 \"Entryway\"\nmap MAP02 \"Underhalls\"\n")]));
+        let first = write_temp("names_first.wad", &build_wad(b"PWAD", &[("MAPINFO", b"map MAP01 \"Sunrise\"\n")]));
+        let second = write_temp("names_second.pk3", &build_zip(&[("mapinfo.txt", b"map MAP01 \"Sunset\"\nmap MAP03 \"New\"\n")]));
+        let merged = merge_level_names(&[base.clone(), first.clone(), second.clone()]).unwrap();
+        let summary: Vec<(&str, &str, &str, Option<&str>)> =
+            merged.iter().map(|m| (m.map.as_str(), m.name.as_str(), m.defined_in.as_str(), m.replaces.as_deref())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("MAP01", "Sunset", second.as_str(), Some(first.as_str())),
+                ("MAP02", "Underhalls", base.as_str(), None),
+                ("MAP03", "New", second.as_str(), None),
+            ]
+        );
+
+        let collisions = level_name_collisions(&[base.clone(), first.clone(), second.clone()]).unwrap();
+        let summary: Vec<(&str, Vec<(&str, &str)>)> = collisions
+            .iter()
+            .map(|c| (c.map.as_str(), c.definitions.iter().map(|d| (d.file.as_str(), d.name.as_str())).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("MAP01", vec![(base.as_str(), "Entryway"), (first.as_str(), "Sunrise"), (second.as_str(), "Sunset")])]
+        );
+    }
+
+    #[test]
+    fn reads_one_lump_by_name_and_index() {
+        let wad = write_temp("onelump.wad", &build_wad(b"PWAD", &[("DECORATE", b"first"), ("MAP01", b""), ("decorate", b"second")]));
+        assert_eq!(read_wad_lump(&wad, "decorate", None).unwrap(), b"second");
+        assert_eq!(read_wad_lump(&wad, "DECORATE", Some(0)).unwrap(), b"first");
+        assert!(read_wad_lump(&wad, "DECORATE", Some(1)).unwrap_err().contains("is not DECORATE"));
+        assert!(read_wad_lump(&wad, "MAPINFO", None).unwrap_err().contains("No lump MAPINFO"));
+
+        let pk3 = write_temp("onelump.pk3", &build_zip(&[("mapinfo.txt", b"map MAP01"), ("maps/mapinfo.txt", b"nested")]));
+        assert_eq!(read_wad_lump(&pk3, "MAPINFO", None).unwrap(), b"map MAP01");
+        assert_eq!(read_wad_lump(&pk3, "mapinfo.txt", None).unwrap(), b"map MAP01");
+        assert_eq!(read_wad_lump(&pk3, "maps/mapinfo.txt", None).unwrap(), b"nested");
+        let nested = write_temp("onelump_nested.pk3", &build_zip(&[("maps/mapinfo.txt", b"nested")]));
+        assert!(read_wad_lump(&nested, "MAPINFO", None).unwrap_err().contains("No lump MAPINFO"));
+    }
+
+    #[test]
+    fn extracts_sprite_frames_from_namespaces() {
+        use crate::picture::test_util::{picture, playpal};
+        let pic = picture();
+        let wad = write_temp(
+            "spr.wad",
+            &build_wad(
+                b"PWAD",
+                &[
+                    ("PLAYPAL", &playpal()),
+                    ("TROOA1", b"outside the namespace"),
+                    ("S_START", b""),
+                    ("TROOB1", &pic),
+                    ("TROOA2A8", &pic),
+                    ("POSSA1", &pic),
+                    ("TROOC1", b"junk"),
+                    ("S_END", b""),
+                ],
+            ),
+        );
+        let names: Vec<String> = extract_sprites(&wad, "troo", None).unwrap().into_iter().map(|f| f.name).collect();
+        assert_eq!(names, vec!["TROOA2A8", "TROOB1"]);
+        assert!(extract_sprites(&wad, "TRO", None).unwrap_err().contains("four characters"));
+
+        let palette = crate::picture::palette_from_playpal(&playpal()).unwrap();
+        let png = crate::picture::encode_png(&crate::picture::decode_picture(&pic, &palette).unwrap()).unwrap();
+        let pk3 = write_temp("spr.pk3", &build_zip(&[("sprites/monsters/trooa1.png", &png), ("graphics/troob1.png", &png)]));
+        let frames = extract_sprites(&pk3, "TROO", None).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!((frames[0].name.as_str(), frames[0].width, frames[0].height), ("TROOA1", 2, 3));
+    }
+
+    #[test]
+    fn reads_palette_preferring_the_pwad_override() {
+        use crate::picture::test_util::playpal;
+        let mut custom = playpal();
+        custom[..3].copy_from_slice(&[1, 2, 3]);
+        let iwad = write_temp("pal_iwad.wad", &build_wad(b"IWAD", &[("PLAYPAL", &playpal())]));
+        let plain = write_temp("pal_plain.wad", &build_wad(b"PWAD", &[("MAP01", b"")]));
+        let custom_wad = write_temp("pal_custom.wad", &build_wad(b"PWAD", &[("PLAYPAL", &custom)]));
+
+        let own = read_palette(&custom_wad, Some(&iwad)).unwrap();
+        assert_eq!(own[0], [1, 2, 3]);
+        let fallback = read_palette(&plain, Some(&iwad)).unwrap();
+        assert_eq!(fallback, crate::picture::palette_from_playpal(&playpal()).unwrap());
+        assert!(read_palette(&plain, None).is_err());
+        assert!(read_palette(&plain, Some(&plain)).unwrap_err().contains("No PLAYPAL"));
+    }
+
+    #[test]
+    fn lists_maps_without_mapinfo_names() {
+        let mapinfo = b"map MAP02 \"Named\" { }";
+        let textmap = b"thing { type = 3004; skill4 = true; single = true; }";
+        // One zombieman on every skill; one UV-only imp; one secret sector.
+        let things: Vec<u8> = [[0u16, 0, 0, 3004, 7], [0, 0, 0, 3001, 4]].iter().flatten().flat_map(|v| v.to_le_bytes()).collect();
+        let mut sectors = vec![0u8; 26];
+        sectors[22] = 9;
+        let wad = build_wad(
+            b"PWAD",
+            &[
+                ("MAP01", b""),
+                ("THINGS", &things),
+                ("LINEDEFS", b""),
+                ("SECTORS", &sectors),
+                ("MAP02", b""),
+                ("TEXTMAP", textmap),
+                ("ENDMAP", b""),
+                ("CUSTOM", b""),
+                ("THINGS", b""),
+                ("BEHAVIOR", b""),
+                ("MAPINFO", mapinfo),
+                ("MAP03", b""),
+                ("DEMO1", b""),
+            ],
+        );
+        let path = write_temp("maps.wad", &wad);
+        let maps = list_maps(&path, 4).unwrap();
+        let stats = |monsters, secrets| Some(map_stats::MapStats { monsters, items: 0, secrets });
+        let expected = vec![
+            MapEntry { id: "MAP01".into(), name: None, format: Some(MapFormat::Doom), stats: stats(2, 1), cluster: None },
+            MapEntry { id: "MAP02".into(), name: Some("Named".into()), format: Some(MapFormat::Udmf), stats: stats(1, 0), cluster: None },
+            MapEntry { id: "CUSTOM".into(), name: None, format: Some(MapFormat::Hexen), stats: stats(0, 0), cluster: None },
+            MapEntry { id: "MAP03".into(), name: None, format: None, stats: None, cluster: None },
+        ];
+        assert_eq!(maps, expected);
+        assert_eq!(list_maps(&path, 2).unwrap()[0].stats, stats(1, 1));
+
+        let inner = build_wad(b"PWAD", &[("MAP01", b""), ("THINGS", b"")]);
+        let pk3 = write_temp("maps.pk3", &build_zip(&[("maps/e1m1.wad", &inner), ("maps/sub/x.wad", &inner)]));
+        let maps: Vec<(String, Option<MapFormat>)> =
+            list_maps(&pk3, 4).unwrap().into_iter().map(|m| (m.id, m.format)).collect();
+        assert_eq!(maps, vec![("E1M1".into(), Some(MapFormat::Doom)), ("MAP01".into(), Some(MapFormat::Doom))]);
+    }
+
+    #[test]
+    fn lists_hexen_levels_by_hub() {
+        let mapinfo = b"map 1 \"WINNOWING HALL\"\nwarptrans 1\nnext 2\ncluster 1\n\n\
+                        map 2 \"SEVEN PORTALS\"\nwarptrans 2\nnext 3\ncluster 2\n\n\
+                        map 3 \"GUARDIAN OF ICE\"\nwarptrans 3\ncluster 2\n";
+        let map = |id: &'static str| [(id, &b""[..]), ("THINGS", b""), ("BEHAVIOR", b"")];
+        let mut lumps: Vec<(&str, &[u8])> = [map("MAP01"), map("MAP02"), map("MAP03")].concat();
+        lumps.extend([("TITLE", &b""[..]), ("MAPINFO", mapinfo)]);
+        let path = write_temp("hexen_hubs.wad", &build_wad(b"IWAD", &lumps));
+
+        let listing = list_levels(&path, 4).unwrap();
+        assert_eq!(listing.game.as_deref(), Some("hexen"));
+        assert_eq!(listing.maps[1].name.as_deref(), Some("SEVEN PORTALS"));
+        assert_eq!(
+            listing.hubs,
+            vec![
+                Hub { cluster: 1, maps: vec!["MAP01".into()] },
+                Hub { cluster: 2, maps: vec!["MAP02".into(), "MAP03".into()] },
+            ]
+        );
+
+        // Doom's clusters are intermission groups, not hubs.
+        let doom = build_wad(b"PWAD", &[("MAP01", b""), ("THINGS", b""), ("MAPINFO", b"map MAP01 \"A\" { cluster = 5 }")]);
+        let listing = list_levels(&write_temp("doom_clusters.wad", &doom), 4).unwrap();
+        assert_eq!((listing.game.as_deref(), listing.maps[0].cluster), (Some("doom2"), Some(5)));
+        assert!(listing.hubs.is_empty());
+    }
+
+    #[test]
+    fn looks_up_language_names() {
+        let zmapinfo = b"map MAP01 lookup \"MAP01NAME\" { }\nmap MAP02 \"$MAP02NAME\" { }\nmap MAP03 \"$NOPE\" { }\n\
+                         episode MAP01 { name = \"$EPI1\" }\n";
+        let language = b"[enu default]\nMAP01NAME = \"Entryway\";\nMAP02NAME = \"Underhalls\";\nEPI1 = \"Act I\";\n\
+                         [de]\nMAP01NAME = \"Eingang\";\n";
+        let path = write_temp("language.pk3", &build_zip(&[("zmapinfo.txt", zmapinfo), ("language.txt", language)]));
+        let names = |locale| -> Vec<Option<String>> { extract_levels(&path, locale).unwrap().into_iter().map(|l| l.name).collect() };
+        let some = |n: &str| Some(n.to_string());
+        assert_eq!(names(None), vec![some("Entryway"), some("Underhalls"), some("$NOPE")]);
+        assert_eq!(names(Some("de")), vec![some("Eingang"), some("Underhalls"), some("$NOPE")]);
+        assert_eq!(extract_episodes(&path, None).unwrap().episodes[0].name, some("Act I"));
+        assert_eq!(extract_level_names(&path).unwrap().get("MAP02"), some("Underhalls").as_ref());
+    }
+
+    #[test]
+    fn resolves_cluster_texts_from_language_and_lumps() {
+        let zmapinfo = b"cluster 1 { exittext = lookup, \"C1TEXT\" }\n\
+                         cluster 2 { exittext = \"$C2TEXT\" }\n\
+                         cluster 3\n{\n  entertext = \"STORY3\"\n  entertextislump\n}\n\
+                         cluster 1 { exittext = lookup, \"C1TEXT_NEW\" }\n";
+        let language = b"[enu default]\nC1TEXT_NEW = \"The moon base\\n\" \"is clean.\";\n";
+        let pk3 = build_zip(&[
+            ("zmapinfo.txt", zmapinfo),
+            ("language.txt", language),
+            ("story3.txt", b"You enter the city."),
+        ]);
+        let clusters = extract_cluster_texts(&write_temp("clusters.pk3", &pk3), None).unwrap();
+        let texts: Vec<(u32, Option<&str>, Option<&str>)> =
+            clusters.iter().map(|c| (c.id, c.enter_text.as_deref(), c.exit_text.as_deref())).collect();
+        assert_eq!(
+            texts,
+            vec![
+                (1, None, Some("The moon base\nis clean.")),
+                (2, None, Some("$C2TEXT")),
+                (3, Some("You enter the city."), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_dehacked_names_under_mapinfo() {
+        let deh = b"[STRINGS]\nHUSTR_1 = level 1: Deh One\nHUSTR_2 = level 2: Deh Two\n";
+        let mapinfo = b"map MAP02 \"Mapinfo Two\" { }";
+        let wad = build_wad(b"PWAD", &[("DEHACKED", deh), ("MAPINFO", mapinfo)]);
+        let path = write_temp("deh_names.wad", &wad);
+        let names = extract_level_names(&path).unwrap();
+        assert_eq!(names["MAP01"], "Deh One");
+        assert_eq!(names["MAP02"], "Mapinfo Two");
+
+        let patch = write_temp("extra.bex", b"[STRINGS]\nHUSTR_1 = External One\nHUSTR_3 = Three\n");
+        let names = extract_level_names_with_patches(&path, &[&patch]).unwrap();
+        assert_eq!(names["MAP01"], "External One");
+        assert_eq!(names["MAP03"], "Three");
+        assert!(extract_level_names_with_patches(&path, &["/nonexistent.deh"]).is_err());
+    }
+
+    #[test]
+    fn finds_embedded_descriptions() {
+        let wad = build_wad(b"PWAD", &[("MAP01", b""), ("INFO", b"Second\r\n"), ("README", b"Read me\r\nplease")]);
+        let d = extract_description(&write_temp("desc.wad", &wad)).unwrap().unwrap();
+        assert_eq!((d.source.as_str(), d.text.as_str()), ("README", "Read me\nplease"));
+        let bare = write_temp("desc_none.wad", &build_wad(b"PWAD", &[("README", b"\0\x01binary")]));
+        assert_eq!(extract_description(&bare).unwrap(), None);
+
+        // idgames: the .txt named after the WAD, not a definition lump.
+        let zip = build_zip(&[("mapinfo.txt", b"map MAP01 {}"), ("sc2.txt", b"Sunder"), ("sc2.wad", &wad)]);
+        let pk3 = write_temp("desc.pk3", &zip);
+        assert_eq!(extract_description(&pk3).unwrap().unwrap().source, "sc2.txt");
+        let pk3 = write_temp("desc2.pk3", &build_zip(&[("mapinfo.txt", b"map MAP01 {}")]));
+        assert_eq!(extract_description(&pk3).unwrap(), None);
+        // Falls back to the nested WAD's lump.
+        let zip = write_temp("desc.zip", &build_zip(&[("maps/x.wad", &wad)]));
+        assert_eq!(extract_description(&zip).unwrap().unwrap().text, "Read me\nplease");
+    }
+}
@@ -2,10 +2,11 @@ import { ref } from "vue";
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { readDir, mkdir } from "@tauri-apps/plugin-fs";
+import { save as saveDialog } from "@tauri-apps/plugin-dialog";
 import { IWADS, type Iwad } from "../lib/schema";
 import type { SkillLevel } from "../lib/statsSchema";
 import { useSettings } from "./useSettings";
-import { useGameplayLog } from "./useGameplayLog";
+import { useGameplayLog, type LogLine } from "./useGameplayLog";
 import { useLibrary } from "./useLibrary";
 import { isExistsError } from "../lib/errors";
 
@@ -16,27 +17,60 @@ interface SessionInfo {
   startedAt: Date;
 }
 
+// Finished-session log from the Rust side. exitCode is null when the engine
+// was killed by a signal (Unix), in which case signal carries its number.
+interface EngineLog {
+  sessionId: number;
+  startedAt: string; // ISO-8601 UTC wall-clock time of the spawn; line times are ms after it
+  lines: Array<LogLine>;
+  droppedLines: number; // earlier lines dropped by the per-session cap
+  exitCode: number | null;
+  signal: number | null;
+}
+
 // Singleton state - shared across all components
 const isRunning = ref(false);
 const availableIwads = ref<Iwad[]>([]);
 const currentSession = ref<SessionInfo | null>(null);
 const iwadFilenames = new Map<Iwad, string>(); // e.g., "doom" -> "doom.wad"
+// Session id of the latest launch; exit events from earlier runs are stale.
+// The exit of a short-lived run can arrive before launch_gzdoom resolves,
+// so the last exited id is remembered too.
+let currentSessionId = 0;
+let lastExitedSessionId = 0;
+
+// Payload of "gzdoom-exited". lineCount includes lines dropped by the cap.
+interface EngineExit {
+  sessionId: number;
+  exitCode: number | null;
+  signal: number | null;
+  runtimeMs: number;
+  lineCount: number;
+}
 
 // The Rust side emits "gzdoom-exited" from the thread that wait()s on the
 // spawned engine process — no polling needed. Registered once, before the
 // first launch.
 let exitListener: UnlistenFn | null = null;
 
-async function handleEngineExit() {
+async function handleEngineExit(event: { payload: EngineExit }) {
+  const { sessionId, exitCode, signal, runtimeMs, lineCount } = event.payload;
+  if (sessionId < currentSessionId) return;
+  if (exitCode !== 0) {
+    console.warn(
+      `[gzdoom] session ${sessionId} ended with ${exitCode ?? `signal ${signal}`} after ${runtimeMs} ms, ${lineCount} lines`,
+    );
+  }
+  lastExitedSessionId = sessionId;
   isRunning.value = false;
   const session = currentSession.value;
   currentSession.value = null;
   if (!session) return;
   try {
-    const log = await invoke<Array<[number, string]> | null>("get_gzdoom_log");
-    if (log && log.length > 0) {
+    const log = await invoke<EngineLog | null>("get_gzdoom_log", { sessionId });
+    if (log && log.lines.length > 0) {
       const { saveGameplayLog } = useGameplayLog();
-      await saveGameplayLog(session.slug, session.skill, log, session.startedAt, new Date());
+      await saveGameplayLog(session.slug, session.skill, log.lines, session.startedAt, new Date());
     }
   } catch (e) {
     console.error("Failed to save gameplay log:", e);
@@ -129,9 +163,38 @@ export function useGZDoom() {
     }
 
     // Use Rust command to launch GZDoom (supports custom paths)
-    await invoke("launch_gzdoom", { gzdoomPath, args });
-    isRunning.value = true;
+    currentSessionId = await invoke<number>("launch_gzdoom", { gzdoomPath, args });
+    isRunning.value = lastExitedSessionId !== currentSessionId;
+  }
+
+  // Ask where to save the latest session's console log, then let the Rust
+  // side write it. Resolves to the path written, or null if cancelled.
+  async function saveLog(format: "text" | "json" = "text"): Promise<string | null> {
+    if (!currentSessionId) throw new Error("No engine session to save");
+    const path = await saveDialog({
+      defaultPath: format === "json" ? "gzdoom-log.json" : "gzdoom-log.log",
+      filters: [format === "json"
+        ? { name: "JSON", extensions: ["json"] }
+        : { name: "Log", extensions: ["log", "txt"] }],
+    });
+    if (!path) return null;
+    return invoke<string>("save_gzdoom_log", { sessionId: currentSessionId, path, format });
+  }
+
+  // Put an IWAD into the library's iwads/ folder under its game's name and
+  // refresh the available IWADs. Symlink falls back to a copy where the OS
+  // refuses links; the result's warning says so.
+  async function importIwad(
+    src: string,
+    mode: "copy" | "symlink" | "move" = "copy"
+  ): Promise<{ path: string; mode: string; warning: string | null }> {
+    const imported = await invoke<{ path: string; mode: string; warning: string | null }>(
+      "import_iwad", { src, iwadsDir: lib.iwadsDir(), mode }
+    );
+    if (imported.warning) console.warn("[importIwad]", imported.warning);
+    await detectIwads();
+    return imported;
   }
 
-  return { isRunning, availableIwads, detectIwads, launch };
+  return { isRunning, availableIwads, detectIwads, launch, saveLog, importIwad };
 }
@@ -14,6 +14,12 @@ import {
 } from "../lib/gameplayLogSchema";
 import type { SkillLevel } from "../lib/statsSchema";
 
+// Which engine output stream a log line came from.
+export type LogStream = "stdout" | "stderr";
+
+// One captured log line: [time_ms, text, stream].
+export type LogLine = [number, string, LogStream];
+
 // Re-export for convenience
 export type { GameplayLog, GameEvent } from "../lib/gameplayLogSchema";
 export { getDeathCount, getLevelsVisited } from "../lib/gameplayLogSchema";
@@ -83,9 +89,9 @@ export function useGameplayLog() {
 
   /**
    * Parse raw log output from GZDoom into events array
-   * Input: array of [time_ms, text] tuples from Rust backend
+   * Input: array of [time_ms, text, stream] tuples from Rust backend
    */
-  function parseRawLog(lines: Array<[number, string]>): GameEvent[] {
+  function parseRawLog(lines: Array<LogLine>): GameEvent[] {
     return lines
       .filter(([, text]) => text.trim().length > 0) // Skip empty lines
       .map(([time_ms, text]) => parseLogLine(text, time_ms));
@@ -97,7 +103,7 @@ export function useGameplayLog() {
   async function saveGameplayLog(
     slug: string,
     skill: SkillLevel,
-    rawLines: Array<[number, string]>,
+    rawLines: Array<LogLine>,
     startedAt: Date,
     endedAt: Date
   ): Promise<string> {
@@ -4,11 +4,43 @@
  */
 
 /**
- * Extract message from any thrown value (Error, string, or object).
+ * Kinds of the structured errors launch and parser commands reject with
+ * (launch_error::LaunchError on the Rust side).
+ */
+export type LaunchErrorKind =
+  | "notFound"
+  | "notExecutable"
+  | "unknownEngine"
+  | "invalidIwad"
+  | "invalidWad"
+  | "alreadyRunning"
+  | "spawnFailed"
+  | "other";
+
+export interface LaunchError {
+  kind: LaunchErrorKind;
+  /** The file the error is about; absent for "other". */
+  path?: string;
+  message: string;
+}
+
+/**
+ * The thrown value as a LaunchError, or null for anything else.
+ */
+export function asLaunchError(e: unknown): LaunchError | null {
+  if (typeof e !== "object" || e === null) return null;
+  const { kind, message } = e as Record<string, unknown>;
+  return typeof kind === "string" && typeof message === "string" ? (e as LaunchError) : null;
+}
+
+/**
+ * Extract message from any thrown value (Error, string, LaunchError, or object).
  */
 export function getErrorMessage(e: unknown): string {
   if (e instanceof Error) return e.message;
   if (typeof e === "string") return e;
+  const launchError = asLaunchError(e);
+  if (launchError) return launchError.message;
   return JSON.stringify(e);
 }
 
@@ -32,3 +64,28 @@ export function isExistsError(e: unknown): boolean {
   const msg = getErrorMessage(e).toLowerCase();
   return msg.includes("eexist") || msg.includes("already exists");
 }
+
+/**
+ * Check if a launch with checkRunning failed because the engine is already
+ * open.
+ */
+export function isAlreadyRunningError(e: unknown): boolean {
+  return asLaunchError(e)?.kind === "alreadyRunning";
+}
+
+/**
+ * Check if a launch failed because the IWAD is missing or isn't one, so the
+ * UI can offer to locate it.
+ */
+export function isInvalidIwadError(e: unknown): boolean {
+  return asLaunchError(e)?.kind === "invalidIwad";
+}
+
+/**
+ * Check if the running check failed because no way of listing processes
+ * works on this system (matches process::DETECTION_UNSUPPORTED), so the UI
+ * can hide the indicator instead of showing an error.
+ */
+export function isDetectionUnsupportedError(e: unknown): boolean {
+  return getErrorMessage(e).startsWith("Process detection unsupported");
+}